@@ -1,6 +1,7 @@
 const loginSection = document.getElementById("login-section");
 const twoFASection = document.getElementById("2fa-section");
 const signupSection = document.getElementById("signup-section");
+const resetSection = document.getElementById("reset-section");
 
 const signupLink = document.getElementById("signup-link");
 const twoFALoginLink = document.getElementById("2fa-login-link");
@@ -30,6 +31,37 @@ signupLoginLink.addEventListener("click", (e) => {
     signupSection.style.display = "none";
 });
 
+// Invitation links point here with ?invite=<code>; open the signup form for them
+const invitationCode = new URLSearchParams(window.location.search).get("invite");
+if (invitationCode) {
+    loginSection.style.display = "none";
+    twoFASection.style.display = "none";
+    signupSection.style.display = "block";
+}
+
+// Password reset emails link here with ?reset=<token>; ask for the new password
+const resetToken = new URLSearchParams(window.location.search).get("reset");
+if (resetToken) {
+    loginSection.style.display = "none";
+    twoFASection.style.display = "none";
+    signupSection.style.display = "none";
+    resetSection.style.display = "block";
+}
+
+// The terms version signup requires, if the server enforces one
+let currentTerms = { version: null, url: null };
+fetch('/terms')
+    .then(response => response.json())
+    .then(terms => {
+        currentTerms = terms;
+        if (terms.version) {
+            document.getElementById("terms-check").style.display = "block";
+            if (terms.url) {
+                document.getElementById("terms-link").href = terms.url;
+            }
+        }
+    });
+
 // -----------------------------------------------------
 
 const loginForm = document.getElementById("login-form");
@@ -39,17 +71,26 @@ const loginErrAlter = document.getElementById("login-err-alert");
 loginButton.addEventListener("click", (e) => {
     e.preventDefault();
 
-    const email = loginForm.email.value;
-    const password = loginForm.password.value;
+    submitLogin(loginForm.email.value, loginForm.password.value, null);
+});
 
+function submitLogin(email, password, acceptedTermsVersion) {
     fetch('/login', {
         method: 'POST',
         headers: {
             'Content-Type': 'application/json',
         },
-        body: JSON.stringify({ email, password }),
+        body: JSON.stringify({ email, password, acceptedTermsVersion }),
     }).then(response => {
-        if (response.status === 206) {
+        if (response.status === 403 && response.headers.get("Content-Type") === "application/json") {
+            // The terms changed since this user last accepted them
+            response.json().then(data => {
+                const where = data.termsUrl ? ` at ${data.termsUrl}` : "";
+                if (confirm(`Our terms of service have changed (version ${data.termsVersion}). Read them${where} and press OK to accept them and log in.`)) {
+                    submitLogin(email, password, data.termsVersion);
+                }
+            });
+        } else if (response.status === 206) {
             TwoFAForm.email.value = email;
             response.json().then(data => {
                 TwoFAForm.login_attempt_id.value = data.loginAttemptId;
@@ -79,7 +120,7 @@ loginButton.addEventListener("click", (e) => {
             });
         }
     });
-});
+}
 
 const signupForm = document.getElementById("signup-form");
 const signupButton = document.getElementById("signup-form-submit");
@@ -91,18 +132,20 @@ signupButton.addEventListener("click", (e) => {
     const email = signupForm.email.value;
     const password = signupForm.password.value;
     const requires2FA = signupForm.twoFA.checked;
+    const acceptedTermsVersion = signupForm.acceptTerms.checked ? currentTerms.version : null;
 
     fetch('/signup', {
         method: 'POST',
         headers: {
             'Content-Type': 'application/json',
         },
-        body: JSON.stringify({ email, password, requires2FA }),
+        body: JSON.stringify({ email, password, requires2FA, invitationCode, acceptedTermsVersion }),
     }).then(response => {
         if (response.ok) {
             signupForm.email.value = "";
             signupForm.password.value = "";
             signupForm.twoFA.checked = false;
+            signupForm.acceptTerms.checked = false;
             signupErrAlter.style.display = "none";
             alert("You have successfully created a user.");
             loginSection.style.display = "block";
@@ -161,4 +204,57 @@ TwoFAButton.addEventListener("click", (e) => {
             });
         }
     });
-});
\ No newline at end of file
+});
+
+const resetForm = document.getElementById("reset-form");
+const resetButton = document.getElementById("reset-form-submit");
+const resetErrAlter = document.getElementById("reset-err-alert");
+const resetLoginLink = document.getElementById("reset-login-link");
+
+resetLoginLink.addEventListener("click", (e) => {
+    e.preventDefault();
+
+    loginSection.style.display = "block";
+    resetSection.style.display = "none";
+});
+
+resetButton.addEventListener("click", (e) => {
+    e.preventDefault();
+
+    const newPassword = resetForm.new_password.value;
+
+    fetch('/password-reset/confirm', {
+        method: 'POST',
+        headers: {
+            'Content-Type': 'application/json',
+        },
+        body: JSON.stringify({ token: resetToken, newPassword }),
+    }).then(response => {
+        if (response.ok) {
+            resetForm.new_password.value = "";
+            resetErrAlter.style.display = "none";
+            alert("Your password has been reset. Please log in.");
+            loginSection.style.display = "block";
+            resetSection.style.display = "none";
+        } else {
+            if (response.headers.get("Content-Type") === "application/json") {
+                // The password broke the policy; list every rule it broke
+                response.json().then(data => {
+                    const rules = data.violations.map(v => `<li>${v}</li>`).join("");
+                    resetErrAlter.innerHTML = `<span><strong>Error: </strong>${data.message}</span><ul class="mb-0">${rules}</ul>`;
+                    resetErrAlter.style.display = "block";
+                });
+                return;
+            }
+            // Other reset errors come back as plain text
+            response.text().then(error_msg => {
+                if (error_msg !== "") {
+                    resetErrAlter.innerHTML = `<span><strong>Error: </strong>${error_msg}</span>`;
+                    resetErrAlter.style.display = "block";
+                } else {
+                    resetErrAlter.style.display = "none";
+                }
+            });
+        }
+    });
+});
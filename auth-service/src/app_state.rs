@@ -2,8 +2,14 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use welds::connections::any::AnyClient;
 
-use crate::domain::{EmailClient, TwoFACodeStore, UserStore};
-use crate::services::TokenService;
+use crate::domain::{
+    Email, EmailClient, InvitationStore, OneTimeTokenStore, TermsStore, TrustedDeviceStore,
+    TwoFACodeStore, UserStore,
+};
+use crate::services::{
+    BreachedPasswordFilter, DisposableDomainList, HashmapInvitationStore, HashmapOneTimeTokenStore,
+    HashmapTermsStore, HashmapTrustedDeviceStore, TokenService,
+};
 use crate::utils::Config;
 
 // Using type aliases to improve readability!
@@ -12,6 +18,12 @@ pub type UserStoreType = Arc<RwLock<dyn UserStore>>;
 pub type TokenServiceType = Arc<RwLock<TokenService>>;
 pub type ConfigType = Arc<RwLock<Config>>;
 pub type EmailClientType = Arc<RwLock<dyn EmailClient>>;
+pub type TrustedDeviceStoreType = Arc<RwLock<dyn TrustedDeviceStore>>;
+pub type OneTimeTokenStoreType = Arc<RwLock<dyn OneTimeTokenStore>>;
+pub type InvitationStoreType = Arc<RwLock<dyn InvitationStore>>;
+pub type TermsStoreType = Arc<RwLock<dyn TermsStore>>;
+pub type BreachedPasswordFilterType = Arc<BreachedPasswordFilter>;
+pub type DisposableDomainListType = Arc<DisposableDomainList>;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -21,6 +33,12 @@ pub struct AppState {
     pub twofa_token_store: TwoFACodeStoreType,
     pub email_client: EmailClientType,
     pub db_client: AnyClient,
+    pub trusted_device_store: TrustedDeviceStoreType,
+    pub one_time_token_store: OneTimeTokenStoreType,
+    pub invitation_store: InvitationStoreType,
+    pub terms_store: TermsStoreType,
+    pub breached_passwords: Option<BreachedPasswordFilterType>,
+    pub disposable_domains: Option<DisposableDomainListType>,
 }
 
 impl AppState {
@@ -39,6 +57,65 @@ impl AppState {
             twofa_token_store,
             email_client,
             db_client,
+            trusted_device_store: Arc::new(RwLock::new(HashmapTrustedDeviceStore::default())),
+            one_time_token_store: Arc::new(RwLock::new(HashmapOneTimeTokenStore::default())),
+            invitation_store: Arc::new(RwLock::new(HashmapInvitationStore::default())),
+            terms_store: Arc::new(RwLock::new(HashmapTermsStore::default())),
+            breached_passwords: None,
+            disposable_domains: None,
         }
     }
+
+    /// Keep trusted devices in `store` instead of in memory.
+    pub fn with_trusted_device_store(mut self, store: TrustedDeviceStoreType) -> Self {
+        self.trusted_device_store = store;
+        self
+    }
+
+    /// Keep one-time tokens in `store` instead of in memory.
+    pub fn with_one_time_token_store(mut self, store: OneTimeTokenStoreType) -> Self {
+        self.one_time_token_store = store;
+        self
+    }
+
+    /// Keep invitations in `store` instead of in memory.
+    pub fn with_invitation_store(mut self, store: InvitationStoreType) -> Self {
+        self.invitation_store = store;
+        self
+    }
+
+    /// Keep terms acceptances in `store` instead of in memory.
+    pub fn with_terms_store(mut self, store: TermsStoreType) -> Self {
+        self.terms_store = store;
+        self
+    }
+
+    /// Refuse new passwords found in `filter`.
+    pub fn with_breached_passwords(mut self, filter: BreachedPasswordFilterType) -> Self {
+        self.breached_passwords = Some(filter);
+        self
+    }
+
+    /// Refuse signups from domains in `list`.
+    pub fn with_disposable_domains(mut self, list: DisposableDomainListType) -> Self {
+        self.disposable_domains = Some(list);
+        self
+    }
+
+    /// Whether a newly chosen password appears in the breached-password
+    /// corpus. Always false when no corpus is configured.
+    pub fn is_breached_password(&self, password: &str) -> bool {
+        self.breached_passwords
+            .as_ref()
+            .is_some_and(|filter| filter.contains_password(password))
+    }
+
+    /// Whether `email` may be used for an account: its domain is inside the
+    /// configured signup domain policy and not a throwaway-mailbox provider.
+    pub async fn permits_email_domain(&self, email: &Email) -> bool {
+        self.config
+            .read()
+            .await
+            .permits_email_domain(email, self.disposable_domains.as_deref())
+    }
 }
@@ -0,0 +1,518 @@
+//! Operator CLI for the auth service. Reads the same environment as the
+//! server and talks to the same database and redis, so changes take effect
+//! for a running server immediately.
+//!
+//! ```text
+//! auth-admin user create <email> [--requires-2fa] [--verified] [--force] < password
+//! auth-admin user disable <email> [--reason <text>]
+//! auth-admin user delete <email>
+//! auth-admin user reset-mfa <email>
+//! auth-admin user import <file> [--format jsonl|csv] [--rejects <file>] [--dry-run]
+//! auth-admin user export <file|-> [--format jsonl|csv]
+//! auth-admin sessions list <email>
+//! auth-admin sessions revoke <email> [--session <id>]
+//! auth-admin migrate up
+//! auth-admin migrate down --yes
+//! auth-admin breached-passwords build <hibp.txt> <out> [--false-positive-rate <p>]
+//! auth-admin config
+//! ```
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use auth_service::domain::{
+    AccountStatus, Email, Password, RefreshError, TermsStore, TrustedDeviceStore, User, UserStore,
+    UserStoreError,
+};
+use auth_service::services::user_transfer::{self, ImportOptions, TransferFormat, MAX_BATCH_SIZE};
+use auth_service::services::{
+    BreachedPasswordFilter, DisposableDomainList, RedisRefreshStore, RedisService,
+    RedisTrustedDeviceStore, SqlTermsStore, SqlUserStore, TokenService,
+};
+use auth_service::utils::Config;
+use auth_service::{get_db_pool, migrations};
+use clap::{Parser, Subcommand};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+use welds::connections::any::AnyClient;
+
+#[derive(Parser)]
+#[command(
+    name = "auth-admin",
+    about = "Manage auth-service users, sessions and schema"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create, disable or delete accounts
+    #[command(subcommand)]
+    User(UserCommand),
+    /// Inspect and revoke refresh sessions
+    #[command(subcommand)]
+    Sessions(SessionsCommand),
+    /// Apply or roll back database migrations
+    #[command(subcommand)]
+    Migrate(MigrateCommand),
+    /// Build the filter read from BREACHED_PASSWORDS_PATH
+    #[command(subcommand)]
+    BreachedPasswords(BreachedPasswordsCommand),
+    /// Print the effective configuration with secrets redacted
+    Config,
+}
+
+#[derive(Subcommand)]
+enum UserCommand {
+    /// Create an account; the password is read from the first line of stdin
+    Create {
+        email: String,
+        #[arg(long = "requires-2fa")]
+        requires_mfa: bool,
+        /// Mark the email address as already verified
+        #[arg(long)]
+        verified: bool,
+        /// Create the account even if signup would refuse its email domain
+        #[arg(long)]
+        force: bool,
+    },
+    /// Block sign-in and revoke every session of the account
+    Disable {
+        email: String,
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    /// Delete the account, its sessions and its trusted devices
+    Delete { email: String },
+    /// Turn off 2FA for the account and forget its trusted devices
+    ResetMfa { email: String },
+    /// Load users with existing password hashes from a JSONL or CSV file.
+    /// Rows with an address signup would refuse are rejected.
+    Import {
+        file: PathBuf,
+        /// Defaults to the file extension
+        #[arg(long)]
+        format: Option<TransferFormat>,
+        /// Where rows that were not imported go; defaults to
+        /// `<file>.rejects.<ext>` next to the input
+        #[arg(long)]
+        rejects: Option<PathBuf>,
+        /// Check every row without writing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Users written per transaction
+        #[arg(long, default_value_t = 500)]
+        batch_size: usize,
+    },
+    /// Write every user, with password hash, to a JSONL or CSV file
+    Export {
+        /// Output file, or `-` for stdout
+        file: PathBuf,
+        /// Defaults to the file extension, or jsonl for stdout
+        #[arg(long)]
+        format: Option<TransferFormat>,
+    },
+}
+
+#[derive(Subcommand)]
+enum SessionsCommand {
+    /// List the active session ids of an account
+    List { email: String },
+    /// Revoke one session, or all of them when `--session` is omitted
+    Revoke {
+        email: String,
+        #[arg(long)]
+        session: Option<Uuid>,
+    },
+}
+
+#[derive(Subcommand)]
+enum MigrateCommand {
+    /// Apply all pending migrations
+    Up,
+    /// Roll back every migration, dropping all account data
+    Down {
+        /// Confirm that all data may be dropped
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum BreachedPasswordsCommand {
+    /// Build a filter from a Have I Been Pwned SHA-1 hash list
+    Build {
+        /// One `HASH:count` line per password, as downloaded
+        hibp: PathBuf,
+        out: PathBuf,
+        /// Share of passwords outside the list that are reported as breached
+        #[arg(long, default_value_t = 0.001)]
+        false_positive_rate: f64,
+    },
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    env_logger::init();
+    let cli = Cli::parse();
+    let config = Config::default();
+
+    let result = match (cli.command, config) {
+        // Works on local files only, so the service environment is not needed
+        (Command::BreachedPasswords(command), _) => run_breached_passwords(command),
+        (_, Err(e)) => Err(format!("failed to load config: {}", e)),
+        (Command::User(command), Ok(config)) => run_user(command, config).await,
+        (Command::Sessions(command), Ok(config)) => run_sessions(command, config).await,
+        (Command::Migrate(command), Ok(config)) => run_migrate(command, &config).await,
+        (Command::Config, Ok(config)) => {
+            for (name, value) in config.redacted_settings() {
+                println!("{}={}", name, value);
+            }
+            Ok(())
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run_user(command: UserCommand, config: Config) -> Result<(), String> {
+    let db_client = connect(&config).await?;
+    let mut user_store =
+        SqlUserStore::new(db_client.clone()).with_argon2_params(config.argon2_params().clone());
+    let redis_service = Arc::new(RedisService::new(config.redis_host()));
+
+    match command {
+        UserCommand::Create {
+            email,
+            requires_mfa,
+            verified,
+            force,
+        } => {
+            let email = parse_email(email)?;
+            if !force && !email_domain_gate(&config)?(&email) {
+                return Err(
+                    "signups from this email domain are not allowed, pass --force to create \
+                     the account anyway"
+                        .to_owned(),
+                );
+            }
+            let password = Password::parse_new(read_password()?, config.password_policy(), &email)
+                .map_err(|violations| {
+                    format!("password does not meet the requirements: {}", violations)
+                })?;
+            if let Some(path) = config.breached_passwords_path() {
+                let filter = BreachedPasswordFilter::load(path).map_err(|e| e.to_string())?;
+                if filter.contains_password(password.as_ref()) {
+                    return Err(
+                        "this password has appeared in a data breach, please choose a different one"
+                            .to_owned(),
+                    );
+                }
+            }
+            let mut user = User::new(email.clone(), password, requires_mfa);
+            user.email_verified = verified;
+            user_store.add_user(user).await.map_err(describe)?;
+            println!("created {}", email.as_ref());
+        }
+        UserCommand::Disable { email, reason } => {
+            let email = parse_email(email)?;
+            user_store
+                .set_status(email.clone(), AccountStatus::Disabled, reason)
+                .await
+                .map_err(describe)?;
+            let revoked = token_service(config, redis_service)
+                .await
+                .revoke_user_sessions(email.as_ref(), None)
+                .await
+                .map_err(describe_refresh)?;
+            println!(
+                "disabled {}, revoked {} session(s)",
+                email.as_ref(),
+                revoked
+            );
+        }
+        UserCommand::Delete { email } => {
+            let email = parse_email(email)?;
+            user_store
+                .delete_user(email.clone())
+                .await
+                .map_err(describe)?;
+            SqlTermsStore::new(db_client)
+                .remove_for(email.as_ref())
+                .await
+                .map_err(|e| format!("could not delete terms acceptances: {:?}", e))?;
+            let revoked = token_service(config, redis_service.clone())
+                .await
+                .revoke_user_sessions(email.as_ref(), None)
+                .await
+                .map_err(describe_refresh)?;
+            RedisTrustedDeviceStore::new(redis_service)
+                .revoke_all(email.as_ref())
+                .await
+                .map_err(|e| format!("could not revoke trusted devices: {:?}", e))?;
+            println!("deleted {}, revoked {} session(s)", email.as_ref(), revoked);
+        }
+        UserCommand::ResetMfa { email } => {
+            let email = parse_email(email)?;
+            let mut user = user_store.get_user(email.clone()).await.map_err(describe)?;
+            user.requires_mfa = false;
+            user_store.update_user(user).await.map_err(describe)?;
+            RedisTrustedDeviceStore::new(redis_service)
+                .revoke_all(email.as_ref())
+                .await
+                .map_err(|e| format!("could not revoke trusted devices: {:?}", e))?;
+            println!("2FA disabled for {}", email.as_ref());
+        }
+        UserCommand::Import {
+            file,
+            format,
+            rejects,
+            dry_run,
+            batch_size,
+        } => {
+            if !(1..=MAX_BATCH_SIZE).contains(&batch_size) {
+                return Err(format!(
+                    "--batch-size must be between 1 and {}",
+                    MAX_BATCH_SIZE
+                ));
+            }
+            let permits_email = email_domain_gate(&config)?;
+            let format = transfer_format(format, &file)?;
+            let rejects = rejects
+                .unwrap_or_else(|| file.with_extension(format!("rejects.{}", format.extension())));
+            let input = File::open(&file)
+                .map_err(|e| format!("could not open {}: {}", file.display(), e))?;
+            let rejects_file = File::create(&rejects)
+                .map_err(|e| format!("could not create {}: {}", rejects.display(), e))?;
+            let summary = user_transfer::import_users(
+                &mut user_store,
+                BufReader::new(input),
+                BufWriter::new(rejects_file),
+                format,
+                ImportOptions {
+                    batch_size,
+                    dry_run,
+                },
+                permits_email,
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+            println!(
+                "{}read {}, {} {}, rejected {} (see {})",
+                if dry_run { "dry run: " } else { "" },
+                summary.read,
+                if dry_run { "would import" } else { "imported" },
+                summary.imported,
+                summary.rejected,
+                rejects.display()
+            );
+        }
+        UserCommand::Export { file, format } => {
+            let to_stdout = file.as_os_str() == "-";
+            let format = match format {
+                Some(format) => format,
+                None if to_stdout => TransferFormat::Jsonl,
+                None => transfer_format(None, &file)?,
+            };
+            let written = if to_stdout {
+                user_transfer::export_users(
+                    &user_store,
+                    io::stdout().lock(),
+                    format,
+                    MAX_BATCH_SIZE,
+                )
+                .await
+            } else {
+                let output = File::create(&file)
+                    .map_err(|e| format!("could not create {}: {}", file.display(), e))?;
+                user_transfer::export_users(
+                    &user_store,
+                    BufWriter::new(output),
+                    format,
+                    MAX_BATCH_SIZE,
+                )
+                .await
+            }
+            .map_err(|e| e.to_string())?;
+            eprintln!("exported {} user(s)", written);
+        }
+    }
+    Ok(())
+}
+
+async fn run_sessions(command: SessionsCommand, config: Config) -> Result<(), String> {
+    let redis_service = Arc::new(RedisService::new(config.redis_host()));
+    let token_service = token_service(config, redis_service).await;
+
+    match command {
+        SessionsCommand::List { email } => {
+            let email = parse_email(email)?;
+            for session_id in token_service
+                .user_sessions(email.as_ref())
+                .await
+                .map_err(describe_refresh)?
+            {
+                println!("{}", session_id);
+            }
+        }
+        SessionsCommand::Revoke {
+            email,
+            session: Some(session_id),
+        } => {
+            let email = parse_email(email)?;
+            if !token_service
+                .user_sessions(email.as_ref())
+                .await
+                .map_err(describe_refresh)?
+                .contains(&session_id)
+            {
+                return Err(format!(
+                    "{} has no active session {}",
+                    email.as_ref(),
+                    session_id
+                ));
+            }
+            token_service.logout_session(session_id).await;
+            println!("revoked session {}", session_id);
+        }
+        SessionsCommand::Revoke {
+            email,
+            session: None,
+        } => {
+            let email = parse_email(email)?;
+            let revoked = token_service
+                .revoke_user_sessions(email.as_ref(), None)
+                .await
+                .map_err(describe_refresh)?;
+            println!("revoked {} session(s)", revoked);
+        }
+    }
+    Ok(())
+}
+
+fn run_breached_passwords(command: BreachedPasswordsCommand) -> Result<(), String> {
+    match command {
+        BreachedPasswordsCommand::Build {
+            hibp,
+            out,
+            false_positive_rate,
+        } => {
+            let open = || {
+                File::open(&hibp)
+                    .map(BufReader::new)
+                    .map_err(|e| format!("could not open {}: {}", hibp.display(), e))
+            };
+            // Counted up front so the filter is sized for the whole list
+            let mut entries = 0;
+            for line in open()?.lines() {
+                let line = line.map_err(|e| format!("could not read {}: {}", hibp.display(), e))?;
+                if !line.trim().is_empty() {
+                    entries += 1;
+                }
+            }
+
+            let mut filter = BreachedPasswordFilter::new(entries, false_positive_rate);
+            let inserted = filter
+                .insert_sha1_hex_lines(open()?)
+                .map_err(|e| e.to_string())?;
+            filter.save(&out).map_err(|e| e.to_string())?;
+            println!("wrote {} hash(es) to {}", inserted, out.display());
+        }
+    }
+    Ok(())
+}
+
+async fn run_migrate(command: MigrateCommand, config: &Config) -> Result<(), String> {
+    let client = connect(config).await?;
+    match command {
+        MigrateCommand::Up => {
+            migrations::up(&client)
+                .await
+                .map_err(|e| format!("migration failed: {}", e))?;
+            println!("migrations applied");
+        }
+        MigrateCommand::Down { yes: false } => {
+            return Err("rolling back drops every account, pass --yes to confirm".to_owned());
+        }
+        MigrateCommand::Down { yes: true } => {
+            migrations::down(&client)
+                .await
+                .map_err(|e| format!("rollback failed: {}", e))?;
+            println!("migrations rolled back");
+        }
+    }
+    Ok(())
+}
+
+async fn connect(config: &Config) -> Result<AnyClient, String> {
+    get_db_pool(config.db_url())
+        .await
+        .map_err(|e| format!("could not connect to database: {}", e))
+}
+
+async fn token_service(config: Config, redis_service: Arc<RedisService>) -> TokenService {
+    TokenService::new(
+        Arc::new(RwLock::new(config)),
+        Box::new(RedisRefreshStore::new(redis_service)),
+    )
+    .await
+}
+
+fn parse_email(email: String) -> Result<Email, String> {
+    Email::parse(email).map_err(|_| "invalid email address".to_owned())
+}
+
+/// The check signup applies to an address's domain: inside the configured
+/// signup domain policy and not on the disposable-domain list.
+fn email_domain_gate(config: &Config) -> Result<impl Fn(&Email) -> bool + '_, String> {
+    let disposable = config
+        .disposable_domains_path()
+        .map(DisposableDomainList::load)
+        .transpose()
+        .map_err(|e| e.to_string())?;
+    Ok(move |email: &Email| config.permits_email_domain(email, disposable.as_ref()))
+}
+
+fn transfer_format(format: Option<TransferFormat>, file: &Path) -> Result<TransferFormat, String> {
+    format
+        .or_else(|| TransferFormat::from_path(file))
+        .ok_or_else(|| {
+            format!(
+                "cannot tell the format of {}, pass --format",
+                file.display()
+            )
+        })
+}
+
+fn read_password() -> Result<String, String> {
+    eprint!("password: ");
+    let _ = io::stderr().flush();
+    let mut line = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|e| format!("could not read password: {}", e))?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_owned())
+}
+
+fn describe(error: UserStoreError) -> String {
+    match error {
+        UserStoreError::UserAlreadyExists => "user already exists".to_owned(),
+        UserStoreError::UserNotFound => "user not found".to_owned(),
+        error => format!("user store error: {:?}", error),
+    }
+}
+
+fn describe_refresh(error: RefreshError) -> String {
+    format!("session store error: {:?}", error)
+}
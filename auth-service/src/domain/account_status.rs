@@ -0,0 +1,84 @@
+use chrono::{DateTime, Utc};
+
+/// Whether an account may sign in. Blocking an account keeps all its data.
+#[derive(PartialEq, Debug, Clone, Default)]
+pub enum AccountStatus {
+    #[default]
+    Active,
+    Disabled,
+    /// Blocked until the given time, after which the account is active again.
+    Suspended {
+        until: DateTime<Utc>,
+    },
+}
+
+impl AccountStatus {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        match self {
+            AccountStatus::Active => true,
+            AccountStatus::Disabled => false,
+            AccountStatus::Suspended { until } => *until <= now,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountStatus::Active => "active",
+            AccountStatus::Disabled => "disabled",
+            AccountStatus::Suspended { .. } => "suspended",
+        }
+    }
+
+    pub fn suspended_until(&self) -> Option<DateTime<Utc>> {
+        match self {
+            AccountStatus::Suspended { until } => Some(*until),
+            _ => None,
+        }
+    }
+
+    /// Rebuild a status from its stored name and suspension end.
+    pub fn from_parts(status: &str, suspended_until: Option<DateTime<Utc>>) -> Option<Self> {
+        match (status, suspended_until) {
+            ("active", _) => Some(AccountStatus::Active),
+            ("disabled", _) => Some(AccountStatus::Disabled),
+            ("suspended", Some(until)) => Some(AccountStatus::Suspended { until }),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_suspension_expires() {
+        let now = Utc::now();
+        let status = AccountStatus::Suspended {
+            until: now + Duration::hours(1),
+        };
+
+        assert!(!status.is_active(now));
+        assert!(status.is_active(now + Duration::hours(2)));
+        assert!(!AccountStatus::Disabled.is_active(now));
+        assert!(AccountStatus::Active.is_active(now));
+    }
+
+    #[test]
+    fn test_round_trips_through_parts() {
+        let until = Utc::now();
+        for status in [
+            AccountStatus::Active,
+            AccountStatus::Disabled,
+            AccountStatus::Suspended { until },
+        ] {
+            assert_eq!(
+                AccountStatus::from_parts(status.as_str(), status.suspended_until()),
+                Some(status)
+            );
+        }
+        assert_eq!(AccountStatus::from_parts("suspended", None), None);
+        assert_eq!(AccountStatus::from_parts("banned", None), None);
+    }
+}
@@ -0,0 +1,45 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct SetAccountStatusRequestBody {
+    /// "active", "disabled" or "suspended"
+    pub status: String,
+    /// Required for, and only allowed with, "suspended"
+    #[serde(rename = "suspendedUntil")]
+    pub suspended_until: Option<DateTime<Utc>>,
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ImportUsersRequestBody {
+    pub users: Vec<ImportUserRecord>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ImportUserRecord {
+    pub email: String,
+    /// Stored as-is: argon2, bcrypt, scrypt or PBKDF2-SHA256, upgraded to
+    /// argon2id on the user's first login
+    #[serde(rename = "passwordHash")]
+    pub password_hash: String,
+    #[serde(rename = "requires2FA", default)]
+    pub requires_mfa: bool,
+    #[serde(rename = "emailVerified", default)]
+    pub email_verified: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct CreateInvitationRequestBody {
+    /// Restrict the invitation to this address
+    pub email: Option<String>,
+    /// Defaults to 1
+    #[serde(rename = "maxUses")]
+    pub max_uses: Option<u32>,
+    /// Defaults to INVITATION_TTL_SECONDS
+    #[serde(rename = "expiresInSeconds")]
+    pub expires_in_seconds: Option<i64>,
+    /// Email the signup link to `email`, which is then required
+    #[serde(rename = "sendEmail", default)]
+    pub send_email: bool,
+}
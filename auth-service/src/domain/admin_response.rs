@@ -0,0 +1,44 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct AdminResponse {
+    pub message: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct AccountStatusResponse {
+    pub email: String,
+    pub status: String,
+    #[serde(rename = "suspendedUntil")]
+    pub suspended_until: Option<DateTime<Utc>>,
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct ImportUsersResponse {
+    pub imported: usize,
+    pub rejected: Vec<ImportRejection>,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct ImportRejection {
+    /// Position of the record in the request
+    pub index: usize,
+    pub email: String,
+    pub error: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct InvitationResponse {
+    /// Only returned here; the service keeps just a hash of it
+    pub code: String,
+    pub email: Option<String>,
+    #[serde(rename = "maxUses")]
+    pub max_uses: u32,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: DateTime<Utc>,
+    /// Link to the signup form with the code filled in
+    #[serde(rename = "signupUrl")]
+    pub signup_url: String,
+}
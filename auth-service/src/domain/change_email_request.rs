@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ChangeEmailRequestBody {
+    #[serde(rename = "newEmail")]
+    pub new_email: String,
+    pub password: String,
+}
+
+/// Query string of the confirm and cancel links sent by email.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ChangeEmailTokenQuery {
+    pub token: String,
+}
@@ -0,0 +1,123 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{LoginLockout, SessionSummary, TermsAcceptance, TrustedDeviceResponse, User};
+
+/// Everything the service holds about one account, as handed out for a
+/// data-subject access request.
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DataExport {
+    pub exported_at: DateTime<Utc>,
+    pub profile: ExportProfile,
+    pub login_lockout: ExportLoginLockout,
+    pub sessions: Vec<ExportSession>,
+    pub mfa: ExportMfa,
+    pub consents: Vec<ExportConsent>,
+}
+
+/// The stored account, without the password hash.
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportProfile {
+    pub email: String,
+    /// The address as it was typed, when it differs from `email`
+    pub email_display: Option<String>,
+    pub email_verified: bool,
+    pub display_name: Option<String>,
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+    pub avatar_url: Option<String>,
+    pub status: String,
+    pub suspended_until: Option<DateTime<Utc>>,
+    /// Why the account was disabled or suspended, as recorded by an admin
+    pub status_reason: Option<String>,
+}
+
+impl ExportProfile {
+    pub fn new(user: &User, status_reason: Option<String>) -> Self {
+        let display = user.email.display();
+        ExportProfile {
+            email: user.email.as_ref().to_owned(),
+            email_display: (display != user.email.as_ref()).then(|| display.to_owned()),
+            email_verified: user.email_verified,
+            display_name: user.profile.display_name.clone(),
+            locale: user.profile.locale.clone(),
+            timezone: user.profile.timezone.clone(),
+            avatar_url: user.profile.avatar_url.clone(),
+            status: user.status.as_str().to_owned(),
+            suspended_until: user.status.suspended_until(),
+            status_reason,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportLoginLockout {
+    pub failed_attempts: u32,
+    pub lockouts: u32,
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+impl From<LoginLockout> for ExportLoginLockout {
+    fn from(lockout: LoginLockout) -> Self {
+        ExportLoginLockout {
+            failed_attempts: lockout.failed_attempts,
+            lockouts: lockout.lockouts,
+            locked_until: lockout.locked_until,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSession {
+    pub session_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub last_refreshed_at: Option<DateTime<Utc>>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl From<SessionSummary> for ExportSession {
+    fn from(session: SessionSummary) -> Self {
+        ExportSession {
+            session_id: session.session_id,
+            created_at: session.created_at,
+            last_refreshed_at: session.last_refreshed_at,
+            expires_at: session.expires_at,
+            revoked_at: session.revoked_at,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportMfa {
+    /// Whether logins need an emailed code
+    pub email_code_enabled: bool,
+    pub trusted_devices: Vec<TrustedDeviceResponse>,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportConsent {
+    /// What was agreed to; currently always "termsOfService"
+    pub kind: String,
+    pub version: String,
+    pub accepted_at: DateTime<Utc>,
+    pub ip_address: Option<String>,
+}
+
+impl From<TermsAcceptance> for ExportConsent {
+    fn from(acceptance: TermsAcceptance) -> Self {
+        ExportConsent {
+            kind: "termsOfService".to_owned(),
+            version: acceptance.version,
+            accepted_at: acceptance.accepted_at,
+            ip_address: acceptance.ip_address,
+        }
+    }
+}
@@ -0,0 +1,14 @@
+use chrono::{DateTime, Utc};
+
+/// A closed-beta invitation. The code handed out is a one-time token; only
+/// its keyed hash is stored, as with `hash_one_time_token`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Invitation {
+    pub code_hash: [u8; 32],
+    /// When set, only this canonical address may sign up with the code.
+    pub email: Option<String>,
+    pub max_uses: u32,
+    pub uses: u32,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,10 @@
+#[derive(Debug, PartialEq)]
+pub enum InvitationStoreError {
+    InvitationNotFound,
+    Expired,
+    /// Every use of the invitation has been redeemed.
+    Exhausted,
+    /// The invitation is bound to a different email address.
+    EmailMismatch,
+    UnexpectedError,
+}
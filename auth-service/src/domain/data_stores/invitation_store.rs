@@ -0,0 +1,22 @@
+use chrono::{DateTime, Utc};
+
+use super::{Invitation, InvitationStoreError};
+
+// This trait represents the interface all concrete invitation stores should implement.
+#[async_trait::async_trait]
+pub trait InvitationStore: Send + Sync {
+    async fn add_invitation(&mut self, invitation: Invitation) -> Result<(), InvitationStoreError>;
+
+    /// Count one use of the invitation for `email` (canonical form). Checking
+    /// and counting happen atomically, so concurrent signups cannot exceed
+    /// `max_uses`.
+    async fn redeem(
+        &mut self,
+        code_hash: &[u8; 32],
+        email: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), InvitationStoreError>;
+
+    /// Give back a use taken by `redeem` when the signup it was for failed.
+    async fn release(&mut self, code_hash: &[u8; 32]) -> Result<(), InvitationStoreError>;
+}
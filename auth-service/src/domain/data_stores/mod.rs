@@ -1,10 +1,23 @@
 pub mod banned_token_store;
 pub mod banned_token_store_err;
 pub mod base_repository;
+pub mod invitation;
+pub mod invitation_err;
+pub mod invitation_store;
 pub mod jwt_key_store;
+pub mod one_time_token;
+pub mod one_time_token_err;
+pub mod one_time_token_store;
 pub mod refresh_err;
 pub mod refresh_record;
 pub mod refresh_store;
+pub mod session_summary;
+pub mod terms_acceptance;
+pub mod terms_err;
+pub mod terms_store;
+pub mod trusted_device;
+pub mod trusted_device_err;
+pub mod trusted_device_store;
 pub mod twofa_code_store;
 pub mod twofa_err;
 pub mod user_store;
@@ -13,10 +26,23 @@ pub mod user_store_err;
 pub use banned_token_store::*;
 pub use banned_token_store_err::*;
 pub use base_repository::*;
+pub use invitation::Invitation;
+pub use invitation_err::InvitationStoreError;
+pub use invitation_store::InvitationStore;
 pub use jwt_key_store::*;
+pub use one_time_token::*;
+pub use one_time_token_err::OneTimeTokenStoreError;
+pub use one_time_token_store::OneTimeTokenStore;
 pub use refresh_err::RefreshError;
 pub use refresh_record::RefreshRecord;
 pub use refresh_store::*;
+pub use session_summary::SessionSummary;
+pub use terms_acceptance::TermsAcceptance;
+pub use terms_err::TermsStoreError;
+pub use terms_store::TermsStore;
+pub use trusted_device::*;
+pub use trusted_device_err::TrustedDeviceStoreError;
+pub use trusted_device_store::TrustedDeviceStore;
 pub use twofa_code_store::TwoFACodeStore;
 pub use twofa_err::TwoFAError;
 pub use user_store::UserStore;
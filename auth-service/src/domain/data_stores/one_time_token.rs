@@ -0,0 +1,56 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as B64_URL;
+use base64::Engine;
+use rand::RngCore;
+
+/// What a one-time token was issued for. Tokens only redeem for the purpose
+/// they were stored under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenPurpose {
+    MagicLink,
+    PasswordReset,
+    ChangeEmail,
+    ChangeEmailCancel,
+}
+
+impl TokenPurpose {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenPurpose::MagicLink => "magic_link",
+            TokenPurpose::PasswordReset => "password_reset",
+            TokenPurpose::ChangeEmail => "change_email",
+            TokenPurpose::ChangeEmailCancel => "change_email_cancel",
+        }
+    }
+}
+
+/// Fresh URL-safe token to hand to the user; only its hash is stored.
+pub fn new_one_time_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rng().fill_bytes(&mut bytes);
+    B64_URL.encode(bytes)
+}
+
+pub fn hash_one_time_token(key32: &[u8; 32], token: &str) -> [u8; 32] {
+    *blake3::keyed_hash(key32, token.as_bytes()).as_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_are_unique_and_hash_with_key() {
+        let (first, second) = (new_one_time_token(), new_one_time_token());
+        assert_ne!(first, second);
+
+        let key = [7u8; 32];
+        assert_eq!(
+            hash_one_time_token(&key, &first),
+            hash_one_time_token(&key, &first)
+        );
+        assert_ne!(
+            hash_one_time_token(&key, &first),
+            hash_one_time_token(&[8u8; 32], &first)
+        );
+    }
+}
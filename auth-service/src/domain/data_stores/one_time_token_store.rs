@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+
+use super::{OneTimeTokenStoreError, TokenPurpose};
+
+// This trait represents the interface all concrete one-time token stores should implement.
+// Tokens are stored by keyed hash only and are consumed on first use.
+#[async_trait::async_trait]
+pub trait OneTimeTokenStore: Send + Sync {
+    /// Store `token_hash` for `purpose`, carrying `subject` (usually the
+    /// user's email) until `expires_at`. Email subjects are read back with
+    /// `Email::parse`, so those stored before canonicalization still name
+    /// the right account.
+    async fn add_token(
+        &mut self,
+        purpose: TokenPurpose,
+        token_hash: [u8; 32],
+        subject: String,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), OneTimeTokenStoreError>;
+
+    /// The subject of the token, leaving it in place. Fails like `take_token`.
+    async fn peek_token(
+        &self,
+        purpose: TokenPurpose,
+        token_hash: &[u8; 32],
+        now: DateTime<Utc>,
+    ) -> Result<String, OneTimeTokenStoreError>;
+
+    /// Remove the token and return its subject. Fails with `TokenNotFound` if
+    /// it was never issued, already used or expired at `now`.
+    async fn take_token(
+        &mut self,
+        purpose: TokenPurpose,
+        token_hash: &[u8; 32],
+        now: DateTime<Utc>,
+    ) -> Result<String, OneTimeTokenStoreError>;
+}
@@ -3,5 +3,6 @@ pub enum RefreshError {
     NotFoundOrExpired,
     Revoked,
     ReuseDetected,
+    AccountInactive,
     Internal,
 }
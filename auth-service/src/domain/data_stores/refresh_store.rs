@@ -3,11 +3,18 @@ use blake3;
 use chrono::{DateTime, Duration, Utc};
 use uuid::Uuid;
 
-use super::{RefreshError, RefreshRecord};
+use super::{RefreshError, RefreshRecord, SessionSummary};
 
 #[async_trait]
 pub trait RefreshStore {
     async fn insert_initial(&mut self, record: RefreshRecord) -> Result<(), RefreshError>;
+    /// The record `presented_plain` belongs to, without using it up. Fails
+    /// with `NotFoundOrExpired` when the store does not know the token.
+    async fn find_record(
+        &self,
+        presented_plain: &str,
+        hash_key: &[u8; 32],
+    ) -> Result<RefreshRecord, RefreshError>;
     async fn rotate(
         &mut self,
         presented_plain: &str,
@@ -22,6 +29,19 @@ pub trait RefreshStore {
     async fn revoke_session_internal(&mut self, session_id: Uuid, now: DateTime<Utc>);
 
     async fn is_session_revoked(&self, session_id: Uuid) -> bool;
+
+    /// Ids of the sessions of `user_id` that have not been revoked. Stores may
+    /// also return sessions whose refresh token lapsed shortly before `now`.
+    async fn user_sessions(
+        &self,
+        user_id: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Uuid>, RefreshError>;
+
+    /// Every session of `user_id` the store still holds, revoked and expired
+    /// ones included, oldest first. Stores drop a session once its refresh
+    /// tokens have expired.
+    async fn session_history(&self, user_id: &str) -> Result<Vec<SessionSummary>, RefreshError>;
 }
 
 pub async fn hash_refresh(key32: &[u8; 32], token: &str) -> [u8; 32] {
@@ -0,0 +1,13 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// One login session as it looks across its whole refresh token chain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SessionSummary {
+    pub session_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    /// When the newest refresh token was issued; `None` if never refreshed
+    pub last_refreshed_at: Option<DateTime<Utc>>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
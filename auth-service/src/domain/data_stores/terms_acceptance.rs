@@ -0,0 +1,21 @@
+use chrono::{DateTime, Utc};
+
+/// A user accepting a terms-of-service version, kept as proof of consent.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TermsAcceptance {
+    pub version: String,
+    pub accepted_at: DateTime<Utc>,
+    /// Address the acceptance was sent from, when known
+    pub ip_address: Option<String>,
+}
+
+impl TermsAcceptance {
+    /// Acceptance of `version` happening now.
+    pub fn new(version: String, ip_address: Option<String>) -> Self {
+        Self {
+            version,
+            accepted_at: Utc::now(),
+            ip_address,
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use super::{TermsAcceptance, TermsStoreError};
+
+// This trait represents the interface all concrete terms acceptance stores should implement.
+// Records belong to the account: `change_email` carries them over to a new
+// address and `remove_for` deletes them with the account.
+#[async_trait::async_trait]
+pub trait TermsStore: Send + Sync {
+    async fn record_acceptance(
+        &mut self,
+        email: &str,
+        acceptance: TermsAcceptance,
+    ) -> Result<(), TermsStoreError>;
+
+    /// The most recent acceptance by the account with `email`, if any.
+    async fn latest_acceptance(
+        &self,
+        email: &str,
+    ) -> Result<Option<TermsAcceptance>, TermsStoreError>;
+
+    /// Every acceptance by the account with `email`, oldest first.
+    async fn list_acceptances(&self, email: &str) -> Result<Vec<TermsAcceptance>, TermsStoreError>;
+
+    /// Hand the acceptances of `old_email` to `new_email` once the account
+    /// has moved there.
+    async fn change_email(
+        &mut self,
+        old_email: &str,
+        new_email: &str,
+    ) -> Result<(), TermsStoreError>;
+
+    /// Delete every acceptance by the account with `email`. Called once the
+    /// account itself is deleted, so a failed delete keeps the history. Stores
+    /// whose records are found through the account, like `SqlTermsStore`,
+    /// lose them with it and have nothing left to delete.
+    async fn remove_for(&mut self, email: &str) -> Result<(), TermsStoreError>;
+}
@@ -0,0 +1,121 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as B64, Engine};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::domain::AsRedisHashArgs;
+
+/// A browser the user asked us to remember after a successful 2FA check.
+/// While the record exists and has not expired, presenting its signed cookie
+/// lets that user skip the email code on login.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrustedDevice {
+    pub device_id: Uuid,
+    pub user_id: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl TrustedDevice {
+    pub fn redis_key(device_id: Uuid) -> String {
+        format!("trusted_device:{}", device_id)
+    }
+
+    pub fn user_set_key(user_id: &str) -> String {
+        format!("trusted_devices:{}", user_id)
+    }
+
+    /// Reconstruct a TrustedDevice from Redis hash fields
+    pub fn from_redis_hash(fields: Vec<(String, String)>) -> Result<Self, String> {
+        let mut device_id: Option<Uuid> = None;
+        let mut user_id: Option<String> = None;
+        let mut created_at: Option<DateTime<Utc>> = None;
+        let mut expires_at: Option<DateTime<Utc>> = None;
+
+        for (key, value) in fields {
+            match key.as_str() {
+                "device_id" => {
+                    device_id = Some(
+                        Uuid::parse_str(&value)
+                            .map_err(|e| format!("Invalid device_id UUID: {}", e))?,
+                    );
+                }
+                "user_id" => user_id = Some(value),
+                "created_at" => created_at = Some(parse_timestamp("created_at", &value)?),
+                "expires_at" => expires_at = Some(parse_timestamp("expires_at", &value)?),
+                _ => { /* ignore unknown */ }
+            }
+        }
+
+        Ok(TrustedDevice {
+            device_id: device_id.ok_or("Missing required field: device_id")?,
+            user_id: user_id.ok_or("Missing required field: user_id")?,
+            created_at: created_at.ok_or("Missing required field: created_at")?,
+            expires_at: expires_at.ok_or("Missing required field: expires_at")?,
+        })
+    }
+}
+
+fn parse_timestamp(field: &str, value: &str) -> Result<DateTime<Utc>, String> {
+    let timestamp: i64 = value
+        .parse()
+        .map_err(|e| format!("Invalid {} timestamp: {}", field, e))?;
+    DateTime::from_timestamp(timestamp, 0).ok_or(format!("Invalid {} timestamp", field))
+}
+
+impl AsRedisHashArgs for TrustedDevice {
+    fn as_redis_hash_args(&self) -> Vec<(String, String)> {
+        vec![
+            ("device_id".into(), self.device_id.to_string()),
+            ("user_id".into(), self.user_id.clone()),
+            ("created_at".into(), self.created_at.timestamp().to_string()),
+            ("expires_at".into(), self.expires_at.timestamp().to_string()),
+        ]
+    }
+}
+
+/// Build the cookie value for a trusted device: `<device id>.<keyed blake3 MAC>`.
+pub fn sign_device_token(key32: &[u8; 32], device_id: Uuid) -> String {
+    let mac = blake3::keyed_hash(key32, device_id.as_bytes());
+    format!("{}.{}", device_id, B64.encode(mac.as_bytes()))
+}
+
+/// Check the MAC of a device cookie value and return the device id it carries.
+pub fn verify_device_token(key32: &[u8; 32], token: &str) -> Option<Uuid> {
+    let (id, mac) = token.split_once('.')?;
+    let device_id = Uuid::parse_str(id).ok()?;
+    let mac: [u8; 32] = B64.decode(mac).ok()?.try_into().ok()?;
+
+    // blake3::Hash compares in constant time
+    if blake3::keyed_hash(key32, device_id.as_bytes()) == blake3::Hash::from(mac) {
+        Some(device_id)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signed_token_round_trips() {
+        let key = [3u8; 32];
+        let device_id = Uuid::new_v4();
+        let token = sign_device_token(&key, device_id);
+        assert_eq!(Some(device_id), verify_device_token(&key, &token));
+    }
+
+    #[test]
+    fn test_rejects_tampered_or_foreign_tokens() {
+        let key = [3u8; 32];
+        let token = sign_device_token(&key, Uuid::new_v4());
+        let (_, mac) = token.split_once('.').unwrap();
+
+        assert_eq!(None, verify_device_token(&[4u8; 32], &token));
+        assert_eq!(
+            None,
+            verify_device_token(&key, &format!("{}.{}", Uuid::new_v4(), mac))
+        );
+        assert_eq!(None, verify_device_token(&key, "not-a-token"));
+    }
+}
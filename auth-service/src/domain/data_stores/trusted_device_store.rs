@@ -0,0 +1,33 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::{TrustedDevice, TrustedDeviceStoreError};
+
+// This trait represents the interface all concrete trusted device stores should implement
+#[async_trait::async_trait]
+pub trait TrustedDeviceStore: Send + Sync {
+    async fn add_device(&mut self, device: TrustedDevice) -> Result<(), TrustedDeviceStoreError>;
+
+    /// Fetch a device that has not expired at `now`.
+    async fn get_device(
+        &self,
+        device_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<TrustedDevice, TrustedDeviceStoreError>;
+
+    /// All unexpired devices trusted by `user_id`, oldest first.
+    async fn list_devices(
+        &self,
+        user_id: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<TrustedDevice>, TrustedDeviceStoreError>;
+
+    /// Revoke one device. Fails with `DeviceNotFound` if it does not belong to `user_id`.
+    async fn revoke_device(
+        &mut self,
+        user_id: &str,
+        device_id: Uuid,
+    ) -> Result<(), TrustedDeviceStoreError>;
+
+    async fn revoke_all(&mut self, user_id: &str) -> Result<(), TrustedDeviceStoreError>;
+}
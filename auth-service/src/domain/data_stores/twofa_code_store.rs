@@ -1,14 +1,63 @@
-use crate::domain::{Email, LoginAttemptId, TwoFACode, TwoFAError};
+use chrono::{DateTime, Duration, Utc};
 
-// This trait represents the interface all concrete 2FA code stores should implement
+use crate::domain::{Email, LoginAttemptId, TwoFACode, TwoFAError, TwoFAPurpose};
+
+// This trait represents the interface all concrete 2FA code stores should implement.
+// Pending challenges are keyed by login attempt, so one user can have several
+// logins in flight (e.g. laptop and phone) without overwriting each other.
 #[async_trait::async_trait]
 pub trait TwoFACodeStore: Send + Sync {
+    /// Store a pending challenge bound to `email` and `purpose`. When the user
+    /// already has `max_pending` challenges outstanding, the oldest one is
+    /// dropped.
     async fn add_code(
         &mut self,
         email: Email,
         login_attempt_id: LoginAttemptId,
         code: TwoFACode,
+        purpose: TwoFAPurpose,
+        max_pending: usize,
     ) -> Result<(), TwoFAError>;
-    async fn remove_code(&mut self, email: &Email) -> Result<(), TwoFAError>;
-    async fn get_code(&self, email: &Email) -> Result<&(LoginAttemptId, TwoFACode), TwoFAError>;
+    /// Swap the code of a pending attempt for a freshly generated one. Fails
+    /// with `ResendCooldown` if the current code was issued less than
+    /// `cooldown` ago and with `ResendLimitReached` after `max_resends` swaps.
+    async fn resend_code(
+        &mut self,
+        login_attempt_id: &LoginAttemptId,
+        code: TwoFACode,
+        now: DateTime<Utc>,
+        cooldown: Duration,
+        max_resends: u32,
+    ) -> Result<(), TwoFAError>;
+    async fn remove_code(&mut self, login_attempt_id: &LoginAttemptId) -> Result<(), TwoFAError>;
+    /// Drop every pending challenge of `email` along with its failure counters.
+    async fn remove_codes_for(&mut self, email: &Email) -> Result<(), TwoFAError>;
+    /// The pending challenge, if it was stored for `purpose`. Challenges for
+    /// other purposes are reported as `LoginAttemptIdNotFound`.
+    async fn get_code(
+        &self,
+        login_attempt_id: &LoginAttemptId,
+        purpose: TwoFAPurpose,
+    ) -> Result<&(Email, TwoFACode), TwoFAError>;
+
+    /// Count a wrong code against the pending login attempt and return the
+    /// number of failures recorded for that attempt so far.
+    async fn record_failed_attempt(
+        &mut self,
+        login_attempt_id: &LoginAttemptId,
+    ) -> Result<u32, TwoFAError>;
+
+    /// Count a wrong code against the account, across login attempts, and
+    /// return the number of failures that happened within `window` of `now`.
+    async fn record_account_failure(
+        &mut self,
+        email: &Email,
+        now: DateTime<Utc>,
+        window: Duration,
+    ) -> Result<u32, TwoFAError>;
+
+    /// Number of wrong codes recorded for the account within `window` of `now`.
+    async fn account_failures(&self, email: &Email, now: DateTime<Utc>, window: Duration) -> u32;
+
+    async fn clear_account_failures(&mut self, email: &Email) -> Result<(), TwoFAError>;
 }
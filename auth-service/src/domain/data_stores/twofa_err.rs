@@ -2,5 +2,7 @@
 pub enum TwoFAError {
     LoginAttemptIdNotFound,
     InvalidToken,
+    ResendCooldown,
+    ResendLimitReached,
     UnexpectedError,
 }
@@ -1,15 +1,83 @@
 use super::UserStoreError;
-use crate::domain::{Email, Password, User};
+use crate::domain::{AccountStatus, Email, LoginLockout, Password, User};
+use argon2::Params;
 use axum::async_trait;
 
 #[async_trait]
 pub trait UserStore: Send + Sync {
     async fn add_user(&mut self, user: User) -> Result<(), UserStoreError>;
+    /// Add a user whose `password` already holds a hash from another system,
+    /// storing it without rehashing. Fails with `UnsupportedPasswordHash` for
+    /// formats logins could not verify and for costs over the limits in
+    /// `password_hashing`.
+    async fn import_user(&mut self, user: User) -> Result<(), UserStoreError>;
+    /// `import_user` for a whole batch, written in one transaction. Returns
+    /// one result per user, in order; rejected users do not stop the others.
+    /// With `dry_run` every user is checked but nothing is written. Fails as
+    /// a whole only when the batch could not be written at all.
+    async fn import_users(
+        &mut self,
+        users: Vec<User>,
+        dry_run: bool,
+    ) -> Result<Vec<Result<(), UserStoreError>>, UserStoreError>;
+    /// Up to `limit` users ordered by email, starting after `after`.
+    /// Loaded users carry their stored password hash.
+    async fn list_users(
+        &self,
+        after: Option<Email>,
+        limit: usize,
+    ) -> Result<Vec<User>, UserStoreError>;
     async fn get_user(&self, username: Email) -> Result<User, UserStoreError>;
+    /// Persist changed account flags (`requires_mfa`, `email_verified`) and
+    /// profile fields for an existing user. The password is left untouched.
+    async fn update_user(&mut self, user: User) -> Result<User, UserStoreError>;
+    /// Replace the user's password, storing a fresh hash of `password`.
+    async fn update_password(
+        &mut self,
+        username: Email,
+        password: Password,
+    ) -> Result<(), UserStoreError>;
+    /// Move an account to `new_email`. Fails with `UserAlreadyExists` when the
+    /// new address already belongs to another account.
+    async fn change_email(
+        &mut self,
+        username: Email,
+        new_email: Email,
+    ) -> Result<User, UserStoreError>;
+    /// Failed-login counters and lock state of the account.
+    async fn get_lockout(&self, username: Email) -> Result<LoginLockout, UserStoreError>;
+    async fn set_lockout(
+        &mut self,
+        username: Email,
+        lockout: LoginLockout,
+    ) -> Result<(), UserStoreError>;
+    /// Set whether the account may sign in, recording why.
+    async fn set_status(
+        &mut self,
+        username: Email,
+        status: AccountStatus,
+        reason: Option<String>,
+    ) -> Result<User, UserStoreError>;
+    /// Why the account got its current status, as given to `set_status`.
+    async fn get_status_reason(&self, username: Email) -> Result<Option<String>, UserStoreError>;
     async fn delete_user(&mut self, username: Email) -> Result<User, UserStoreError>;
+    /// Check `password` against the stored hash without changing anything.
     async fn validate_user(
         &self,
         username: Email,
         password: Password,
     ) -> Result<User, UserStoreError>;
+    /// Parameters new password hashes are made with, or `None` when the
+    /// store keeps passwords as given. Lets callers hash a password with
+    /// `password_hashing::stored_form` before taking the store's write lock.
+    fn argon2_params(&self) -> Option<Params>;
+    /// Save `new_hash` verbatim in place of `current_hash`, unless the stored
+    /// password changed since `current_hash` was read. Returns whether it was
+    /// replaced.
+    async fn replace_password_hash(
+        &mut self,
+        username: Email,
+        current_hash: Password,
+        new_hash: Password,
+    ) -> Result<bool, UserStoreError>;
 }
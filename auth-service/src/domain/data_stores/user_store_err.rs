@@ -3,5 +3,6 @@ pub enum UserStoreError {
     UserAlreadyExists,
     UserNotFound,
     InvalidCredentials,
+    UnsupportedPasswordHash,
     UnexpectedError,
 }
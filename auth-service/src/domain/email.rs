@@ -1,19 +1,56 @@
-use crate::validation::is_valid_email;
+use std::hash::{Hash, Hasher};
 
-#[derive(PartialEq, Debug, Clone, Eq, Hash)]
-pub struct Email(String);
+use crate::validation::{canonicalize_email, is_valid_email};
+
+/// An account's email address. Two addresses are the same account when their
+/// canonical forms match, whatever case or script they were typed in.
+#[derive(Debug, Clone)]
+pub struct Email {
+    canonical: String,
+    display: String,
+}
 
 impl Email {
     pub fn parse(email: String) -> Result<Email, String> {
-        match is_valid_email(&email) {
-            true => Ok(Email(email)),
-            false => Err(format!("Email {} is not valid", email)),
+        match canonicalize_email(&email) {
+            Some(canonical) if is_valid_email(&canonical) => Ok(Email {
+                canonical,
+                display: email.trim().to_owned(),
+            }),
+            _ => Err(format!("Email {} is not valid", email)),
         }
     }
+
+    /// The address as the user typed it, for showing back to them.
+    pub fn display(&self) -> &str {
+        &self.display
+    }
+
+    /// Domain of the canonical form, in punycode.
+    pub fn domain(&self) -> &str {
+        self.canonical
+            .rsplit_once('@')
+            .map_or("", |(_, domain)| domain)
+    }
 }
 
+/// The canonical form, used as the account's identity everywhere.
 impl AsRef<str> for Email {
     fn as_ref(&self) -> &str {
-        &self.0
+        &self.canonical
+    }
+}
+
+impl PartialEq for Email {
+    fn eq(&self, other: &Self) -> bool {
+        self.canonical == other.canonical
+    }
+}
+
+impl Eq for Email {}
+
+impl Hash for Email {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.canonical.hash(state);
     }
 }
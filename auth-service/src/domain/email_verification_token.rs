@@ -0,0 +1,87 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as B64, Engine};
+use chrono::{DateTime, Utc};
+
+use super::Email;
+
+// Verification links are stateless: the token carries the address and its
+// expiry, and a keyed MAC over both proves we issued it.
+fn mac(key32: &[u8; 32], email: &str, expires_at: i64) -> blake3::Hash {
+    blake3::keyed_hash(key32, format!("{}\n{}", email, expires_at).as_bytes())
+}
+
+/// Token for the link that confirms `email`, valid until `expires_at`.
+pub fn sign_email_verification_token(
+    key32: &[u8; 32],
+    email: &Email,
+    expires_at: DateTime<Utc>,
+) -> String {
+    let expires_at = expires_at.timestamp();
+    format!(
+        "{}.{}.{}",
+        B64.encode(email.as_ref()),
+        expires_at,
+        B64.encode(mac(key32, email.as_ref(), expires_at).as_bytes())
+    )
+}
+
+/// Check the MAC and expiry of a verification token and return the email it confirms.
+pub fn verify_email_verification_token(
+    key32: &[u8; 32],
+    token: &str,
+    now: DateTime<Utc>,
+) -> Option<Email> {
+    let mut parts = token.splitn(3, '.');
+    let email = String::from_utf8(B64.decode(parts.next()?).ok()?).ok()?;
+    let expires_at: i64 = parts.next()?.parse().ok()?;
+    let presented: [u8; 32] = B64.decode(parts.next()?).ok()?.try_into().ok()?;
+
+    // blake3::Hash compares in constant time
+    if mac(key32, &email, expires_at) != blake3::Hash::from(presented) {
+        return None;
+    }
+    if expires_at <= now.timestamp() {
+        return None;
+    }
+    Email::parse(email).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn email() -> Email {
+        Email::parse("lads@tst.com".to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_signed_token_round_trips() {
+        let key = [5u8; 32];
+        let now = Utc::now();
+        let token = sign_email_verification_token(&key, &email(), now + Duration::hours(1));
+        assert_eq!(
+            Some(email()),
+            verify_email_verification_token(&key, &token, now)
+        );
+    }
+
+    #[test]
+    fn test_rejects_expired_tampered_or_foreign_tokens() {
+        let key = [5u8; 32];
+        let now = Utc::now();
+        let token = sign_email_verification_token(&key, &email(), now + Duration::hours(1));
+
+        assert_eq!(
+            None,
+            verify_email_verification_token(&key, &token, now + Duration::hours(2))
+        );
+        assert_eq!(
+            None,
+            verify_email_verification_token(&[6u8; 32], &token, now)
+        );
+
+        let (_, rest) = token.split_once('.').unwrap();
+        let forged = format!("{}.{}", B64.encode("other@tst.com"), rest);
+        assert_eq!(None, verify_email_verification_token(&key, &forged, now));
+    }
+}
@@ -1,7 +1,7 @@
 use crate::domain::TwoFAError;
 use uuid::*;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct LoginAttemptId(String);
 
 impl LoginAttemptId {
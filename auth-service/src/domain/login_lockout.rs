@@ -0,0 +1,113 @@
+use chrono::{DateTime, Duration, Utc};
+
+/// Failed password attempts of one account and the lock they led to.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LoginLockout {
+    /// Wrong passwords since the last successful login or lock.
+    pub failed_attempts: u32,
+    /// Locks since the last successful login; each one doubles the next.
+    pub lockouts: u32,
+    /// Always a whole second, so stores that keep seconds hold it exactly.
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+impl LoginLockout {
+    /// Whole seconds until the account unlocks, or `None` when it is not locked.
+    pub fn retry_after(&self, now: DateTime<Utc>) -> Option<i64> {
+        self.locked_until
+            .filter(|until| *until > now)
+            .map(|until| ((until - now).num_milliseconds() + 999) / 1000)
+    }
+
+    /// Count one wrong password and lock the account once `max_failures` is
+    /// reached. Returns true when this failure caused the lock. A
+    /// `max_failures` of 0 disables locking.
+    pub fn record_failure(
+        &mut self,
+        now: DateTime<Utc>,
+        max_failures: u32,
+        base_seconds: i64,
+        max_seconds: i64,
+    ) -> bool {
+        self.failed_attempts += 1;
+        if max_failures == 0 || self.failed_attempts < max_failures {
+            return false;
+        }
+
+        let factor = 2i64.saturating_pow(self.lockouts.min(62));
+        let seconds = base_seconds.saturating_mul(factor).min(max_seconds);
+        self.failed_attempts = 0;
+        self.lockouts += 1;
+        self.locked_until = Some(ceil_to_second(now + Duration::seconds(seconds)));
+        true
+    }
+}
+
+/// Rounded up so a lock never ends earlier than computed.
+fn ceil_to_second(time: DateTime<Utc>) -> DateTime<Utc> {
+    let rounded = time.timestamp() + i64::from(time.timestamp_subsec_nanos() > 0);
+    DateTime::from_timestamp(rounded, 0).unwrap_or(time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn whole_second_now() -> DateTime<Utc> {
+        DateTime::from_timestamp(Utc::now().timestamp(), 0).unwrap()
+    }
+
+    #[test]
+    fn test_locks_after_max_failures_and_escalates() {
+        let now = whole_second_now();
+        let mut lockout = LoginLockout::default();
+
+        assert!(!lockout.record_failure(now, 3, 60, 3600));
+        assert!(!lockout.record_failure(now, 3, 60, 3600));
+        assert_eq!(lockout.retry_after(now), None);
+        assert!(lockout.record_failure(now, 3, 60, 3600));
+        assert_eq!(lockout.retry_after(now), Some(60));
+
+        let later = now + Duration::seconds(61);
+        assert_eq!(lockout.retry_after(later), None);
+        for _ in 0..3 {
+            lockout.record_failure(later, 3, 60, 3600);
+        }
+        assert_eq!(lockout.retry_after(later), Some(120));
+    }
+
+    #[test]
+    fn test_lock_is_capped() {
+        let now = whole_second_now();
+        let mut lockout = LoginLockout {
+            lockouts: 40,
+            ..Default::default()
+        };
+
+        assert!(lockout.record_failure(now, 1, 60, 3600));
+        assert_eq!(lockout.retry_after(now), Some(3600));
+    }
+
+    #[test]
+    fn test_lock_ends_on_a_whole_second() {
+        let now = whole_second_now() + Duration::milliseconds(300);
+        let mut lockout = LoginLockout::default();
+
+        assert!(lockout.record_failure(now, 1, 60, 3600));
+        let until = lockout.locked_until.unwrap();
+        assert_eq!(until.timestamp_subsec_nanos(), 0);
+        assert_eq!(until - now, Duration::milliseconds(60_700));
+        assert_eq!(lockout.retry_after(now), Some(61));
+    }
+
+    #[test]
+    fn test_zero_max_failures_never_locks() {
+        let now = Utc::now();
+        let mut lockout = LoginLockout::default();
+
+        for _ in 0..10 {
+            assert!(!lockout.record_failure(now, 0, 60, 3600));
+        }
+        assert_eq!(lockout.retry_after(now), None);
+    }
+}
@@ -3,5 +3,12 @@ use serde::{Deserialize, Serialize};
 #[derive(Deserialize, Serialize, Debug)]
 pub struct LoginRequestBody {
     pub email: String,
-    pub password: String
+    pub password: String,
+    /// Sent again with the login after a terms acceptance prompt
+    #[serde(
+        rename = "acceptedTermsVersion",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub accepted_terms_version: Option<String>,
 }
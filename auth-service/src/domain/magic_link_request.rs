@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct MagicLinkRequestBody {
+    pub email: String,
+}
+
+/// Query string of the link sent by email.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct MagicLinkConsumeQuery {
+    pub token: String,
+    /// Added when following the link again after a terms acceptance prompt
+    #[serde(
+        rename = "acceptedTermsVersion",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub accepted_terms_version: Option<String>,
+}
@@ -0,0 +1,56 @@
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Partial profile update. A missing field is left alone, `null` clears it.
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct UpdateProfileRequestBody {
+    #[serde(
+        rename = "displayName",
+        default,
+        deserialize_with = "present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub display_name: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub locale: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub timezone: Option<Option<String>>,
+    #[serde(
+        rename = "avatarUrl",
+        default,
+        deserialize_with = "present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub avatar_url: Option<Option<String>>,
+}
+
+// Tells an explicit `null` (Some(None)) apart from an absent field (None)
+fn present<'de, D>(deserializer: D) -> Result<Option<Option<String>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_absent_null_and_value_are_distinct() {
+        let body: UpdateProfileRequestBody =
+            serde_json::from_str(r#"{"displayName": null, "locale": "en"}"#).unwrap();
+
+        assert_eq!(body.display_name, Some(None));
+        assert_eq!(body.locale, Some(Some("en".to_owned())));
+        assert_eq!(body.timezone, None);
+        assert_eq!(body.avatar_url, None);
+    }
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+use super::User;
+
+/// The caller's own account as shown to front-ends. Built field by field so
+/// nothing secret, such as the password hash, can leak into it.
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct MeResponse {
+    pub email: String,
+    #[serde(rename = "requires2FA")]
+    pub requires_mfa: bool,
+    #[serde(rename = "emailVerified")]
+    pub email_verified: bool,
+    #[serde(rename = "displayName")]
+    pub display_name: Option<String>,
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+    #[serde(rename = "avatarUrl")]
+    pub avatar_url: Option<String>,
+}
+
+impl From<User> for MeResponse {
+    fn from(user: User) -> Self {
+        MeResponse {
+            email: user.email.display().to_owned(),
+            requires_mfa: user.requires_mfa,
+            email_verified: user.email_verified,
+            display_name: user.profile.display_name,
+            locale: user.profile.locale,
+            timezone: user.profile.timezone,
+            avatar_url: user.profile.avatar_url,
+        }
+    }
+}
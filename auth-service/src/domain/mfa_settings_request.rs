@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+/// Step-up body for turning 2FA off. Sent first with only the password to
+/// receive a code, then again with the code and its login attempt id.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct DisableMFARequestBody {
+    pub password: String,
+    #[serde(
+        default,
+        rename(serialize = "loginAttemptId", deserialize = "login_attempt_id"),
+        alias = "loginAttemptId"
+    )]
+    pub login_attempt_id: Option<String>,
+    #[serde(
+        default,
+        rename(serialize = "2FACode", deserialize = "mfa_code"),
+        alias = "2FACode"
+    )]
+    pub mfa_code: Option<String>,
+}
@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct MfaSettingsResponse {
+    pub message: String,
+    #[serde(rename = "requires2FA")]
+    pub requires_mfa: bool,
+}
@@ -1,37 +1,96 @@
 pub mod access_claims;
+pub mod account_status;
+pub mod admin_request;
+pub mod admin_response;
 pub mod as_redis_hash_args;
+pub mod change_email_request;
+pub mod change_email_response;
+pub mod change_password_request;
+pub mod change_password_response;
+pub mod data_export;
 pub mod data_stores;
+pub mod delete_account_request;
+pub mod delete_account_response;
 pub mod email;
 pub mod email_client;
+pub mod email_verification_token;
 pub mod issued_tokens;
 pub mod login_attempt_id;
+pub mod login_lockout;
 pub mod login_request;
 pub mod login_response;
 pub mod logout_response;
+pub mod magic_link_request;
+pub mod magic_link_response;
+pub mod me_request;
+pub mod me_response;
+pub mod mfa_settings_request;
+pub mod mfa_settings_response;
 pub mod models;
 pub mod password;
+pub mod password_reset_request;
+pub mod password_reset_response;
+pub mod resend_mfa_request;
 pub mod signup_request;
 pub mod signup_response;
+pub mod terms_response;
+pub mod trusted_device_response;
 pub mod twofa_code;
 mod user;
+pub mod user_profile;
+pub mod verify_email_request;
+pub mod verify_email_response;
 pub mod verify_mfa_request;
 pub mod verify_token_request;
 
 pub use access_claims::*;
+pub use account_status::AccountStatus;
+pub use admin_request::{
+    CreateInvitationRequestBody, ImportUserRecord, ImportUsersRequestBody,
+    SetAccountStatusRequestBody,
+};
+pub use admin_response::{
+    AccountStatusResponse, AdminResponse, ImportRejection, ImportUsersResponse, InvitationResponse,
+};
 pub use as_redis_hash_args::AsRedisHashArgs;
+pub use change_email_request::*;
+pub use change_email_response::ChangeEmailResponse;
+pub use change_password_request::ChangePasswordRequestBody;
+pub use change_password_response::ChangePasswordResponse;
+pub use data_export::{
+    DataExport, ExportConsent, ExportLoginLockout, ExportMfa, ExportProfile, ExportSession,
+};
 pub use data_stores::*;
+pub use delete_account_request::DeleteAccountRequestBody;
+pub use delete_account_response::DeleteAccountResponse;
 pub use email::*;
 pub use email_client::*;
+pub use email_verification_token::*;
 pub use issued_tokens::*;
 pub use login_attempt_id::LoginAttemptId;
+pub use login_lockout::LoginLockout;
 pub use login_request::*;
 pub use login_response::*;
 pub use logout_response::*;
+pub use magic_link_request::*;
+pub use magic_link_response::MagicLinkResponse;
+pub use me_request::UpdateProfileRequestBody;
+pub use me_response::MeResponse;
+pub use mfa_settings_request::DisableMFARequestBody;
+pub use mfa_settings_response::MfaSettingsResponse;
 pub use models::*;
 pub use password::*;
+pub use password_reset_request::*;
+pub use password_reset_response::{PasswordPolicyResponse, PasswordResetResponse};
+pub use resend_mfa_request::ResendMFARequestBody;
 pub use signup_request::*;
 pub use signup_response::*;
-pub use twofa_code::TwoFACode;
+pub use terms_response::TermsResponse;
+pub use trusted_device_response::TrustedDeviceResponse;
+pub use twofa_code::{TwoFACode, TwoFAPurpose};
 pub use user::*;
+pub use user_profile::UserProfile;
+pub use verify_email_request::*;
+pub use verify_email_response::VerifyEmailResponse;
 pub use verify_mfa_request::VerifyMFARequestBody;
 pub use verify_token_request::*;
@@ -0,0 +1,15 @@
+use welds::prelude::*;
+
+#[derive(WeldsModel, Clone)]
+#[welds(table = "invitations")]
+pub struct InvitationModel {
+    #[welds(primary_key)]
+    pub id: i64,
+    /// Hex of the keyed code hash, see `Invitation`
+    pub code_hash: String,
+    pub email: Option<String>,
+    pub max_uses: i32,
+    pub uses: i32,
+    pub expires_at: i64,
+    pub created_at: i64,
+}
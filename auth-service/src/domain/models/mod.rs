@@ -1,3 +1,7 @@
+mod invitation;
+mod terms_acceptance;
 mod user;
 
+pub use invitation::*;
+pub use terms_acceptance::*;
 pub use user::*;
@@ -0,0 +1,13 @@
+use welds::prelude::*;
+
+#[derive(WeldsModel, Clone)]
+#[welds(table = "terms_acceptances")]
+pub struct TermsAcceptanceModel {
+    #[welds(primary_key)]
+    pub id: i64,
+    /// `users.id`, so records survive email changes
+    pub user_id: i64,
+    pub terms_version: String,
+    pub accepted_at: i64,
+    pub ip_address: Option<String>,
+}
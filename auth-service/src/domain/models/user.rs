@@ -5,10 +5,24 @@ use welds::prelude::*;
 pub struct UserModel {
     #[welds(primary_key)]
     pub id: i64,
+    /// Canonical form, see `Email`
     pub email: String,
+    /// The address as the user typed it
+    pub email_display: Option<String>,
     pub password_hash: String,
     #[welds(rename = "requires_2fa")]
     pub requires_mfa: bool,
+    pub email_verified: bool,
+    pub failed_login_attempts: i32,
+    pub lockout_count: i32,
+    pub locked_until: Option<i64>,
+    pub display_name: Option<String>,
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+    pub avatar_url: Option<String>,
+    pub status: String,
+    pub suspended_until: Option<i64>,
+    pub status_reason: Option<String>,
     pub created_at: i64,
     pub updated_at: i64,
 }
@@ -1,14 +1,46 @@
-use crate::validation::is_valid_password;
+use crate::domain::Email;
+use crate::validation::{
+    normalize_password, PasswordPolicy, PasswordPolicyViolations, MAX_PASSWORD_LENGTH,
+};
 
 #[derive(PartialEq, Debug, Clone)]
 pub struct Password(String);
 
 impl Password {
+    /// A password typed to prove identity (login, re-authentication). Only
+    /// the shape is checked, so accounts keep working when the policy for
+    /// new passwords is tightened.
     pub fn parse(password: String) -> Result<Self, String> {
-        match is_valid_password(&password) {
-            true => Ok(Self(password)),
-            false => Err(format!("Password is not valid, must be at least 8 characters long, contain at least one uppercase letter and one special character.")),
+        let password = normalize_password(&password);
+        if password.is_empty() {
+            return Err("Password must not be empty".to_owned());
         }
+        if password.chars().count() > MAX_PASSWORD_LENGTH {
+            return Err(format!(
+                "Password must be at most {} characters long",
+                MAX_PASSWORD_LENGTH
+            ));
+        }
+        Ok(Self(password))
+    }
+    /// `password` exactly as typed, for hashes stored before passwords were
+    /// normalized. `None` when normalizing leaves it unchanged, as then
+    /// `parse` already yields the same password.
+    pub fn as_typed(password: String) -> Option<Self> {
+        (normalize_password(&password) != password).then_some(Self(password))
+    }
+    /// A password being set for `email`, which must satisfy `policy`.
+    pub fn parse_new(
+        password: String,
+        policy: &PasswordPolicy,
+        email: &Email,
+    ) -> Result<Self, PasswordPolicyViolations> {
+        let password = normalize_password(&password);
+        let violations = policy.check(&password, Some(email.as_ref()));
+        if !violations.is_empty() {
+            return Err(PasswordPolicyViolations(violations));
+        }
+        Ok(Self(password))
     }
     pub fn from_hash(password_hash: String) -> Self {
         Self(password_hash)
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct PasswordResetRequestBody {
+    pub email: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct PasswordResetConfirmBody {
+    pub token: String,
+    #[serde(rename = "newPassword")]
+    pub new_password: String,
+}
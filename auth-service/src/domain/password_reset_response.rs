@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+use crate::validation::PasswordPolicyViolations;
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct PasswordResetResponse {
+    pub message: String,
+}
+
+/// Returned with 422 by every endpoint that sets a password when it breaks
+/// the password policy, one entry per broken rule.
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct PasswordPolicyResponse {
+    pub message: String,
+    pub violations: Vec<String>,
+}
+
+impl From<PasswordPolicyViolations> for PasswordPolicyResponse {
+    fn from(violations: PasswordPolicyViolations) -> Self {
+        PasswordPolicyResponse {
+            message: "password does not meet the requirements".to_owned(),
+            violations: violations.0.iter().map(ToString::to_string).collect(),
+        }
+    }
+}
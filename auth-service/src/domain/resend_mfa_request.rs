@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ResendMFARequestBody {
+    pub email: String,
+    #[serde(
+        rename(serialize = "loginAttemptId", deserialize = "login_attempt_id"),
+        alias = "loginAttemptId"
+    )]
+    pub login_attempt_id: String,
+}
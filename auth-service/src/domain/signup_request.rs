@@ -9,4 +9,18 @@ pub struct SignupRequestBody {
         alias = "requires2fa"
     )]
     pub requires_mfa: bool,
+    /// Required while INVITE_ONLY_SIGNUP is on
+    #[serde(
+        rename = "invitationCode",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub invitation_code: Option<String>,
+    /// Must name the current version while TERMS_VERSION is set
+    #[serde(
+        rename = "acceptedTermsVersion",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub accepted_terms_version: Option<String>,
 }
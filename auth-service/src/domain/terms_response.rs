@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct TermsResponse {
+    /// Version new users must accept; `None` while terms are not enforced
+    pub version: Option<String>,
+    pub url: Option<String>,
+}
@@ -0,0 +1,23 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::TrustedDevice;
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustedDeviceResponse {
+    pub device_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl From<TrustedDevice> for TrustedDeviceResponse {
+    fn from(device: TrustedDevice) -> Self {
+        Self {
+            device_id: device.device_id,
+            created_at: device.created_at,
+            expires_at: device.expires_at,
+        }
+    }
+}
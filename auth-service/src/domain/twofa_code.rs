@@ -21,6 +21,15 @@ impl Default for TwoFACode {
     }
 }
 
+/// What a 2FA code was sent for. Codes only verify for the purpose they were
+/// stored under, so a login code cannot approve turning 2FA off and a step-up
+/// code cannot finish a login.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TwoFAPurpose {
+    Login,
+    DisableMfa,
+}
+
 impl AsRef<str> for TwoFACode {
     fn as_ref(&self) -> &str {
         &self.0
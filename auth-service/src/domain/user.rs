@@ -1,10 +1,15 @@
-use super::{email::Email, password::Password};
+use super::{
+    account_status::AccountStatus, email::Email, password::Password, user_profile::UserProfile,
+};
 
 #[derive(PartialEq, Debug, Clone)]
 pub struct User {
     pub email: Email,
     pub password: Password,
     pub requires_mfa: bool,
+    pub email_verified: bool,
+    pub profile: UserProfile,
+    pub status: AccountStatus,
 }
 
 impl User {
@@ -13,6 +18,9 @@ impl User {
             email,
             password,
             requires_mfa,
+            email_verified: false,
+            profile: UserProfile::default(),
+            status: AccountStatus::Active,
         }
     }
 }
@@ -0,0 +1,10 @@
+/// Optional, user-editable details shown by front-ends.
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct UserProfile {
+    pub display_name: Option<String>,
+    /// BCP 47 language tag, e.g. "en-GB"
+    pub locale: Option<String>,
+    /// IANA time zone name, e.g. "Europe/Berlin"
+    pub timezone: Option<String>,
+    pub avatar_url: Option<String>,
+}
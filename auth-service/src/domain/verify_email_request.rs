@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+/// Query string of the verification link sent at signup.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct VerifyEmailQuery {
+    pub token: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ResendVerificationRequestBody {
+    pub email: String,
+}
@@ -13,4 +13,11 @@ pub struct VerifyMFARequestBody {
         alias = "2FACode"
     )]
     pub mfa_code: String,
+    /// Remember this browser so later logins can skip the 2FA step
+    #[serde(
+        default,
+        rename(serialize = "trustDevice", deserialize = "trust_device"),
+        alias = "trustDevice"
+    )]
+    pub trust_device: bool,
 }
@@ -0,0 +1,42 @@
+use axum::{http::StatusCode, response::IntoResponse};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum AdminError {
+    #[error("admin token missing or invalid")]
+    Unauthorized,
+
+    #[error("invalid email address")]
+    InvalidEmail,
+
+    #[error("user not found")]
+    UserNotFound,
+
+    #[error("invalid status: {0}")]
+    InvalidStatus(&'static str),
+
+    #[error("invalid invitation: {0}")]
+    InvalidInvitation(&'static str),
+
+    #[error("at most {0} users can be imported per request")]
+    ImportTooLarge(usize),
+
+    #[error("Something went wrong, please try again later.")]
+    InternalServerError,
+}
+
+impl IntoResponse for AdminError {
+    fn into_response(self) -> axum::response::Response {
+        let status = match self {
+            AdminError::Unauthorized => StatusCode::UNAUTHORIZED,
+            AdminError::InvalidEmail => StatusCode::UNPROCESSABLE_ENTITY,
+            AdminError::UserNotFound => StatusCode::NOT_FOUND,
+            AdminError::InvalidStatus(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AdminError::InvalidInvitation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AdminError::ImportTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            AdminError::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        (status, self.to_string()).into_response()
+    }
+}
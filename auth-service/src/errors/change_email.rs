@@ -0,0 +1,52 @@
+use axum::{http::StatusCode, response::IntoResponse};
+use thiserror::Error;
+
+use super::LoginError;
+
+#[derive(Error, Debug)]
+pub enum ChangeEmailError {
+    #[error("Invalid token provided")]
+    InvalidToken,
+
+    #[error("invalid email address")]
+    InvalidEmail,
+
+    #[error("invalid password")]
+    InvalidPassword,
+
+    #[error("incorrect credentials")]
+    IncorrectCredentials,
+
+    #[error("link is invalid or has expired")]
+    InvalidLink,
+
+    #[error("email address is already in use")]
+    EmailAlreadyInUse,
+
+    #[error("addresses in this email domain are not allowed")]
+    EmailDomainNotAllowed,
+
+    #[error(transparent)]
+    Login(#[from] LoginError),
+
+    #[error("Something went wrong, please try again later.")]
+    InternalServerError,
+}
+
+impl IntoResponse for ChangeEmailError {
+    fn into_response(self) -> axum::response::Response {
+        let status = match self {
+            ChangeEmailError::InvalidToken => StatusCode::UNAUTHORIZED,
+            ChangeEmailError::InvalidEmail => StatusCode::UNPROCESSABLE_ENTITY,
+            ChangeEmailError::InvalidPassword => StatusCode::UNPROCESSABLE_ENTITY,
+            ChangeEmailError::IncorrectCredentials => StatusCode::UNAUTHORIZED,
+            ChangeEmailError::InvalidLink => StatusCode::BAD_REQUEST,
+            ChangeEmailError::EmailAlreadyInUse => StatusCode::CONFLICT,
+            ChangeEmailError::EmailDomainNotAllowed => StatusCode::FORBIDDEN,
+            ChangeEmailError::Login(e) => return e.into_response(),
+            ChangeEmailError::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        (status, self.to_string()).into_response()
+    }
+}
@@ -0,0 +1,49 @@
+use axum::{http::StatusCode, response::IntoResponse, Json};
+use thiserror::Error;
+
+use super::LoginError;
+use crate::domain::PasswordPolicyResponse;
+use crate::validation::PasswordPolicyViolations;
+
+#[derive(Error, Debug)]
+pub enum ChangePasswordError {
+    #[error("Invalid token provided")]
+    InvalidToken,
+
+    #[error("invalid current password")]
+    InvalidPassword,
+
+    #[error("password does not meet the requirements: {0}")]
+    PasswordPolicy(PasswordPolicyViolations),
+
+    #[error("this password has appeared in a data breach, please choose a different one")]
+    BreachedPassword,
+
+    #[error("incorrect credentials")]
+    IncorrectCredentials,
+
+    #[error(transparent)]
+    Login(#[from] LoginError),
+
+    #[error("Something went wrong, please try again later.")]
+    InternalServerError,
+}
+
+impl IntoResponse for ChangePasswordError {
+    fn into_response(self) -> axum::response::Response {
+        let status = match self {
+            ChangePasswordError::InvalidToken => StatusCode::UNAUTHORIZED,
+            ChangePasswordError::InvalidPassword => StatusCode::UNPROCESSABLE_ENTITY,
+            ChangePasswordError::PasswordPolicy(violations) => {
+                let body = PasswordPolicyResponse::from(violations);
+                return (StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response();
+            }
+            ChangePasswordError::BreachedPassword => StatusCode::UNPROCESSABLE_ENTITY,
+            ChangePasswordError::IncorrectCredentials => StatusCode::UNAUTHORIZED,
+            ChangePasswordError::Login(e) => return e.into_response(),
+            ChangePasswordError::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        (status, self.to_string()).into_response()
+    }
+}
@@ -0,0 +1,36 @@
+use axum::{http::StatusCode, response::IntoResponse};
+use thiserror::Error;
+
+use super::LoginError;
+
+#[derive(Error, Debug)]
+pub enum DeleteAccountError {
+    #[error("Invalid token provided")]
+    InvalidToken,
+
+    #[error("invalid password")]
+    InvalidPassword,
+
+    #[error("incorrect credentials")]
+    IncorrectCredentials,
+
+    #[error(transparent)]
+    Login(#[from] LoginError),
+
+    #[error("Something went wrong, please try again later.")]
+    InternalServerError,
+}
+
+impl IntoResponse for DeleteAccountError {
+    fn into_response(self) -> axum::response::Response {
+        let status = match self {
+            DeleteAccountError::InvalidToken => StatusCode::UNAUTHORIZED,
+            DeleteAccountError::InvalidPassword => StatusCode::UNPROCESSABLE_ENTITY,
+            DeleteAccountError::IncorrectCredentials => StatusCode::UNAUTHORIZED,
+            DeleteAccountError::Login(e) => return e.into_response(),
+            DeleteAccountError::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        (status, self.to_string()).into_response()
+    }
+}
@@ -1,4 +1,7 @@
-use axum::{http::StatusCode, response::IntoResponse};
+use axum::{
+    http::{header, StatusCode},
+    response::IntoResponse,
+};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -9,7 +12,7 @@ pub enum LoginError {
     #[error("invalid email address")]
     InvalidEmail,
 
-    #[error("password must be at least 8 characters long, contain at least one uppercase letter and one special character.")]
+    #[error("invalid password")]
     InvalidPassword,
 
     #[error("Something went wrong, please try again later.")]
@@ -17,6 +20,21 @@ pub enum LoginError {
 
     #[error("User with email {0} not found.")]
     UserNotFound(String),
+
+    #[error("email address has not been verified")]
+    EmailNotVerified,
+
+    #[error("incorrect credentials")]
+    IncorrectCredentials,
+
+    #[error("account is locked, try again in {0} seconds")]
+    AccountLocked(i64),
+
+    #[error("account has been disabled")]
+    AccountDisabled,
+
+    #[error("account is suspended until {}", .0.to_rfc3339())]
+    AccountSuspended(chrono::DateTime<chrono::Utc>),
 }
 
 impl IntoResponse for LoginError {
@@ -27,6 +45,18 @@ impl IntoResponse for LoginError {
             LoginError::InvalidPassword => StatusCode::UNPROCESSABLE_ENTITY,
             LoginError::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
             LoginError::UserNotFound(_) => StatusCode::UNAUTHORIZED,
+            LoginError::EmailNotVerified => StatusCode::FORBIDDEN,
+            LoginError::IncorrectCredentials => StatusCode::UNAUTHORIZED,
+            LoginError::AccountDisabled => StatusCode::FORBIDDEN,
+            LoginError::AccountSuspended(_) => StatusCode::FORBIDDEN,
+            LoginError::AccountLocked(retry_after) => {
+                return (
+                    StatusCode::LOCKED,
+                    [(header::RETRY_AFTER, retry_after.to_string())],
+                    self.to_string(),
+                )
+                    .into_response()
+            }
         };
 
         (status, self.to_string()).into_response()
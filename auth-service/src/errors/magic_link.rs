@@ -0,0 +1,32 @@
+use axum::{http::StatusCode, response::IntoResponse};
+use thiserror::Error;
+
+use super::LoginError;
+
+#[derive(Error, Debug)]
+pub enum MagicLinkError {
+    #[error("invalid email address")]
+    InvalidEmail,
+
+    #[error("login link is invalid or has expired")]
+    InvalidLink,
+
+    #[error(transparent)]
+    Login(#[from] LoginError),
+
+    #[error("Something went wrong, please try again later.")]
+    InternalServerError,
+}
+
+impl IntoResponse for MagicLinkError {
+    fn into_response(self) -> axum::response::Response {
+        let status = match self {
+            MagicLinkError::InvalidEmail => StatusCode::UNPROCESSABLE_ENTITY,
+            MagicLinkError::InvalidLink => StatusCode::UNAUTHORIZED,
+            MagicLinkError::Login(e) => return e.into_response(),
+            MagicLinkError::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        (status, self.to_string()).into_response()
+    }
+}
@@ -0,0 +1,26 @@
+use axum::{http::StatusCode, response::IntoResponse};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum MeError {
+    #[error("Invalid token provided")]
+    InvalidToken,
+
+    #[error("invalid profile: {}", .0.join("; "))]
+    InvalidProfile(Vec<String>),
+
+    #[error("Something went wrong, please try again later.")]
+    InternalServerError,
+}
+
+impl IntoResponse for MeError {
+    fn into_response(self) -> axum::response::Response {
+        let status = match self {
+            MeError::InvalidToken => StatusCode::UNAUTHORIZED,
+            MeError::InvalidProfile(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            MeError::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        (status, self.to_string()).into_response()
+    }
+}
@@ -0,0 +1,44 @@
+use axum::{http::StatusCode, response::IntoResponse};
+use thiserror::Error;
+
+use super::{LoginError, VerifyMfaError};
+
+#[derive(Error, Debug)]
+pub enum MfaSettingsError {
+    #[error("Invalid token provided")]
+    InvalidToken,
+
+    #[error("invalid password")]
+    InvalidPassword,
+
+    #[error("incorrect credentials")]
+    IncorrectCredentials,
+
+    #[error("2fa is not enabled for this account")]
+    NotEnabled,
+
+    #[error(transparent)]
+    Code(#[from] VerifyMfaError),
+
+    #[error(transparent)]
+    Login(#[from] LoginError),
+
+    #[error("Something went wrong, please try again later.")]
+    InternalServerError,
+}
+
+impl IntoResponse for MfaSettingsError {
+    fn into_response(self) -> axum::response::Response {
+        let status = match self {
+            MfaSettingsError::InvalidToken => StatusCode::UNAUTHORIZED,
+            MfaSettingsError::InvalidPassword => StatusCode::UNPROCESSABLE_ENTITY,
+            MfaSettingsError::IncorrectCredentials => StatusCode::UNAUTHORIZED,
+            MfaSettingsError::NotEnabled => StatusCode::CONFLICT,
+            MfaSettingsError::Code(e) => return e.into_response(),
+            MfaSettingsError::Login(e) => return e.into_response(),
+            MfaSettingsError::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        (status, self.to_string()).into_response()
+    }
+}
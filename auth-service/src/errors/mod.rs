@@ -1,11 +1,33 @@
+mod admin;
+mod change_email;
+mod change_password;
+mod delete_account;
 mod login;
 mod logout;
+mod magic_link;
+mod me;
+mod mfa_settings;
+mod password_reset;
+mod resend_mfa;
 mod signup;
+mod trusted_devices;
+mod verify_email;
 mod verify_mfa;
 mod verify_token;
 
+pub use admin::*;
+pub use change_email::*;
+pub use change_password::*;
+pub use delete_account::*;
 pub use login::*;
 pub use logout::*;
+pub use magic_link::*;
+pub use me::*;
+pub use mfa_settings::*;
+pub use password_reset::*;
+pub use resend_mfa::*;
 pub use signup::*;
+pub use trusted_devices::*;
+pub use verify_email::*;
 pub use verify_mfa::*;
 pub use verify_token::*;
@@ -0,0 +1,36 @@
+use axum::{http::StatusCode, response::IntoResponse, Json};
+use thiserror::Error;
+
+use crate::domain::PasswordPolicyResponse;
+use crate::validation::PasswordPolicyViolations;
+
+#[derive(Error, Debug)]
+pub enum PasswordResetError {
+    #[error("password does not meet the requirements: {0}")]
+    PasswordPolicy(PasswordPolicyViolations),
+
+    #[error("this password has appeared in a data breach, please choose a different one")]
+    BreachedPassword,
+
+    #[error("reset link is invalid or has expired")]
+    InvalidToken,
+
+    #[error("Something went wrong, please try again later.")]
+    InternalServerError,
+}
+
+impl IntoResponse for PasswordResetError {
+    fn into_response(self) -> axum::response::Response {
+        let status = match self {
+            PasswordResetError::PasswordPolicy(violations) => {
+                let body = PasswordPolicyResponse::from(violations);
+                return (StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response();
+            }
+            PasswordResetError::BreachedPassword => StatusCode::UNPROCESSABLE_ENTITY,
+            PasswordResetError::InvalidToken => StatusCode::BAD_REQUEST,
+            PasswordResetError::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        (status, self.to_string()).into_response()
+    }
+}
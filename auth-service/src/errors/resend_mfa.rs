@@ -0,0 +1,38 @@
+use axum::{http::StatusCode, response::IntoResponse};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ResendMfaError {
+    #[error("malformed json: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("invalid email address")]
+    InvalidEmail,
+
+    #[error("invalid login request id")]
+    InvalidLoginRequestId,
+
+    #[error("please wait before requesting another 2fa code")]
+    Cooldown,
+
+    #[error("too many 2fa codes requested, please log in again")]
+    TooManyResends,
+
+    #[error("Something went wrong, please try again later.")]
+    InternalServerError,
+}
+
+impl IntoResponse for ResendMfaError {
+    fn into_response(self) -> axum::response::Response {
+        let status = match self {
+            ResendMfaError::Json(_) => StatusCode::BAD_REQUEST,
+            ResendMfaError::InvalidEmail => StatusCode::UNAUTHORIZED,
+            ResendMfaError::InvalidLoginRequestId => StatusCode::UNAUTHORIZED,
+            ResendMfaError::Cooldown => StatusCode::TOO_MANY_REQUESTS,
+            ResendMfaError::TooManyResends => StatusCode::TOO_MANY_REQUESTS,
+            ResendMfaError::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        (status, self.to_string()).into_response()
+    }
+}
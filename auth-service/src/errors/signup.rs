@@ -1,6 +1,9 @@
-use axum::{http::StatusCode, response::IntoResponse};
+use axum::{http::StatusCode, response::IntoResponse, Json};
 use thiserror::Error;
 
+use crate::domain::PasswordPolicyResponse;
+use crate::validation::PasswordPolicyViolations;
+
 #[derive(Error, Debug)]
 pub enum SignupError {
     #[error("malformed json: {0}")]
@@ -9,8 +12,32 @@ pub enum SignupError {
     #[error("invalid email address")]
     InvalidEmail,
 
-    #[error("password must be at least 8 characters long, contain at least one uppercase letter and one special character.")]
-    InvalidPassword,
+    #[error("password does not meet the requirements: {0}")]
+    PasswordPolicy(PasswordPolicyViolations),
+
+    #[error("this password has appeared in a data breach, please choose a different one")]
+    BreachedPassword,
+
+    #[error("terms of service version {0} must be accepted to sign up")]
+    TermsNotAccepted(String),
+
+    #[error("signups from this email domain are not allowed")]
+    EmailDomainNotAllowed,
+
+    #[error("an invitation code is required to sign up")]
+    InvitationRequired,
+
+    #[error("invalid invitation code")]
+    InvalidInvitation,
+
+    #[error("invitation code has expired")]
+    InvitationExpired,
+
+    #[error("invitation code has already been used")]
+    InvitationUsed,
+
+    #[error("invitation code was issued for a different email address")]
+    InvitationEmailMismatch,
 
     #[error("Something went wrong, please try again later.")]
     InternalServerError,
@@ -24,7 +51,18 @@ impl IntoResponse for SignupError {
         let status = match self {
             SignupError::Json(_) => StatusCode::BAD_REQUEST,
             SignupError::InvalidEmail => StatusCode::UNPROCESSABLE_ENTITY,
-            SignupError::InvalidPassword => StatusCode::UNPROCESSABLE_ENTITY,
+            SignupError::PasswordPolicy(violations) => {
+                let body = PasswordPolicyResponse::from(violations);
+                return (StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response();
+            }
+            SignupError::BreachedPassword => StatusCode::UNPROCESSABLE_ENTITY,
+            SignupError::TermsNotAccepted(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            SignupError::EmailDomainNotAllowed
+            | SignupError::InvitationRequired
+            | SignupError::InvalidInvitation
+            | SignupError::InvitationExpired
+            | SignupError::InvitationUsed
+            | SignupError::InvitationEmailMismatch => StatusCode::FORBIDDEN,
             SignupError::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
             SignupError::UserAlreadyExists(_) => StatusCode::CONFLICT,
         };
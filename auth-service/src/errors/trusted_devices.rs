@@ -0,0 +1,26 @@
+use axum::{http::StatusCode, response::IntoResponse};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum TrustedDeviceError {
+    #[error("Something went wrong, please try again later.")]
+    InternalServerError,
+
+    #[error("Invalid token provided")]
+    InvalidToken,
+
+    #[error("Trusted device not found")]
+    DeviceNotFound,
+}
+
+impl IntoResponse for TrustedDeviceError {
+    fn into_response(self) -> axum::response::Response {
+        let status = match self {
+            TrustedDeviceError::InvalidToken => StatusCode::UNAUTHORIZED,
+            TrustedDeviceError::DeviceNotFound => StatusCode::NOT_FOUND,
+            TrustedDeviceError::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        (status, self.to_string()).into_response()
+    }
+}
@@ -0,0 +1,26 @@
+use axum::{http::StatusCode, response::IntoResponse};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum VerifyEmailError {
+    #[error("invalid email address")]
+    InvalidEmail,
+
+    #[error("verification link is invalid or has expired")]
+    InvalidLink,
+
+    #[error("Something went wrong, please try again later.")]
+    InternalServerError,
+}
+
+impl IntoResponse for VerifyEmailError {
+    fn into_response(self) -> axum::response::Response {
+        let status = match self {
+            VerifyEmailError::InvalidEmail => StatusCode::UNPROCESSABLE_ENTITY,
+            VerifyEmailError::InvalidLink => StatusCode::BAD_REQUEST,
+            VerifyEmailError::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        (status, self.to_string()).into_response()
+    }
+}
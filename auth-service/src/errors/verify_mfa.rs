@@ -1,6 +1,8 @@
 use axum::{http::StatusCode, response::IntoResponse};
 use thiserror::Error;
 
+use super::LoginError;
+
 #[derive(Error, Debug)]
 pub enum VerifyMfaError {
     #[error("malformed json: {0}")]
@@ -18,6 +20,12 @@ pub enum VerifyMfaError {
     #[error("code has been used before")]
     OldCode,
 
+    #[error("too many incorrect 2fa codes, please log in again")]
+    TooManyAttempts,
+
+    #[error(transparent)]
+    Login(#[from] LoginError),
+
     #[error("Something went wrong, please try again later.")]
     InternalServerError,
 }
@@ -30,6 +38,8 @@ impl IntoResponse for VerifyMfaError {
             VerifyMfaError::InvalidLoginRequestId => StatusCode::UNAUTHORIZED,
             VerifyMfaError::InvalidMFACode => StatusCode::UNAUTHORIZED,
             VerifyMfaError::OldCode => StatusCode::UNAUTHORIZED,
+            VerifyMfaError::TooManyAttempts => StatusCode::TOO_MANY_REQUESTS,
+            VerifyMfaError::Login(e) => return e.into_response(),
             VerifyMfaError::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
         };
 
@@ -2,12 +2,16 @@ use crate::proto::auth_server::AuthServer;
 use crate::routes::AuthGrpc;
 use app_state::AppState;
 use axum::{
-    routing::{delete, post},
+    routing::{delete, get, post, put},
     Router,
 };
 use axum_server::bind;
-use routes::{delete_account, login, logout, signup, verify_mfa, verify_token};
-use std::{error::Error, future::Future, pin::Pin};
+use routes::{
+    admin, change_email, change_password, delete_account, login, logout, magic_link, me,
+    mfa_settings, password_reset, resend_mfa, signup, terms, trusted_devices, verify_email,
+    verify_mfa, verify_token,
+};
+use std::{error::Error, future::Future, net::SocketAddr, pin::Pin};
 use tonic::transport::server::Router as GrpcRouter;
 use tonic::transport::Error as GrpcError;
 use tonic::transport::Server;
@@ -53,11 +57,56 @@ pub fn app_router(app_state: AppState) -> Router {
     Router::new()
         .nest_service("/", ServeDir::new("assets"))
         .route("/signup", post(signup::signup))
+        .route("/terms", get(terms::get_terms))
+        .route("/verify-email", get(verify_email::verify_email))
+        .route(
+            "/verify-email/resend",
+            post(verify_email::resend_verification_email),
+        )
         .route("/login", post(login::login))
         .route("/verify-2fa", post(verify_mfa::verify_mfa))
+        .route("/resend-2fa", post(resend_mfa::resend_mfa))
+        .route("/enable-2fa", post(mfa_settings::enable_mfa))
+        .route("/disable-2fa", post(mfa_settings::disable_mfa))
+        .route("/magic-link", post(magic_link::request_magic_link))
+        .route("/magic-link/consume", get(magic_link::consume_magic_link))
+        .route(
+            "/password-reset/request",
+            post(password_reset::request_password_reset),
+        )
+        .route(
+            "/password-reset/confirm",
+            post(password_reset::confirm_password_reset),
+        )
+        .route("/me", get(me::get_me).patch(me::update_me))
+        .route("/me/export", get(me::export_me))
+        .route("/change-password", post(change_password::change_password))
+        .route("/change-email", post(change_email::request_email_change))
+        .route(
+            "/change-email/confirm",
+            get(change_email::confirm_email_change),
+        )
+        .route(
+            "/change-email/cancel",
+            get(change_email::cancel_email_change),
+        )
         .route("/logout", post(logout::logout))
         .route("/verify-token", post(verify_token::verify_token))
         .route("/delete-account", delete(delete_account::delete_account))
+        .route(
+            "/trusted-devices",
+            get(trusted_devices::list_trusted_devices)
+                .delete(trusted_devices::revoke_all_trusted_devices),
+        )
+        .route(
+            "/trusted-devices/:device_id",
+            delete(trusted_devices::revoke_trusted_device),
+        )
+        .route("/admin/users/:email/unlock", post(admin::unlock_account))
+        .route("/admin/users/:email/status", put(admin::set_account_status))
+        .route("/admin/users/:email/export", get(admin::export_user_data))
+        .route("/admin/users/import", post(admin::import_users))
+        .route("/admin/invitations", post(admin::create_invitation))
         .with_state(app_state)
         .layer(ServiceBuilder::new().layer(TraceLayer::new_for_http()))
 }
@@ -95,7 +144,8 @@ impl Application {
 
         let grpc_future = create_grpc_server(app_state.clone()).serve(grpc_address.parse()?);
 
-        let http_future = bind(address.parse()?).serve(http_router.into_make_service());
+        let http_future = bind(address.parse()?)
+            .serve(http_router.into_make_service_with_connect_info::<SocketAddr>());
 
         Ok(Self {
             http_future: Box::pin(http_future),
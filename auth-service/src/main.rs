@@ -1,12 +1,14 @@
-use auth_service::app_state::AppState;
+use auth_service::app_state::{AppState, UserStoreType};
 use auth_service::migrations;
 
 use auth_service::services::{
-    HashmapTwoFACodeStore, MockEmailClient, RedisRefreshStore, RedisService, SqlUserStore,
-    TokenService,
+    BreachedPasswordFilter, DisposableDomainList, HashmapTwoFACodeStore, MockEmailClient,
+    RedisOneTimeTokenStore, RedisRefreshStore, RedisService, RedisTrustedDeviceStore,
+    SqlInvitationStore, SqlTermsStore, SqlUserStore, TokenService,
 };
 use auth_service::utils::Config;
 use auth_service::{get_db_pool, Application};
+use chrono::Utc;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use welds::connections::any::AnyClient;
@@ -17,27 +19,47 @@ async fn main() {
     let config = Arc::new(RwLock::new(
         Config::default().expect("Failed to load config"),
     ));
+    let redis_service = Arc::new(RedisService::new(config.read().await.redis_host()));
+    let db_client = get_configured_db_connection(config.read().await.db_url()).await;
+    let user_store: UserStoreType = Arc::new(RwLock::new(
+        SqlUserStore::new(db_client.clone())
+            .with_argon2_params(config.read().await.argon2_params().clone()),
+    ));
+    let refresh_store = RedisRefreshStore::new(redis_service.clone());
+    let mut trusted_device_store = RedisTrustedDeviceStore::new(redis_service.clone());
+    revoke_legacy_redis_entries(&refresh_store, &mut trusted_device_store).await;
     let token_service = Arc::new(RwLock::new(
-        TokenService::new(
-            config.clone(),
-            Box::new(RedisRefreshStore::new(Arc::new(RedisService::new(
-                config.read().await.redis_host(),
-            )))),
-        )
-        .await,
+        TokenService::new(config.clone(), Box::new(refresh_store))
+            .await
+            .with_user_store(user_store.clone()),
     ));
     let twofa_code_store = Arc::new(RwLock::new(HashmapTwoFACodeStore::default()));
     let email_client = Arc::new(RwLock::new(MockEmailClient::default()));
-    let db_client = get_configured_db_connection(config.read().await.db_url()).await;
-    let user_store = SqlUserStore::new(db_client.clone());
-    let app_state = AppState::new(
-        Arc::new(RwLock::new(user_store)),
+    let trusted_device_store = Arc::new(RwLock::new(trusted_device_store));
+    let one_time_token_store = Arc::new(RwLock::new(RedisOneTimeTokenStore::new(redis_service)));
+    let invitation_store = Arc::new(RwLock::new(SqlInvitationStore::new(db_client.clone())));
+    let terms_store = Arc::new(RwLock::new(SqlTermsStore::new(db_client.clone())));
+    let mut app_state = AppState::new(
+        user_store,
         token_service,
         config.clone(),
         twofa_code_store,
         email_client,
         db_client,
-    );
+    )
+    .with_trusted_device_store(trusted_device_store)
+    .with_one_time_token_store(one_time_token_store)
+    .with_invitation_store(invitation_store)
+    .with_terms_store(terms_store);
+    if let Some(path) = config.read().await.breached_passwords_path() {
+        let filter =
+            BreachedPasswordFilter::load(path).expect("Failed to load breached password filter");
+        app_state = app_state.with_breached_passwords(Arc::new(filter));
+    }
+    if let Some(path) = config.read().await.disposable_domains_path() {
+        let list = DisposableDomainList::load(path).expect("Failed to load disposable domain list");
+        app_state = app_state.with_disposable_domains(Arc::new(list));
+    }
     let app = Application::build(app_state, "0.0.0.0:3000", "0.0.0.0:50051")
         .await
         .expect("Failed to build app");
@@ -47,7 +69,30 @@ async fn main() {
 
 async fn get_configured_db_connection(db_url: &str) -> AnyClient {
     let db_client = get_db_pool(db_url).await.unwrap();
-    //log the migration error to tracing layer
-    let _ = migrations::up(&db_client).await;
+    if let Err(e) = migrations::up(&db_client).await {
+        panic!("Failed to run migrations: {}", e);
+    }
     db_client
 }
+
+/// Sessions and trusted devices are keyed by email address, so those saved
+/// under an address from before canonicalization are revoked at startup.
+async fn revoke_legacy_redis_entries(
+    refresh_store: &RedisRefreshStore,
+    trusted_device_store: &mut RedisTrustedDeviceStore,
+) {
+    match refresh_store.revoke_legacy_sessions(Utc::now()).await {
+        Ok(0) => {}
+        Ok(count) => println!("revoked {count} session(s) stored under non-canonical emails"),
+        Err(_) => eprintln!("warning: could not revoke sessions stored under non-canonical emails"),
+    }
+    match trusted_device_store.revoke_legacy_devices().await {
+        Ok(0) => {}
+        Ok(count) => {
+            println!("revoked {count} trusted device(s) stored under non-canonical emails")
+        }
+        Err(_) => {
+            eprintln!("warning: could not revoke trusted devices stored under non-canonical emails")
+        }
+    }
+}
@@ -0,0 +1,10 @@
+use welds::errors::Result;
+use welds::migrations::prelude::*;
+
+pub(super) fn step(_state: &TableState) -> Result<MigrationStep> {
+    // Accounts created before verification existed are treated as verified;
+    // new signups write the column explicitly.
+    let m = Manual::up("ALTER TABLE users ADD COLUMN email_verified BOOLEAN NOT NULL DEFAULT TRUE")
+        .down("ALTER TABLE users DROP COLUMN email_verified");
+    Ok(MigrationStep::new("add_email_verified_to_users", m))
+}
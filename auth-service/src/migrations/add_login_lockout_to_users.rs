@@ -0,0 +1,21 @@
+use welds::errors::Result;
+use welds::migrations::prelude::*;
+
+use super::Combined;
+
+pub(super) fn step(state: &TableState) -> Result<MigrationStep> {
+    // The builder cannot give a column a default, which existing rows need
+    let counters = Manual::up(
+        "ALTER TABLE users ADD COLUMN failed_login_attempts INTEGER NOT NULL DEFAULT 0; \
+         ALTER TABLE users ADD COLUMN lockout_count INTEGER NOT NULL DEFAULT 0",
+    )
+    .down(
+        "ALTER TABLE users DROP COLUMN lockout_count; \
+         ALTER TABLE users DROP COLUMN failed_login_attempts",
+    );
+    let locked_until = change_table(state, "users")?
+        .add_column("locked_until", Type::IntBig)
+        .null();
+    let m = Combined(vec![Box::new(counters), Box::new(locked_until)]);
+    Ok(MigrationStep::new("add_login_lockout_to_users", m))
+}
@@ -0,0 +1,18 @@
+use welds::errors::Result;
+use welds::migrations::prelude::*;
+
+use super::Combined;
+
+pub(super) fn step(state: &TableState) -> Result<MigrationStep> {
+    let mut columns: Vec<Box<dyn MigrationWriter>> = Vec::new();
+    for name in ["display_name", "locale", "timezone", "avatar_url"] {
+        let column = change_table(state, "users")?
+            .add_column(name, Type::String)
+            .null();
+        columns.push(Box::new(column));
+    }
+    Ok(MigrationStep::new(
+        "add_profile_to_users",
+        Combined(columns),
+    ))
+}
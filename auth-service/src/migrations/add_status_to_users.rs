@@ -0,0 +1,22 @@
+use welds::errors::Result;
+use welds::migrations::prelude::*;
+
+use super::Combined;
+
+pub(super) fn step(state: &TableState) -> Result<MigrationStep> {
+    // The builder cannot give a column a default, which existing rows need
+    let status = Manual::up("ALTER TABLE users ADD COLUMN status TEXT NOT NULL DEFAULT 'active'")
+        .down("ALTER TABLE users DROP COLUMN status");
+    let suspended_until = change_table(state, "users")?
+        .add_column("suspended_until", Type::IntBig)
+        .null();
+    let status_reason = change_table(state, "users")?
+        .add_column("status_reason", Type::String)
+        .null();
+    let m = Combined(vec![
+        Box::new(status),
+        Box::new(suspended_until),
+        Box::new(status_reason),
+    ]);
+    Ok(MigrationStep::new("add_status_to_users", m))
+}
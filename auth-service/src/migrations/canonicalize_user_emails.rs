@@ -0,0 +1,23 @@
+use welds::errors::Result;
+use welds::migrations::prelude::*;
+
+use super::Combined;
+
+pub(super) fn step(state: &TableState) -> Result<MigrationStep> {
+    let email_display = change_table(state, "users")?
+        .add_column("email_display", Type::String)
+        .null();
+    // Addresses saved before canonicalization are plain ASCII, so LOWER()
+    // gives their canonical form. `up` only gets here once no two rows
+    // share a canonical form. Each UPDATE is its own writer, since Postgres
+    // prepares one command per statement.
+    let backfill_display = Manual::up("UPDATE users SET email_display = email");
+    let lowercase = Manual::up("UPDATE users SET email = LOWER(email) WHERE email <> LOWER(email)")
+        .down("UPDATE users SET email = email_display WHERE email_display IS NOT NULL");
+    let m = Combined(vec![
+        Box::new(email_display),
+        Box::new(backfill_display),
+        Box::new(lowercase),
+    ]);
+    Ok(MigrationStep::new("canonicalize_user_emails", m))
+}
@@ -0,0 +1,14 @@
+use welds::errors::Result;
+use welds::migrations::prelude::*;
+
+pub(super) fn step(_state: &TableState) -> Result<MigrationStep> {
+    let m = create_table("invitations")
+        .id(|c| c("id", Type::IntBig))
+        .column(|c| c("code_hash", Type::String).create_unique_index())
+        .column(|c| c("email", Type::String).is_null())
+        .column(|c| c("max_uses", Type::Int))
+        .column(|c| c("uses", Type::Int))
+        .column(|c| c("expires_at", Type::IntBig))
+        .column(|c| c("created_at", Type::IntBig));
+    Ok(MigrationStep::new("create_table_invitations", m))
+}
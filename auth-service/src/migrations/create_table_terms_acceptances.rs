@@ -0,0 +1,12 @@
+use welds::errors::Result;
+use welds::migrations::prelude::*;
+
+pub(super) fn step(_state: &TableState) -> Result<MigrationStep> {
+    let m = create_table("terms_acceptances")
+        .id(|c| c("id", Type::IntBig))
+        .column(|c| c("user_id", Type::IntBig).create_index())
+        .column(|c| c("terms_version", Type::String))
+        .column(|c| c("accepted_at", Type::IntBig))
+        .column(|c| c("ip_address", Type::String).is_null());
+    Ok(MigrationStep::new("create_table_terms_acceptances", m))
+}
@@ -1,16 +1,109 @@
-use welds::errors::Result;
+use thiserror::Error;
+use welds::errors::{Result, WeldsError};
 use welds::migrations::prelude::*;
 
-pub async fn up(client: &dyn welds::TransactStart) -> Result<()> {
-    let list: Vec<MigrationFn> = vec![create_table_users::step, add_requires_mfa_to_users::step];
-    welds::migrations::up(client, list.as_slice()).await?;
+#[derive(Error, Debug)]
+pub enum MigrationError {
+    #[error(transparent)]
+    Database(#[from] WeldsError),
+    #[error(
+        "accounts {} differ only in email case; delete all but one account of each group from \
+         the users table, then migrate again",
+        .0.iter().map(|emails| emails.join(", ")).collect::<Vec<_>>().join("; ")
+    )]
+    EmailCollisions(Vec<Vec<String>>),
+}
+
+/// Applies every pending migration. Emails are only canonicalized once no
+/// two accounts share a canonical address, so the migrations stop before
+/// `canonicalize_user_emails` while `email_collisions` finds any.
+pub async fn up(client: &dyn welds::TransactStart) -> std::result::Result<(), MigrationError> {
+    let before: Vec<MigrationFn> = vec![
+        create_table_users::step,
+        add_requires_mfa_to_users::step,
+        add_email_verified_to_users::step,
+        add_login_lockout_to_users::step,
+        add_profile_to_users::step,
+        add_status_to_users::step,
+    ];
+    welds::migrations::up(client, before.as_slice()).await?;
+
+    let collisions = {
+        let trans = client.begin().await.map_err(WeldsError::from)?;
+        email_collisions(&trans).await?
+    };
+    if !collisions.is_empty() {
+        return Err(MigrationError::EmailCollisions(collisions));
+    }
+
+    let after: Vec<MigrationFn> = vec![
+        canonicalize_user_emails::step,
+        create_table_invitations::step,
+        create_table_terms_acceptances::step,
+    ];
+    welds::migrations::up(client, after.as_slice()).await?;
     Ok(())
 }
 
 pub async fn down(client: &dyn welds::TransactStart) -> Result<Option<String>> {
+    welds::migrations::down(client, "create_table_terms_acceptances").await?;
+    welds::migrations::down(client, "create_table_invitations").await?;
+    welds::migrations::down(client, "canonicalize_user_emails").await?;
+    welds::migrations::down(client, "add_status_to_users").await?;
+    welds::migrations::down(client, "add_profile_to_users").await?;
+    welds::migrations::down(client, "add_login_lockout_to_users").await?;
+    welds::migrations::down(client, "add_email_verified_to_users").await?;
     welds::migrations::down(client, "add_requires_mfa_to_users").await?;
     welds::migrations::down(client, "create_table_users").await
 }
 
+/// Several writers run as one migration step, rolled back in reverse order.
+/// Lets a step pair `change_table` builders with the raw SQL they cannot
+/// express, such as a column default or a data backfill.
+struct Combined(Vec<Box<dyn MigrationWriter>>);
+
+impl MigrationWriter for Combined {
+    fn up_sql(&self, syntax: welds::Syntax) -> Vec<String> {
+        self.0.iter().flat_map(|w| w.up_sql(syntax)).collect()
+    }
+
+    fn down_sql(&self, syntax: welds::Syntax) -> Vec<String> {
+        self.0
+            .iter()
+            .rev()
+            .flat_map(|w| w.down_sql(syntax))
+            .collect()
+    }
+}
+
+/// Stored addresses that differ only in case, grouped by canonical form.
+pub async fn email_collisions(client: &dyn welds::Client) -> Result<Vec<Vec<String>>> {
+    let rows = client
+        .fetch_rows(
+            "SELECT email FROM users WHERE LOWER(email) IN ( \
+                 SELECT LOWER(email) FROM users GROUP BY LOWER(email) HAVING COUNT(*) > 1) \
+             ORDER BY LOWER(email), email",
+            &[],
+        )
+        .await?;
+
+    let mut groups: Vec<Vec<String>> = Vec::new();
+    for row in rows {
+        let email: String = row.get("email")?;
+        match groups.last_mut() {
+            Some(group) if group[0].to_lowercase() == email.to_lowercase() => group.push(email),
+            _ => groups.push(vec![email]),
+        }
+    }
+    Ok(groups)
+}
+
+mod add_email_verified_to_users;
+mod add_login_lockout_to_users;
+mod add_profile_to_users;
 mod add_requires_mfa_to_users;
+mod add_status_to_users;
+mod canonicalize_user_emails;
+mod create_table_invitations;
+mod create_table_terms_acceptances;
 mod create_table_users;
@@ -1,9 +1,12 @@
 use crate::app_state::AppState;
-use crate::domain::{Email, Password};
+use crate::domain::{Email, Password, PasswordPolicyResponse, TermsAcceptance};
 use crate::errors::SignupError;
-use crate::proto::{SignupRequest, SignupResponse};
+use crate::proto::{PasswordPolicyViolations, SignupRequest, SignupResponse};
 use crate::services::AuthService;
-use tonic::{Request, Response, Status};
+use crate::utils::client_ip;
+use crate::validation;
+use prost::Message;
+use tonic::{Code, Request, Response, Status};
 
 #[derive(Clone)]
 pub struct AuthGrpc {
@@ -16,17 +19,69 @@ impl crate::proto::auth_server::Auth for AuthGrpc {
         &self,
         request: Request<SignupRequest>,
     ) -> Result<Response<SignupResponse>, Status> {
+        let peer = request.remote_addr();
+        let headers = request.metadata().clone().into_headers();
         let req = request.into_inner();
         let email = Email::parse(req.email).or(Err(Status::invalid_argument("invalid email")))?;
-        let password =
-            Password::parse(req.password).or(Err(Status::invalid_argument("invalid password")))?;
-        AuthService::signup(self.state.clone(), email, password, req.requires_mfa)
-            .await
-            .map_err(|e| match e {
-                SignupError::UserAlreadyExists(message) => Status::already_exists(message),
-                SignupError::InternalServerError | _ => Status::internal("internal server error"),
-            })?;
+        let password = Password::parse_new(
+            req.password,
+            self.state.config.read().await.password_policy(),
+            &email,
+        )
+        .map_err(password_policy_status)?;
+        if self.state.is_breached_password(password.as_ref()) {
+            return Err(Status::invalid_argument(
+                SignupError::BreachedPassword.to_string(),
+            ));
+        }
+        // proto3 strings cannot be absent, so an empty code means none
+        let invitation_code = Some(req.invitation_code).filter(|code| !code.is_empty());
+        let trust_x_forwarded_for = self.state.config.read().await.trust_x_forwarded_for();
+        let accepted_terms = Some(req.accepted_terms_version)
+            .filter(|version| !version.is_empty())
+            .map(|version| {
+                TermsAcceptance::new(version, client_ip(&headers, peer, trust_x_forwarded_for))
+            });
+        AuthService::signup(
+            self.state.clone(),
+            email,
+            password,
+            req.requires_mfa,
+            invitation_code,
+            accepted_terms,
+        )
+        .await
+        .map_err(|e| match e {
+            SignupError::UserAlreadyExists(message) => Status::already_exists(message),
+            SignupError::PasswordPolicy(violations) => password_policy_status(violations),
+            e @ (SignupError::Json(_)
+            | SignupError::InvalidEmail
+            | SignupError::BreachedPassword) => Status::invalid_argument(e.to_string()),
+            e @ SignupError::TermsNotAccepted(_) => Status::failed_precondition(e.to_string()),
+            e @ (SignupError::EmailDomainNotAllowed
+            | SignupError::InvitationRequired
+            | SignupError::InvalidInvitation
+            | SignupError::InvitationExpired
+            | SignupError::InvitationUsed
+            | SignupError::InvitationEmailMismatch) => Status::permission_denied(e.to_string()),
+            SignupError::InternalServerError => Status::internal("internal server error"),
+        })?;
 
         Ok(Response::new(SignupResponse { success: true }))
     }
 }
+
+/// INVALID_ARGUMENT carrying every broken rule as `PasswordPolicyViolations`
+/// details, the same list HTTP clients get in `PasswordPolicyResponse`.
+fn password_policy_status(violations: validation::PasswordPolicyViolations) -> Status {
+    let response = PasswordPolicyResponse::from(violations);
+    let details = PasswordPolicyViolations {
+        message: response.message.clone(),
+        violations: response.violations,
+    };
+    Status::with_details(
+        Code::InvalidArgument,
+        response.message,
+        details.encode_to_vec().into(),
+    )
+}
@@ -0,0 +1,336 @@
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::Response;
+use axum::Json;
+use chrono::{Duration, Utc};
+
+use crate::app_state::AppState;
+use crate::domain::{
+    hash_one_time_token, new_one_time_token, AccountStatus, AccountStatusResponse, AdminResponse,
+    CreateInvitationRequestBody, Email, ImportRejection, ImportUsersRequestBody,
+    ImportUsersResponse, Invitation, InvitationResponse, LoginLockout, Password,
+    SetAccountStatusRequestBody, User, UserStoreError,
+};
+use crate::errors::AdminError;
+use crate::services::data_export::export_response;
+use crate::utils::is_admin;
+
+/// Lift a login lockout and forget the failures that led to it.
+pub async fn unlock_account(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(email): Path<String>,
+) -> Result<(StatusCode, Json<AdminResponse>), AdminError> {
+    if !is_admin(&state, &headers).await {
+        return Err(AdminError::Unauthorized);
+    }
+    let email = Email::parse(email).or(Err(AdminError::InvalidEmail))?;
+
+    state
+        .user_store
+        .write()
+        .await
+        .set_lockout(email, LoginLockout::default())
+        .await
+        .map_err(|e| match e {
+            UserStoreError::UserNotFound => AdminError::UserNotFound,
+            _ => AdminError::InternalServerError,
+        })?;
+
+    Ok((
+        StatusCode::OK,
+        Json(AdminResponse {
+            message: "Account unlocked".to_owned(),
+        }),
+    ))
+}
+
+/// Download everything held about an account, for answering a data-subject
+/// access request on the user's behalf.
+pub async fn export_user_data(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(email): Path<String>,
+) -> Result<Response, AdminError> {
+    if !is_admin(&state, &headers).await {
+        return Err(AdminError::Unauthorized);
+    }
+    let email = Email::parse(email).or(Err(AdminError::InvalidEmail))?;
+
+    let user = state
+        .user_store
+        .read()
+        .await
+        .get_user(email)
+        .await
+        .map_err(|e| match e {
+            UserStoreError::UserNotFound => AdminError::UserNotFound,
+            _ => AdminError::InternalServerError,
+        })?;
+
+    export_response(&state, &user)
+        .await
+        .map_err(|_| AdminError::InternalServerError)
+}
+
+/// Activate, disable or suspend an account. Any change signs the user out
+/// everywhere and drops pending 2FA logins.
+pub async fn set_account_status(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(email): Path<String>,
+    Json(request): Json<SetAccountStatusRequestBody>,
+) -> Result<(StatusCode, Json<AccountStatusResponse>), AdminError> {
+    if !is_admin(&state, &headers).await {
+        return Err(AdminError::Unauthorized);
+    }
+    let email = Email::parse(email).or(Err(AdminError::InvalidEmail))?;
+
+    let status = match (request.status.as_str(), request.suspended_until) {
+        ("active", None) => AccountStatus::Active,
+        ("disabled", None) => AccountStatus::Disabled,
+        ("suspended", Some(until)) if until > Utc::now() => AccountStatus::Suspended { until },
+        ("suspended", Some(_)) => {
+            return Err(AdminError::InvalidStatus(
+                "suspendedUntil must be in the future",
+            ))
+        }
+        ("suspended", None) => {
+            return Err(AdminError::InvalidStatus(
+                "suspended requires suspendedUntil",
+            ))
+        }
+        ("active" | "disabled", Some(_)) => {
+            return Err(AdminError::InvalidStatus(
+                "suspendedUntil is only allowed with suspended",
+            ))
+        }
+        _ => {
+            return Err(AdminError::InvalidStatus(
+                "status must be active, disabled or suspended",
+            ))
+        }
+    };
+    let reason = request
+        .reason
+        .map(|reason| reason.trim().to_owned())
+        .filter(|reason| !reason.is_empty());
+    if reason
+        .as_ref()
+        .is_some_and(|reason| reason.chars().count() > 500)
+    {
+        return Err(AdminError::InvalidStatus(
+            "reason must be at most 500 characters",
+        ));
+    }
+
+    let user = state
+        .user_store
+        .write()
+        .await
+        .set_status(email.clone(), status, reason.clone())
+        .await
+        .map_err(|e| match e {
+            UserStoreError::UserNotFound => AdminError::UserNotFound,
+            _ => AdminError::InternalServerError,
+        })?;
+
+    state
+        .token_service
+        .read()
+        .await
+        .revoke_user_sessions(email.as_ref(), None)
+        .await
+        .map_err(|_| AdminError::InternalServerError)?;
+    state
+        .twofa_token_store
+        .write()
+        .await
+        .remove_codes_for(&email)
+        .await
+        .map_err(|_| AdminError::InternalServerError)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(AccountStatusResponse {
+            email: user.email.as_ref().to_owned(),
+            status: user.status.as_str().to_owned(),
+            suspended_until: user.status.suspended_until(),
+            reason,
+        }),
+    ))
+}
+
+const MAX_IMPORT_BATCH: usize = 1000;
+
+/// Create accounts from another system, keeping their existing password
+/// hashes, in a single transaction. Bad records are reported back and do not
+/// stop the rest.
+pub async fn import_users(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<ImportUsersRequestBody>,
+) -> Result<(StatusCode, Json<ImportUsersResponse>), AdminError> {
+    if !is_admin(&state, &headers).await {
+        return Err(AdminError::Unauthorized);
+    }
+    if request.users.len() > MAX_IMPORT_BATCH {
+        return Err(AdminError::ImportTooLarge(MAX_IMPORT_BATCH));
+    }
+
+    let mut rejected = Vec::new();
+    let mut accepted = Vec::new();
+    let mut users = Vec::new();
+    for (index, record) in request.users.into_iter().enumerate() {
+        let Ok(email) = Email::parse(record.email.clone()) else {
+            rejected.push(ImportRejection {
+                index,
+                email: record.email,
+                error: "invalid email address".to_owned(),
+            });
+            continue;
+        };
+        // Imported accounts get the same domain gate as signups
+        if !state.permits_email_domain(&email).await {
+            rejected.push(ImportRejection {
+                index,
+                email: record.email,
+                error: "email domain is not allowed".to_owned(),
+            });
+            continue;
+        }
+        let mut user = User::new(
+            email,
+            Password::from_hash(record.password_hash),
+            record.requires_mfa,
+        );
+        user.email_verified = record.email_verified;
+        accepted.push((index, record.email));
+        users.push(user);
+    }
+
+    let results = state
+        .user_store
+        .write()
+        .await
+        .import_users(users, false)
+        .await
+        .map_err(|_| AdminError::InternalServerError)?;
+    let mut imported = 0;
+    for ((index, email), result) in accepted.into_iter().zip(results) {
+        let error = match result {
+            Ok(()) => {
+                imported += 1;
+                continue;
+            }
+            Err(UserStoreError::UserAlreadyExists) => "user already exists",
+            Err(UserStoreError::UnsupportedPasswordHash) => {
+                "unsupported password hash format or cost"
+            }
+            Err(_) => return Err(AdminError::InternalServerError),
+        };
+        rejected.push(ImportRejection {
+            index,
+            email,
+            error: error.to_owned(),
+        });
+    }
+    rejected.sort_by_key(|rejection| rejection.index);
+
+    Ok((
+        StatusCode::OK,
+        Json(ImportUsersResponse { imported, rejected }),
+    ))
+}
+
+/// Create an invitation for invite-only signup and optionally email its
+/// signup link. The code is returned once; only its hash is stored.
+pub async fn create_invitation(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<CreateInvitationRequestBody>,
+) -> Result<(StatusCode, Json<InvitationResponse>), AdminError> {
+    if !is_admin(&state, &headers).await {
+        return Err(AdminError::Unauthorized);
+    }
+    let email = request
+        .email
+        .map(|email| Email::parse(email).or(Err(AdminError::InvalidEmail)))
+        .transpose()?;
+    if request.send_email && email.is_none() {
+        return Err(AdminError::InvalidInvitation("sendEmail requires email"));
+    }
+    let max_uses = request.max_uses.unwrap_or(1);
+    if max_uses == 0 {
+        return Err(AdminError::InvalidInvitation("maxUses must be at least 1"));
+    }
+    if request
+        .expires_in_seconds
+        .is_some_and(|seconds| seconds <= 0)
+    {
+        return Err(AdminError::InvalidInvitation(
+            "expiresInSeconds must be positive",
+        ));
+    }
+
+    let code = new_one_time_token();
+    let now = Utc::now();
+    let (code_hash, expires_at, signup_url) = {
+        let config = state.config.read().await;
+        let ttl_seconds = request
+            .expires_in_seconds
+            .unwrap_or(config.invitation_ttl_seconds());
+        let expires_at = Duration::try_seconds(ttl_seconds)
+            .and_then(|ttl| now.checked_add_signed(ttl))
+            .ok_or(AdminError::InvalidInvitation(
+                "expiresInSeconds is too large",
+            ))?;
+        (
+            hash_one_time_token(config.invitation_key(), &code),
+            expires_at,
+            format!(
+                "{}/?invite={}",
+                config.public_base_url().trim_end_matches('/'),
+                code
+            ),
+        )
+    };
+
+    // Sent before the invitation is stored, so a failed send does not leave
+    // behind a live code nobody received
+    if let Some(email) = email.as_ref().filter(|_| request.send_email) {
+        state
+            .email_client
+            .read()
+            .await
+            .send_email(email, "you are invited to sign up", &signup_url)
+            .await
+            .map_err(|_| AdminError::InternalServerError)?;
+    }
+
+    state
+        .invitation_store
+        .write()
+        .await
+        .add_invitation(Invitation {
+            code_hash,
+            email: email.as_ref().map(|email| email.as_ref().to_owned()),
+            max_uses,
+            uses: 0,
+            expires_at,
+            created_at: now,
+        })
+        .await
+        .map_err(|_| AdminError::InternalServerError)?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(InvitationResponse {
+            code,
+            email: email.map(|email| email.display().to_owned()),
+            max_uses,
+            expires_at,
+            signup_url,
+        }),
+    ))
+}
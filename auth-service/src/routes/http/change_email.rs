@@ -0,0 +1,286 @@
+use axum::extract::{Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as B64_URL;
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+
+use crate::app_state::AppState;
+use crate::domain::{
+    hash_one_time_token, new_one_time_token, ChangeEmailRequestBody, ChangeEmailResponse,
+    ChangeEmailTokenQuery, Email, Password, TokenPurpose, UserStoreError,
+};
+use crate::errors::{ChangeEmailError, LoginError};
+use crate::services::AuthService;
+use crate::utils::bearer_claims;
+
+/// Start moving the caller's account to a new address. Nothing changes until
+/// the link sent to the new address is followed; the old address gets a
+/// notice with a link that cancels the pending change.
+pub async fn request_email_change(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<ChangeEmailRequestBody>,
+) -> Result<(StatusCode, Json<ChangeEmailResponse>), ChangeEmailError> {
+    let claims = bearer_claims(&state, &headers)
+        .await
+        .ok_or(ChangeEmailError::InvalidToken)?;
+    let email = Email::parse(claims.sub).or(Err(ChangeEmailError::InvalidToken))?;
+    let new_email = Email::parse(request.new_email).or(Err(ChangeEmailError::InvalidEmail))?;
+    let password = Password::parse(request.password).or(Err(ChangeEmailError::InvalidPassword))?;
+
+    if new_email == email {
+        return Err(ChangeEmailError::InvalidEmail);
+    }
+    // The same domains signup refuses cannot be moved to either
+    if !state.permits_email_domain(&new_email).await {
+        return Err(ChangeEmailError::EmailDomainNotAllowed);
+    }
+
+    // Wrong passwords count towards the same lockout as failed logins
+    AuthService::check_password(&state, &email, password, None)
+        .await
+        .map_err(|e| match e {
+            LoginError::IncorrectCredentials => ChangeEmailError::IncorrectCredentials,
+            LoginError::UserNotFound(_) => ChangeEmailError::InvalidToken,
+            e => ChangeEmailError::Login(e),
+        })?;
+
+    let confirm_token = new_one_time_token();
+    let cancel_token = new_one_time_token();
+    let (confirm_hash, cancel_hash, expires_at, base_url) = {
+        let config = state.config.read().await;
+        (
+            hash_one_time_token(config.one_time_token_key(), &confirm_token),
+            hash_one_time_token(config.one_time_token_key(), &cancel_token),
+            Utc::now() + Duration::seconds(config.change_email_ttl_seconds()),
+            config.public_base_url().trim_end_matches('/').to_owned(),
+        )
+    };
+
+    // The cancel token points at the confirm token's hash, so redeeming it
+    // burns the confirmation link.
+    store_token(
+        &state,
+        TokenPurpose::ChangeEmail,
+        confirm_hash,
+        format!("{}\n{}", email.as_ref(), new_email.as_ref()),
+        expires_at,
+    )
+    .await?;
+    store_token(
+        &state,
+        TokenPurpose::ChangeEmailCancel,
+        cancel_hash,
+        B64_URL.encode(confirm_hash),
+        expires_at,
+    )
+    .await?;
+
+    let email_client = state.email_client.read().await;
+    email_client
+        .send_email(
+            &new_email,
+            "confirm your new email address",
+            &format!("{}/change-email/confirm?token={}", base_url, confirm_token),
+        )
+        .await
+        .map_err(|_| ChangeEmailError::InternalServerError)?;
+    email_client
+        .send_email(
+            &email,
+            "your email address is changing",
+            &format!(
+                "A change of your account's email address to {} was requested. If this wasn't you, cancel it here: {}/change-email/cancel?token={}",
+                new_email.as_ref(),
+                base_url,
+                cancel_token
+            ),
+        )
+        .await
+        .map_err(|_| ChangeEmailError::InternalServerError)?;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(ChangeEmailResponse {
+            message: "A confirmation link has been sent to the new address".to_owned(),
+        }),
+    ))
+}
+
+/// Apply a pending change. Sessions, pending 2FA codes and trusted devices
+/// are all keyed by the old address, so they are revoked and the user logs in
+/// again with the new one. The link is only redeemed once the change can go
+/// ahead, so a refused attempt leaves it usable.
+pub async fn confirm_email_change(
+    State(state): State<AppState>,
+    Query(query): Query<ChangeEmailTokenQuery>,
+) -> Result<(StatusCode, Json<ChangeEmailResponse>), ChangeEmailError> {
+    let token_hash = {
+        let config = state.config.read().await;
+        hash_one_time_token(config.one_time_token_key(), &query.token)
+    };
+    let subject = state
+        .one_time_token_store
+        .read()
+        .await
+        .peek_token(TokenPurpose::ChangeEmail, &token_hash, Utc::now())
+        .await
+        .map_err(|_| ChangeEmailError::InvalidLink)?;
+    let (old_email, new_email) = subject
+        .split_once('\n')
+        .and_then(|(old, new)| {
+            Some((
+                Email::parse(old.to_owned()).ok()?,
+                Email::parse(new.to_owned()).ok()?,
+            ))
+        })
+        .ok_or(ChangeEmailError::InvalidLink)?;
+
+    {
+        let user_store = state.user_store.read().await;
+        let user = user_store
+            .get_user(old_email.clone())
+            .await
+            .map_err(|e| match e {
+                UserStoreError::UserNotFound => ChangeEmailError::InvalidLink,
+                _ => ChangeEmailError::InternalServerError,
+            })?;
+        AuthService::ensure_active(&user)?;
+        match user_store.get_user(new_email.clone()).await {
+            Ok(_) => return Err(ChangeEmailError::EmailAlreadyInUse),
+            Err(UserStoreError::UserNotFound) => {}
+            Err(_) => return Err(ChangeEmailError::InternalServerError),
+        }
+    }
+
+    state
+        .one_time_token_store
+        .write()
+        .await
+        .take_token(TokenPurpose::ChangeEmail, &token_hash, Utc::now())
+        .await
+        .map_err(|_| ChangeEmailError::InvalidLink)?;
+
+    {
+        let mut user_store = state.user_store.write().await;
+        let mut user = user_store
+            .change_email(old_email.clone(), new_email)
+            .await
+            .map_err(|e| match e {
+                UserStoreError::UserAlreadyExists => ChangeEmailError::EmailAlreadyInUse,
+                UserStoreError::UserNotFound => ChangeEmailError::InvalidLink,
+                _ => ChangeEmailError::InternalServerError,
+            })?;
+
+        state
+            .terms_store
+            .write()
+            .await
+            .change_email(old_email.as_ref(), user.email.as_ref())
+            .await
+            .map_err(|_| ChangeEmailError::InternalServerError)?;
+
+        // Following the link proves the user controls the new address
+        if !user.email_verified {
+            user.email_verified = true;
+            user_store
+                .update_user(user)
+                .await
+                .map_err(|_| ChangeEmailError::InternalServerError)?;
+        }
+    }
+
+    state
+        .token_service
+        .read()
+        .await
+        .revoke_user_sessions(old_email.as_ref(), None)
+        .await
+        .map_err(|_| ChangeEmailError::InternalServerError)?;
+    state
+        .twofa_token_store
+        .write()
+        .await
+        .remove_codes_for(&old_email)
+        .await
+        .map_err(|_| ChangeEmailError::InternalServerError)?;
+    state
+        .trusted_device_store
+        .write()
+        .await
+        .revoke_all(old_email.as_ref())
+        .await
+        .map_err(|_| ChangeEmailError::InternalServerError)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(ChangeEmailResponse {
+            message: "Email address changed, please log in again".to_owned(),
+        }),
+    ))
+}
+
+/// Withdraw a pending change from the link sent to the old address.
+pub async fn cancel_email_change(
+    State(state): State<AppState>,
+    Query(query): Query<ChangeEmailTokenQuery>,
+) -> Result<(StatusCode, Json<ChangeEmailResponse>), ChangeEmailError> {
+    let subject = take_token(&state, TokenPurpose::ChangeEmailCancel, &query.token).await?;
+    let confirm_hash: [u8; 32] = B64_URL
+        .decode(subject)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(ChangeEmailError::InvalidLink)?;
+
+    // Fails once the change has been confirmed or has expired
+    state
+        .one_time_token_store
+        .write()
+        .await
+        .take_token(TokenPurpose::ChangeEmail, &confirm_hash, Utc::now())
+        .await
+        .map_err(|_| ChangeEmailError::InvalidLink)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(ChangeEmailResponse {
+            message: "Email change cancelled".to_owned(),
+        }),
+    ))
+}
+
+async fn store_token(
+    state: &AppState,
+    purpose: TokenPurpose,
+    token_hash: [u8; 32],
+    subject: String,
+    expires_at: DateTime<Utc>,
+) -> Result<(), ChangeEmailError> {
+    state
+        .one_time_token_store
+        .write()
+        .await
+        .add_token(purpose, token_hash, subject, expires_at)
+        .await
+        .map_err(|_| ChangeEmailError::InternalServerError)
+}
+
+async fn take_token(
+    state: &AppState,
+    purpose: TokenPurpose,
+    token: &str,
+) -> Result<String, ChangeEmailError> {
+    let token_hash = {
+        let config = state.config.read().await;
+        hash_one_time_token(config.one_time_token_key(), token)
+    };
+
+    state
+        .one_time_token_store
+        .write()
+        .await
+        .take_token(purpose, &token_hash, Utc::now())
+        .await
+        .map_err(|_| ChangeEmailError::InvalidLink)
+}
@@ -0,0 +1,96 @@
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use uuid::Uuid;
+
+use crate::app_state::AppState;
+use crate::domain::{ChangePasswordRequestBody, ChangePasswordResponse, Email, Password};
+use crate::errors::{ChangePasswordError, LoginError};
+use crate::services::{password_hashing, AuthService};
+use crate::utils::bearer_claims;
+
+/// Replace the caller's password after re-checking the current one. Every
+/// other session is signed out; the one making the request stays valid.
+pub async fn change_password(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<ChangePasswordRequestBody>,
+) -> Result<(StatusCode, Json<ChangePasswordResponse>), ChangePasswordError> {
+    let claims = bearer_claims(&state, &headers)
+        .await
+        .ok_or(ChangePasswordError::InvalidToken)?;
+    let email = Email::parse(claims.sub).or(Err(ChangePasswordError::InvalidToken))?;
+    let current_session = Uuid::parse_str(&claims.sid).ok();
+
+    let current_password =
+        Password::parse(request.current_password).or(Err(ChangePasswordError::InvalidPassword))?;
+    let new_password = Password::parse_new(
+        request.new_password,
+        state.config.read().await.password_policy(),
+        &email,
+    )
+    .map_err(ChangePasswordError::PasswordPolicy)?;
+    if state.is_breached_password(new_password.as_ref()) {
+        return Err(ChangePasswordError::BreachedPassword);
+    }
+
+    // Checking the current password and hashing the new one both happen
+    // outside the write lock; it is only held to swap the hash, and only if
+    // nothing changed the password in between.
+    // Wrong passwords count towards the same lockout as failed logins.
+    let (user, _) = AuthService::check_password(&state, &email, current_password, None)
+        .await
+        .map_err(|e| match e {
+            LoginError::IncorrectCredentials => ChangePasswordError::IncorrectCredentials,
+            LoginError::UserNotFound(_) => ChangePasswordError::InvalidToken,
+            e => ChangePasswordError::Login(e),
+        })?;
+    let params = state.user_store.read().await.argon2_params();
+    let new_hash = password_hashing::stored_form(new_password.as_ref(), params)
+        .await
+        .map_err(|_| ChangePasswordError::InternalServerError)?;
+    let replaced = state
+        .user_store
+        .write()
+        .await
+        .replace_password_hash(email.clone(), user.password, Password::from_hash(new_hash))
+        .await
+        .map_err(|_| ChangePasswordError::InternalServerError)?;
+    if !replaced {
+        return Err(ChangePasswordError::IncorrectCredentials);
+    }
+
+    state
+        .token_service
+        .read()
+        .await
+        .revoke_user_sessions(email.as_ref(), current_session)
+        .await
+        .map_err(|_| ChangePasswordError::InternalServerError)?;
+    state
+        .twofa_token_store
+        .write()
+        .await
+        .remove_codes_for(&email)
+        .await
+        .map_err(|_| ChangePasswordError::InternalServerError)?;
+
+    state
+        .email_client
+        .read()
+        .await
+        .send_email(
+            &email,
+            "your password was changed",
+            "The password for your account was changed and your other sessions were signed out.",
+        )
+        .await
+        .map_err(|_| ChangePasswordError::InternalServerError)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(ChangePasswordResponse {
+            message: "Password changed".to_owned(),
+        }),
+    ))
+}
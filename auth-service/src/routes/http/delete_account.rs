@@ -1,6 +1,91 @@
-use axum::http::StatusCode;
-use axum::response::IntoResponse;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use axum_extra::extract::CookieJar;
 
-pub async fn delete_account() -> impl IntoResponse {
-    StatusCode::OK.into_response()
+use crate::app_state::AppState;
+use crate::domain::{DeleteAccountRequestBody, DeleteAccountResponse, Email, Password};
+use crate::errors::{DeleteAccountError, LoginError};
+use crate::services::AuthService;
+use crate::utils::{bearer_claims, cookie_helpers::clear_cookie};
+
+/// Delete the caller's account after re-checking their password, and tear
+/// down everything still tied to it: terms acceptances, sessions, pending
+/// 2FA challenges and trusted devices.
+pub async fn delete_account(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    jar: CookieJar,
+    Json(request): Json<DeleteAccountRequestBody>,
+) -> Result<(CookieJar, (StatusCode, Json<DeleteAccountResponse>)), DeleteAccountError> {
+    let claims = bearer_claims(&state, &headers)
+        .await
+        .ok_or(DeleteAccountError::InvalidToken)?;
+    let email = Email::parse(claims.sub).or(Err(DeleteAccountError::InvalidToken))?;
+    let password =
+        Password::parse(request.password).or(Err(DeleteAccountError::InvalidPassword))?;
+
+    // Wrong passwords count towards the same lockout as failed logins. The
+    // hash is checked under the read lock; the write lock is only taken for
+    // the deletion itself.
+    AuthService::check_password(&state, &email, password, None)
+        .await
+        .map_err(|e| match e {
+            LoginError::IncorrectCredentials => DeleteAccountError::IncorrectCredentials,
+            LoginError::UserNotFound(_) => DeleteAccountError::InvalidToken,
+            e => DeleteAccountError::Login(e),
+        })?;
+
+    state
+        .user_store
+        .write()
+        .await
+        .delete_user(email.clone())
+        .await
+        .map_err(|_| DeleteAccountError::InternalServerError)?;
+    state
+        .terms_store
+        .write()
+        .await
+        .remove_for(email.as_ref())
+        .await
+        .map_err(|_| DeleteAccountError::InternalServerError)?;
+
+    state
+        .token_service
+        .read()
+        .await
+        .revoke_user_sessions(email.as_ref(), None)
+        .await
+        .map_err(|_| DeleteAccountError::InternalServerError)?;
+    state
+        .twofa_token_store
+        .write()
+        .await
+        .remove_codes_for(&email)
+        .await
+        .map_err(|_| DeleteAccountError::InternalServerError)?;
+    state
+        .trusted_device_store
+        .write()
+        .await
+        .revoke_all(email.as_ref())
+        .await
+        .map_err(|_| DeleteAccountError::InternalServerError)?;
+
+    let jar = {
+        let config = state.config.read().await;
+        jar.add(clear_cookie(config.access_cookie_name(), "/"))
+            .add(clear_cookie(config.refresh_cookie_name(), "/refresh-token"))
+    };
+
+    Ok((
+        jar,
+        (
+            StatusCode::OK,
+            Json(DeleteAccountResponse {
+                message: "Account deleted successfully".to_string(),
+            }),
+        ),
+    ))
 }
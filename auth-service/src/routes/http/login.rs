@@ -1,29 +1,128 @@
 use crate::app_state::AppState;
-use crate::domain::{Email, LoginAttemptId, LoginRequestBody, LoginResponse, Password, TwoFACode};
+use crate::domain::{
+    verify_device_token, Email, LoginAttemptId, LoginRequestBody, LoginResponse, Password,
+    TermsAcceptance, TwoFACode, TwoFAPurpose, User,
+};
 use crate::errors::LoginError;
 use crate::services::AuthService;
+use crate::utils::client_ip;
 use crate::utils::cookie_helpers::{access_cookie, refresh_cookie};
-use axum::extract::State;
-use axum::http::StatusCode;
+use axum::extract::{ConnectInfo, State};
+use axum::http::{HeaderMap, StatusCode};
+use std::net::SocketAddr;
 
 use axum::Json;
 use axum_extra::extract::CookieJar;
+use chrono::Utc;
 
 use serde::{Deserialize, Serialize};
 
 pub async fn login(
     State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
     jar: CookieJar,
     Json(request): Json<LoginRequestBody>,
 ) -> Result<(CookieJar, (StatusCode, Json<LoginTypes>)), LoginError> {
     let email = Email::parse(request.email).or(Err(LoginError::InvalidEmail))?;
+    let typed_password = Password::as_typed(request.password.clone());
     let password = Password::parse(request.password).or(Err(LoginError::InvalidPassword))?;
-    let user = AuthService::login(state.clone(), email.clone(), password).await?;
+    let user = AuthService::login(state.clone(), email.clone(), password, typed_password).await?;
 
+    let peer = connect_info.map(|ConnectInfo(addr)| addr);
+    if let Some(response) = terms_required(
+        &state,
+        &user.email,
+        request.accepted_terms_version,
+        &headers,
+        peer,
+    )
+    .await?
+    {
+        return Ok((jar, response));
+    }
+
+    complete_login(&user, &state, jar).await
+}
+
+/// Once the terms version is bumped, the user has to accept it before a
+/// session or 2FA challenge is handed out. Returns the 403 asking for that,
+/// if still needed; an `accepted_terms_version` sent along with the request
+/// is recorded first.
+pub(crate) async fn terms_required(
+    state: &AppState,
+    email: &Email,
+    accepted_terms_version: Option<String>,
+    headers: &HeaderMap,
+    peer: Option<SocketAddr>,
+) -> Result<Option<(StatusCode, Json<LoginTypes>)>, LoginError> {
+    let (trust_x_forwarded_for, terms_url) = {
+        let config = state.config.read().await;
+        (
+            config.trust_x_forwarded_for(),
+            config.terms_url().map(str::to_owned),
+        )
+    };
+    let accepted_terms = accepted_terms_version.map(|version| {
+        TermsAcceptance::new(version, client_ip(headers, peer, trust_x_forwarded_for))
+    });
+
+    let terms_version = AuthService::pending_terms_version(state, email, accepted_terms).await?;
+    Ok(terms_version.map(|terms_version| {
+        let response = Json(LoginTypes::TermsRequired(TermsRequiredResponse {
+            message: "Terms of service acceptance required".to_owned(),
+            terms_version,
+            terms_url,
+        }));
+        (StatusCode::FORBIDDEN, response)
+    }))
+}
+
+/// Finish a login for an already authenticated `user`: issue a session
+/// directly, or start a 2FA challenge when the account requires one.
+pub(crate) async fn complete_login(
+    user: &User,
+    state: &AppState,
+    jar: CookieJar,
+) -> Result<(CookieJar, (StatusCode, Json<LoginTypes>)), LoginError> {
     match user.requires_mfa {
+        // A browser the user chose to trust after a previous 2FA check skips the code
+        true if is_trusted_device(&user.email, state, &jar).await => {
+            handle_no_2fa_login(&user.email, state, jar).await
+        }
         // We are now passing `&user.email` and `&state` to `handle_2fa`
-        true => handle_2fa_login(&user.email, &state, jar).await,
-        false => handle_no_2fa_login(&user.email, &state, jar).await,
+        true => handle_2fa_login(&user.email, state, jar).await,
+        false => handle_no_2fa_login(&user.email, state, jar).await,
+    }
+}
+
+/// True when the request carries a valid, unrevoked trusted-device cookie
+/// issued to `email`.
+async fn is_trusted_device(email: &Email, state: &AppState, jar: &CookieJar) -> bool {
+    let (cookie_name, key) = {
+        let config = state.config.read().await;
+        (
+            config.trusted_device_cookie_name().to_owned(),
+            *config.trusted_device_key(),
+        )
+    };
+
+    let Some(device_id) = jar
+        .get(&cookie_name)
+        .and_then(|cookie| verify_device_token(&key, cookie.value()))
+    else {
+        return false;
+    };
+
+    match state
+        .trusted_device_store
+        .read()
+        .await
+        .get_device(device_id, Utc::now())
+        .await
+    {
+        Ok(device) => device.user_id == email.as_ref(),
+        Err(_) => false,
     }
 }
 
@@ -35,11 +134,19 @@ async fn handle_2fa_login(
     let login_attempt_id = LoginAttemptId::default();
     let two_fa_code = TwoFACode::default();
 
+    let max_pending = state.config.read().await.mfa_max_pending_attempts();
+
     state
         .twofa_token_store
         .write()
         .await
-        .add_code(email.clone(), login_attempt_id.clone(), two_fa_code.clone())
+        .add_code(
+            email.clone(),
+            login_attempt_id.clone(),
+            two_fa_code.clone(),
+            TwoFAPurpose::Login,
+            max_pending,
+        )
         .await
         .map_err(|_| LoginError::InternalServerError)?;
 
@@ -103,6 +210,7 @@ async fn handle_no_2fa_login(
 pub enum LoginTypes {
     RegularAuth(LoginResponse),
     TwoFactorAuth(TwoFactorAuthResponse),
+    TermsRequired(TermsRequiredResponse),
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -111,3 +219,14 @@ pub struct TwoFactorAuthResponse {
     #[serde(rename = "loginAttemptId")]
     pub login_attempt_id: String,
 }
+
+/// Returned with 403 until the user accepts the current terms by logging in
+/// again with `acceptedTermsVersion`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TermsRequiredResponse {
+    pub message: String,
+    #[serde(rename = "termsVersion")]
+    pub terms_version: String,
+    #[serde(rename = "termsUrl")]
+    pub terms_url: Option<String>,
+}
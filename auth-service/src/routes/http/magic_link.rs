@@ -0,0 +1,147 @@
+use axum::extract::{ConnectInfo, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use axum_extra::extract::CookieJar;
+use chrono::{Duration, Utc};
+use std::net::SocketAddr;
+
+use crate::app_state::AppState;
+use crate::domain::{
+    hash_one_time_token, new_one_time_token, Email, MagicLinkConsumeQuery, MagicLinkRequestBody,
+    MagicLinkResponse, TokenPurpose, UserStoreError,
+};
+use crate::errors::MagicLinkError;
+use crate::routes::{complete_login, terms_required, LoginTypes};
+use crate::services::AuthService;
+
+/// Email a single-use login link. Answers the same way whether or not the
+/// account exists, so the endpoint cannot be used to probe for users.
+pub async fn request_magic_link(
+    State(state): State<AppState>,
+    Json(request): Json<MagicLinkRequestBody>,
+) -> Result<(StatusCode, Json<MagicLinkResponse>), MagicLinkError> {
+    let email = Email::parse(request.email).or(Err(MagicLinkError::InvalidEmail))?;
+
+    match state.user_store.read().await.get_user(email.clone()).await {
+        Ok(_) => send_magic_link(&state, &email).await?,
+        Err(UserStoreError::UserNotFound) => {}
+        Err(_) => return Err(MagicLinkError::InternalServerError),
+    }
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(MagicLinkResponse {
+            message: "If the account exists, a login link has been sent".to_owned(),
+        }),
+    ))
+}
+
+/// Redeem a login link. Accounts with 2FA get a challenge (206) instead of a
+/// session, exactly like a password login. While the current terms still
+/// have to be accepted the link is left unused, so it can be followed again
+/// with `acceptedTermsVersion`.
+pub async fn consume_magic_link(
+    State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    jar: CookieJar,
+    Query(query): Query<MagicLinkConsumeQuery>,
+) -> Result<(CookieJar, (StatusCode, Json<LoginTypes>)), MagicLinkError> {
+    let token_hash = {
+        let config = state.config.read().await;
+        hash_one_time_token(config.one_time_token_key(), &query.token)
+    };
+
+    let subject = state
+        .one_time_token_store
+        .read()
+        .await
+        .peek_token(TokenPurpose::MagicLink, &token_hash, Utc::now())
+        .await
+        .map_err(|_| MagicLinkError::InvalidLink)?;
+    let email = Email::parse(subject).or(Err(MagicLinkError::InvalidLink))?;
+
+    let mut user = state
+        .user_store
+        .read()
+        .await
+        .get_user(email)
+        .await
+        .map_err(|e| match e {
+            UserStoreError::UserNotFound => MagicLinkError::InvalidLink,
+            _ => MagicLinkError::InternalServerError,
+        })?;
+
+    AuthService::ensure_active(&user)?;
+    let peer = connect_info.map(|ConnectInfo(addr)| addr);
+    if let Some(response) = terms_required(
+        &state,
+        &user.email,
+        query.accepted_terms_version,
+        &headers,
+        peer,
+    )
+    .await?
+    {
+        return Ok((jar, response));
+    }
+
+    state
+        .one_time_token_store
+        .write()
+        .await
+        .take_token(TokenPurpose::MagicLink, &token_hash, Utc::now())
+        .await
+        .map_err(|_| MagicLinkError::InvalidLink)?;
+
+    // Following a link sent to the address proves the user controls it
+    if !user.email_verified {
+        user.email_verified = true;
+        user = state
+            .user_store
+            .write()
+            .await
+            .update_user(user)
+            .await
+            .map_err(|_| MagicLinkError::InternalServerError)?;
+    }
+
+    Ok(complete_login(&user, &state, jar).await?)
+}
+
+async fn send_magic_link(state: &AppState, email: &Email) -> Result<(), MagicLinkError> {
+    let token = new_one_time_token();
+    let (token_hash, ttl_seconds, link) = {
+        let config = state.config.read().await;
+        (
+            hash_one_time_token(config.one_time_token_key(), &token),
+            config.magic_link_ttl_seconds(),
+            format!(
+                "{}/magic-link/consume?token={}",
+                config.public_base_url().trim_end_matches('/'),
+                token
+            ),
+        )
+    };
+
+    state
+        .one_time_token_store
+        .write()
+        .await
+        .add_token(
+            TokenPurpose::MagicLink,
+            token_hash,
+            email.as_ref().to_owned(),
+            Utc::now() + Duration::seconds(ttl_seconds),
+        )
+        .await
+        .map_err(|_| MagicLinkError::InternalServerError)?;
+
+    state
+        .email_client
+        .read()
+        .await
+        .send_email(email, "your login link", &link)
+        .await
+        .map_err(|_| MagicLinkError::InternalServerError)
+}
@@ -0,0 +1,126 @@
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::Response;
+use axum::Json;
+
+use crate::app_state::AppState;
+use crate::domain::{Email, MeResponse, UpdateProfileRequestBody, User, UserStoreError};
+use crate::errors::MeError;
+use crate::services::data_export::export_response;
+use crate::utils::bearer_claims;
+use crate::validation::{
+    is_valid_avatar_url, is_valid_display_name, is_valid_locale, is_valid_timezone,
+};
+
+/// The account behind the access token.
+pub async fn get_me(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<(StatusCode, Json<MeResponse>), MeError> {
+    let user = authenticated_user(&state, &headers).await?;
+
+    Ok((StatusCode::OK, Json(user.into())))
+}
+
+/// Download everything held about the caller's account as JSON.
+pub async fn export_me(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Response, MeError> {
+    let user = authenticated_user(&state, &headers).await?;
+
+    export_response(&state, &user)
+        .await
+        .map_err(|_| MeError::InternalServerError)
+}
+
+/// Update profile fields. Every invalid field is reported at once and
+/// nothing is saved unless all of them pass.
+pub async fn update_me(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<UpdateProfileRequestBody>,
+) -> Result<(StatusCode, Json<MeResponse>), MeError> {
+    let mut user = authenticated_user(&state, &headers).await?;
+
+    let mut errors = Vec::new();
+    let profile = &mut user.profile;
+    apply(
+        &mut profile.display_name,
+        request
+            .display_name
+            .map(|name| name.map(|n| n.trim().to_owned())),
+        is_valid_display_name,
+        "displayName must be 1-64 characters without control characters",
+        &mut errors,
+    );
+    apply(
+        &mut profile.locale,
+        request.locale,
+        is_valid_locale,
+        "locale must be a language tag such as en-GB",
+        &mut errors,
+    );
+    apply(
+        &mut profile.timezone,
+        request.timezone,
+        is_valid_timezone,
+        "timezone must be an IANA time zone name such as Europe/Berlin",
+        &mut errors,
+    );
+    apply(
+        &mut profile.avatar_url,
+        request.avatar_url,
+        is_valid_avatar_url,
+        "avatarUrl must be an https URL of at most 2048 characters",
+        &mut errors,
+    );
+    if !errors.is_empty() {
+        return Err(MeError::InvalidProfile(errors));
+    }
+
+    let user = state
+        .user_store
+        .write()
+        .await
+        .update_user(user)
+        .await
+        .map_err(|_| MeError::InternalServerError)?;
+
+    Ok((StatusCode::OK, Json(user.into())))
+}
+
+async fn authenticated_user(state: &AppState, headers: &HeaderMap) -> Result<User, MeError> {
+    let claims = bearer_claims(state, headers)
+        .await
+        .ok_or(MeError::InvalidToken)?;
+    let email = Email::parse(claims.sub).or(Err(MeError::InvalidToken))?;
+
+    state
+        .user_store
+        .read()
+        .await
+        .get_user(email)
+        .await
+        .map_err(|e| match e {
+            UserStoreError::UserNotFound => MeError::InvalidToken,
+            _ => MeError::InternalServerError,
+        })
+}
+
+/// Apply one field of a partial update: absent keeps `field`, `null` clears
+/// it and a value replaces it if `is_valid` accepts it.
+fn apply(
+    field: &mut Option<String>,
+    update: Option<Option<String>>,
+    is_valid: fn(&str) -> bool,
+    rule: &str,
+    errors: &mut Vec<String>,
+) {
+    match update {
+        None => {}
+        Some(None) => *field = None,
+        Some(Some(value)) if is_valid(&value) => *field = Some(value),
+        Some(Some(_)) => errors.push(rule.to_owned()),
+    }
+}
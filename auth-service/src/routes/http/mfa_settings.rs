@@ -0,0 +1,192 @@
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use serde::Serialize;
+
+use crate::app_state::AppState;
+use crate::domain::{
+    DisableMFARequestBody, Email, LoginAttemptId, MfaSettingsResponse, Password, TwoFACode,
+    TwoFAPurpose, User, UserStoreError,
+};
+use crate::errors::{LoginError, MfaSettingsError, VerifyMfaError};
+use crate::routes::{consume_2fa_code, TwoFactorAuthResponse};
+use crate::services::AuthService;
+use crate::utils::bearer_claims;
+
+pub async fn enable_mfa(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<(StatusCode, Json<MfaSettingsResponse>), MfaSettingsError> {
+    let email = authenticated_email(&state, &headers).await?;
+
+    let mut user = state
+        .user_store
+        .read()
+        .await
+        .get_user(email.clone())
+        .await
+        .map_err(|e| match e {
+            UserStoreError::UserNotFound => MfaSettingsError::InvalidToken,
+            _ => MfaSettingsError::InternalServerError,
+        })?;
+
+    if !user.requires_mfa {
+        user.requires_mfa = true;
+        save_and_notify(&state, user, "2FA has been enabled on your account.").await?;
+    }
+
+    Ok((
+        StatusCode::OK,
+        Json(MfaSettingsResponse {
+            message: "2FA enabled".to_owned(),
+            requires_mfa: true,
+        }),
+    ))
+}
+
+/// Turning 2FA off needs the password and a fresh code. Without a code the
+/// password is checked and a challenge is emailed (206); with one the change
+/// is applied (200).
+pub async fn disable_mfa(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<DisableMFARequestBody>,
+) -> Result<(StatusCode, Json<DisableMfaTypes>), MfaSettingsError> {
+    let email = authenticated_email(&state, &headers).await?;
+    let password = Password::parse(request.password).or(Err(MfaSettingsError::InvalidPassword))?;
+
+    // Wrong passwords count towards the same lockout as failed logins
+    let (mut user, _) = AuthService::check_password(&state, &email, password, None)
+        .await
+        .map_err(|e| match e {
+            LoginError::IncorrectCredentials => MfaSettingsError::IncorrectCredentials,
+            LoginError::UserNotFound(_) => MfaSettingsError::InvalidToken,
+            e => MfaSettingsError::Login(e),
+        })?;
+
+    if !user.requires_mfa {
+        return Err(MfaSettingsError::NotEnabled);
+    }
+
+    let (login_attempt_id, mfa_code) = match (request.login_attempt_id, request.mfa_code) {
+        (Some(login_attempt_id), Some(mfa_code)) => (login_attempt_id, mfa_code),
+        _ => return send_step_up_code(&state, &email).await,
+    };
+
+    let login_attempt_id =
+        LoginAttemptId::parse(login_attempt_id).or(Err(VerifyMfaError::InvalidLoginRequestId))?;
+    let two_fa_code = TwoFACode::parse(mfa_code).or(Err(VerifyMfaError::InvalidMFACode))?;
+    consume_2fa_code(
+        &state,
+        &email,
+        &login_attempt_id,
+        &two_fa_code,
+        TwoFAPurpose::DisableMfa,
+    )
+    .await?;
+
+    user.requires_mfa = false;
+    save_and_notify(&state, user, "2FA has been disabled on your account.").await?;
+
+    // Remembered browsers only make sense while 2FA is on; drop them so
+    // re-enabling it starts from a clean slate.
+    state
+        .trusted_device_store
+        .write()
+        .await
+        .revoke_all(email.as_ref())
+        .await
+        .map_err(|_| MfaSettingsError::InternalServerError)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(DisableMfaTypes::Disabled(MfaSettingsResponse {
+            message: "2FA disabled".to_owned(),
+            requires_mfa: false,
+        })),
+    ))
+}
+
+async fn authenticated_email(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<Email, MfaSettingsError> {
+    let claims = bearer_claims(state, headers)
+        .await
+        .ok_or(MfaSettingsError::InvalidToken)?;
+
+    Email::parse(claims.sub).or(Err(MfaSettingsError::InvalidToken))
+}
+
+async fn send_step_up_code(
+    state: &AppState,
+    email: &Email,
+) -> Result<(StatusCode, Json<DisableMfaTypes>), MfaSettingsError> {
+    let login_attempt_id = LoginAttemptId::default();
+    let two_fa_code = TwoFACode::default();
+
+    let max_pending = state.config.read().await.mfa_max_pending_attempts();
+
+    state
+        .twofa_token_store
+        .write()
+        .await
+        .add_code(
+            email.clone(),
+            login_attempt_id.clone(),
+            two_fa_code.clone(),
+            TwoFAPurpose::DisableMfa,
+            max_pending,
+        )
+        .await
+        .map_err(|_| MfaSettingsError::InternalServerError)?;
+
+    state
+        .email_client
+        .read()
+        .await
+        .send_email(email, "your 2fa code", two_fa_code.as_ref())
+        .await
+        .map_err(|_| MfaSettingsError::InternalServerError)?;
+
+    Ok((
+        StatusCode::PARTIAL_CONTENT,
+        Json(DisableMfaTypes::TwoFactorAuth(TwoFactorAuthResponse {
+            message: "2FA required".to_owned(),
+            login_attempt_id: login_attempt_id.as_ref().to_owned(),
+        })),
+    ))
+}
+
+async fn save_and_notify(
+    state: &AppState,
+    user: User,
+    notice: &str,
+) -> Result<(), MfaSettingsError> {
+    let user = state
+        .user_store
+        .write()
+        .await
+        .update_user(user)
+        .await
+        .map_err(|_| MfaSettingsError::InternalServerError)?;
+
+    // The change is already saved, so a failed notice must not report it as failed
+    let sent = state
+        .email_client
+        .read()
+        .await
+        .send_email(&user.email, "your 2fa settings changed", notice)
+        .await;
+    if sent.is_err() {
+        eprintln!("error: could not send a 2fa settings change notice");
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum DisableMfaTypes {
+    Disabled(MfaSettingsResponse),
+    TwoFactorAuth(TwoFactorAuthResponse),
+}
@@ -1,14 +1,36 @@
+pub(crate) mod admin;
+pub(crate) mod change_email;
+pub(crate) mod change_password;
 pub(crate) mod delete_account;
 pub(crate) mod login;
 pub(crate) mod logout;
+pub(crate) mod magic_link;
+pub(crate) mod me;
+pub(crate) mod mfa_settings;
+pub(crate) mod password_reset;
+pub(crate) mod resend_mfa;
 pub(crate) mod signup;
+pub(crate) mod terms;
+pub(crate) mod trusted_devices;
+pub(crate) mod verify_email;
 pub(crate) mod verify_mfa;
 pub(crate) mod verify_token;
 
 // re-export items from sub-modules
+pub use admin::*;
+pub use change_email::*;
+pub use change_password::*;
 pub use delete_account::*;
 pub use login::*;
 pub use logout::*;
+pub use magic_link::*;
+pub use me::*;
+pub use mfa_settings::*;
+pub use password_reset::*;
+pub use resend_mfa::*;
 pub use signup::*;
+pub use terms::*;
+pub use trusted_devices::*;
+pub use verify_email::*;
 pub use verify_mfa::*;
 pub use verify_token::*;
@@ -0,0 +1,181 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use chrono::{Duration, Utc};
+
+use crate::app_state::AppState;
+use crate::domain::{
+    hash_one_time_token, new_one_time_token, Email, LoginLockout, Password,
+    PasswordResetConfirmBody, PasswordResetRequestBody, PasswordResetResponse, TokenPurpose,
+    UserStoreError,
+};
+use crate::errors::PasswordResetError;
+use crate::services::password_hashing;
+
+/// Email a single-use reset link. Always answers 202, including for unknown
+/// or malformed addresses and for store or email failures, so the endpoint
+/// cannot be used to probe for users. Failures are logged instead.
+pub async fn request_password_reset(
+    State(state): State<AppState>,
+    Json(request): Json<PasswordResetRequestBody>,
+) -> (StatusCode, Json<PasswordResetResponse>) {
+    if let Ok(email) = Email::parse(request.email) {
+        let user = state.user_store.read().await.get_user(email.clone()).await;
+        let sent = match user {
+            Ok(_) => send_reset_link(&state, &email).await,
+            Err(UserStoreError::UserNotFound) => Ok(()),
+            Err(_) => Err(PasswordResetError::InternalServerError),
+        };
+        if sent.is_err() {
+            eprintln!("error: could not send a password reset link");
+        }
+    }
+
+    (
+        StatusCode::ACCEPTED,
+        Json(PasswordResetResponse {
+            message: "If the account exists, a password reset link has been sent".to_owned(),
+        }),
+    )
+}
+
+/// Set a new password with a reset token. Every existing session and pending
+/// 2FA login is revoked, so whoever held the old password is signed out.
+pub async fn confirm_password_reset(
+    State(state): State<AppState>,
+    Json(request): Json<PasswordResetConfirmBody>,
+) -> Result<(StatusCode, Json<PasswordResetResponse>), PasswordResetError> {
+    let (policy, token_hash) = {
+        let config = state.config.read().await;
+        (
+            config.password_policy().clone(),
+            hash_one_time_token(config.one_time_token_key(), &request.token),
+        )
+    };
+
+    // Every check runs before the token is redeemed, so a rejected password
+    // does not burn the link. The token only names the account until then.
+    let subject = state
+        .one_time_token_store
+        .read()
+        .await
+        .peek_token(TokenPurpose::PasswordReset, &token_hash, Utc::now())
+        .await
+        .map_err(|_| PasswordResetError::InvalidToken)?;
+    let email = Email::parse(subject).or(Err(PasswordResetError::InvalidToken))?;
+    let password = Password::parse_new(request.new_password, &policy, &email)
+        .map_err(PasswordResetError::PasswordPolicy)?;
+    if state.is_breached_password(password.as_ref()) {
+        return Err(PasswordResetError::BreachedPassword);
+    }
+    // Hashed before taking the user store lock, which is held only to save it
+    let params = state.user_store.read().await.argon2_params();
+    let new_hash = password_hashing::stored_form(password.as_ref(), params)
+        .await
+        .map_err(|_| PasswordResetError::InternalServerError)?;
+
+    state
+        .one_time_token_store
+        .write()
+        .await
+        .take_token(TokenPurpose::PasswordReset, &token_hash, Utc::now())
+        .await
+        .map_err(|_| PasswordResetError::InvalidToken)?;
+
+    {
+        let mut user_store = state.user_store.write().await;
+        let user = user_store
+            .get_user(email.clone())
+            .await
+            .map_err(|e| match e {
+                UserStoreError::UserNotFound => PasswordResetError::InvalidToken,
+                _ => PasswordResetError::InternalServerError,
+            })?;
+        let replaced = user_store
+            .replace_password_hash(email.clone(), user.password, Password::from_hash(new_hash))
+            .await
+            .map_err(|_| PasswordResetError::InternalServerError)?;
+        if !replaced {
+            return Err(PasswordResetError::InternalServerError);
+        }
+        // Owning the mailbox is enough to lift a lockout
+        user_store
+            .set_lockout(email.clone(), LoginLockout::default())
+            .await
+            .map_err(|_| PasswordResetError::InternalServerError)?;
+    }
+
+    state
+        .token_service
+        .read()
+        .await
+        .revoke_user_sessions(email.as_ref(), None)
+        .await
+        .map_err(|_| PasswordResetError::InternalServerError)?;
+
+    state
+        .twofa_token_store
+        .write()
+        .await
+        .remove_codes_for(&email)
+        .await
+        .map_err(|_| PasswordResetError::InternalServerError)?;
+
+    state
+        .email_client
+        .read()
+        .await
+        .send_email(
+            &email,
+            "your password was changed",
+            "The password for your account has been reset and all sessions were signed out.",
+        )
+        .await
+        .map_err(|_| PasswordResetError::InternalServerError)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(PasswordResetResponse {
+            message: "Password has been reset".to_owned(),
+        }),
+    ))
+}
+
+/// The link opens the reset form on the bundled page in `assets/`, which
+/// posts the token to `/password-reset/confirm`.
+async fn send_reset_link(state: &AppState, email: &Email) -> Result<(), PasswordResetError> {
+    let token = new_one_time_token();
+    let (token_hash, ttl_seconds, link) = {
+        let config = state.config.read().await;
+        (
+            hash_one_time_token(config.one_time_token_key(), &token),
+            config.password_reset_ttl_seconds(),
+            format!(
+                "{}/?reset={}",
+                config.public_base_url().trim_end_matches('/'),
+                token
+            ),
+        )
+    };
+
+    state
+        .one_time_token_store
+        .write()
+        .await
+        .add_token(
+            TokenPurpose::PasswordReset,
+            token_hash,
+            email.as_ref().to_owned(),
+            Utc::now() + Duration::seconds(ttl_seconds),
+        )
+        .await
+        .map_err(|_| PasswordResetError::InternalServerError)?;
+
+    state
+        .email_client
+        .read()
+        .await
+        .send_email(email, "reset your password", &link)
+        .await
+        .map_err(|_| PasswordResetError::InternalServerError)
+}
@@ -0,0 +1,75 @@
+use axum::extract::State;
+use axum::{http::StatusCode, Json};
+use chrono::{Duration, Utc};
+
+use crate::domain::{
+    Email, LoginAttemptId, ResendMFARequestBody, TwoFACode, TwoFAError, TwoFAPurpose,
+};
+use crate::errors::ResendMfaError;
+use crate::routes::TwoFactorAuthResponse;
+use crate::AppState;
+
+pub async fn resend_mfa(
+    State(state): State<AppState>,
+    Json(request): Json<ResendMFARequestBody>,
+) -> Result<(StatusCode, Json<TwoFactorAuthResponse>), ResendMfaError> {
+    let email = Email::parse(request.email).or(Err(ResendMfaError::InvalidEmail))?;
+
+    let login_attempt_id = LoginAttemptId::parse(request.login_attempt_id)
+        .or(Err(ResendMfaError::InvalidLoginRequestId))?;
+
+    let (cooldown, max_resends) = {
+        let config = state.config.read().await;
+        (
+            Duration::seconds(config.mfa_resend_cooldown_seconds()),
+            config.mfa_max_resends(),
+        )
+    };
+
+    let two_fa_code = TwoFACode::default();
+
+    {
+        let mut twofa_token_store = state.twofa_token_store.write().await;
+
+        // The challenge is bound to the email that started the login.
+        match twofa_token_store
+            .get_code(&login_attempt_id, TwoFAPurpose::Login)
+            .await
+        {
+            Ok((stored_email, _)) if *stored_email == email => {}
+            _ => return Err(ResendMfaError::InvalidLoginRequestId),
+        }
+
+        twofa_token_store
+            .resend_code(
+                &login_attempt_id,
+                two_fa_code.clone(),
+                Utc::now(),
+                cooldown,
+                max_resends,
+            )
+            .await
+            .map_err(|e| match e {
+                TwoFAError::ResendCooldown => ResendMfaError::Cooldown,
+                TwoFAError::ResendLimitReached => ResendMfaError::TooManyResends,
+                TwoFAError::LoginAttemptIdNotFound => ResendMfaError::InvalidLoginRequestId,
+                _ => ResendMfaError::InternalServerError,
+            })?;
+    }
+
+    state
+        .email_client
+        .read()
+        .await
+        .send_email(&email, "your 2fa code", two_fa_code.as_ref())
+        .await
+        .map_err(|_| ResendMfaError::InternalServerError)?;
+
+    Ok((
+        StatusCode::OK,
+        Json(TwoFactorAuthResponse {
+            message: "2FA code resent".to_owned(),
+            login_attempt_id: login_attempt_id.as_ref().to_owned(),
+        }),
+    ))
+}
@@ -1,18 +1,45 @@
 use crate::app_state::AppState;
-use crate::domain::{Email, Password, SignupRequestBody, SignupResponse};
+use crate::domain::{Email, Password, SignupRequestBody, SignupResponse, TermsAcceptance};
 use crate::errors::SignupError;
 use crate::services::AuthService;
-use axum::extract::State;
+use crate::utils::client_ip;
+use axum::extract::{ConnectInfo, State};
+use axum::http::HeaderMap;
 use axum::{http::StatusCode, response::IntoResponse, Json};
+use std::net::SocketAddr;
 
 pub async fn signup(
     State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
     Json(request): Json<SignupRequestBody>,
 ) -> Result<impl IntoResponse, SignupError> {
     let email = Email::parse(request.email).or(Err(SignupError::InvalidEmail))?;
-    let password = Password::parse(request.password).or(Err(SignupError::InvalidPassword))?;
+    let password = Password::parse_new(
+        request.password,
+        state.config.read().await.password_policy(),
+        &email,
+    )
+    .map_err(SignupError::PasswordPolicy)?;
+    if state.is_breached_password(password.as_ref()) {
+        return Err(SignupError::BreachedPassword);
+    }
 
-    AuthService::signup(state, email, password, request.requires_mfa).await?;
+    let trust_x_forwarded_for = state.config.read().await.trust_x_forwarded_for();
+    let accepted_terms = request.accepted_terms_version.map(|version| {
+        let peer = connect_info.map(|ConnectInfo(addr)| addr);
+        TermsAcceptance::new(version, client_ip(&headers, peer, trust_x_forwarded_for))
+    });
+
+    AuthService::signup(
+        state,
+        email,
+        password,
+        request.requires_mfa,
+        request.invitation_code,
+        accepted_terms,
+    )
+    .await?;
 
     Ok((
         StatusCode::CREATED,
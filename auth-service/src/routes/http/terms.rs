@@ -0,0 +1,15 @@
+use axum::extract::State;
+use axum::Json;
+
+use crate::app_state::AppState;
+use crate::domain::TermsResponse;
+
+/// The terms version signup currently requires, so clients can show it and
+/// send it back as `acceptedTermsVersion`.
+pub async fn get_terms(State(state): State<AppState>) -> Json<TermsResponse> {
+    let config = state.config.read().await;
+    Json(TermsResponse {
+        version: config.terms_version().map(str::to_owned),
+        url: config.terms_url().map(str::to_owned),
+    })
+}
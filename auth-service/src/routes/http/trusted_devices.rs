@@ -0,0 +1,76 @@
+use axum::extract::{Path, State};
+use axum::http::HeaderMap;
+use axum::{http::StatusCode, Json};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::domain::{TrustedDeviceResponse, TrustedDeviceStoreError};
+use crate::errors::TrustedDeviceError;
+use crate::utils::bearer_claims;
+use crate::AppState;
+
+/// Resolve the caller's user id from the bearer access token.
+async fn authenticated_user(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<String, TrustedDeviceError> {
+    bearer_claims(state, headers)
+        .await
+        .map(|claims| claims.sub)
+        .ok_or(TrustedDeviceError::InvalidToken)
+}
+
+pub async fn list_trusted_devices(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<TrustedDeviceResponse>>, TrustedDeviceError> {
+    let user_id = authenticated_user(&state, &headers).await?;
+
+    let devices = state
+        .trusted_device_store
+        .read()
+        .await
+        .list_devices(&user_id, Utc::now())
+        .await
+        .map_err(|_| TrustedDeviceError::InternalServerError)?;
+
+    Ok(Json(devices.into_iter().map(Into::into).collect()))
+}
+
+pub async fn revoke_trusted_device(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(device_id): Path<Uuid>,
+) -> Result<StatusCode, TrustedDeviceError> {
+    let user_id = authenticated_user(&state, &headers).await?;
+
+    state
+        .trusted_device_store
+        .write()
+        .await
+        .revoke_device(&user_id, device_id)
+        .await
+        .map_err(|e| match e {
+            TrustedDeviceStoreError::DeviceNotFound => TrustedDeviceError::DeviceNotFound,
+            TrustedDeviceStoreError::UnexpectedError => TrustedDeviceError::InternalServerError,
+        })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn revoke_all_trusted_devices(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<StatusCode, TrustedDeviceError> {
+    let user_id = authenticated_user(&state, &headers).await?;
+
+    state
+        .trusted_device_store
+        .write()
+        .await
+        .revoke_all(&user_id)
+        .await
+        .map_err(|_| TrustedDeviceError::InternalServerError)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,71 @@
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::Json;
+use chrono::Utc;
+
+use crate::app_state::AppState;
+use crate::domain::{
+    verify_email_verification_token, Email, ResendVerificationRequestBody, UserStoreError,
+    VerifyEmailQuery, VerifyEmailResponse,
+};
+use crate::errors::VerifyEmailError;
+use crate::services::AuthService;
+
+pub async fn verify_email(
+    State(state): State<AppState>,
+    Query(query): Query<VerifyEmailQuery>,
+) -> Result<(StatusCode, Json<VerifyEmailResponse>), VerifyEmailError> {
+    let email = {
+        let config = state.config.read().await;
+        verify_email_verification_token(config.email_verification_key(), &query.token, Utc::now())
+            .ok_or(VerifyEmailError::InvalidLink)?
+    };
+
+    let mut user_store = state.user_store.write().await;
+    let mut user = user_store.get_user(email).await.map_err(|e| match e {
+        UserStoreError::UserNotFound => VerifyEmailError::InvalidLink,
+        _ => VerifyEmailError::InternalServerError,
+    })?;
+
+    if !user.email_verified {
+        user.email_verified = true;
+        user_store
+            .update_user(user)
+            .await
+            .map_err(|_| VerifyEmailError::InternalServerError)?;
+    }
+
+    Ok((
+        StatusCode::OK,
+        Json(VerifyEmailResponse {
+            message: "Email address verified".to_owned(),
+        }),
+    ))
+}
+
+/// Send a fresh verification link. Answers the same way for unknown and
+/// already verified addresses, so it cannot be used to probe for accounts.
+pub async fn resend_verification_email(
+    State(state): State<AppState>,
+    Json(request): Json<ResendVerificationRequestBody>,
+) -> Result<(StatusCode, Json<VerifyEmailResponse>), VerifyEmailError> {
+    let email = Email::parse(request.email).or(Err(VerifyEmailError::InvalidEmail))?;
+
+    let user = state.user_store.read().await.get_user(email.clone()).await;
+    match user {
+        Ok(user) if !user.email_verified => {
+            AuthService::send_verification_email(&state, &email)
+                .await
+                .map_err(|_| VerifyEmailError::InternalServerError)?;
+        }
+        Ok(_) | Err(UserStoreError::UserNotFound) => {}
+        Err(_) => return Err(VerifyEmailError::InternalServerError),
+    }
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(VerifyEmailResponse {
+            message: "If the address still needs confirming, a new link has been sent".to_owned(),
+        }),
+    ))
+}
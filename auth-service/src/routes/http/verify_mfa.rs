@@ -1,10 +1,16 @@
 use axum::extract::State;
 use axum::{http::StatusCode, Json};
 use axum_extra::extract::CookieJar;
+use chrono::{Duration, Utc};
+use uuid::Uuid;
 
-use crate::domain::{Email, LoginAttemptId, LoginResponse, TwoFACode, VerifyMFARequestBody};
-use crate::errors::VerifyMfaError;
-use crate::utils::cookie_helpers::{access_cookie, refresh_cookie};
+use crate::domain::{
+    sign_device_token, Email, LoginAttemptId, LoginResponse, TrustedDevice, TwoFACode,
+    TwoFAPurpose, UserStoreError, VerifyMFARequestBody,
+};
+use crate::errors::{LoginError, VerifyMfaError};
+use crate::services::AuthService;
+use crate::utils::cookie_helpers::{access_cookie, refresh_cookie, trusted_device_cookie};
 use crate::AppState;
 
 pub async fn verify_mfa(
@@ -12,6 +18,7 @@ pub async fn verify_mfa(
     jar: CookieJar,
     Json(request): Json<VerifyMFARequestBody>,
 ) -> Result<(CookieJar, (StatusCode, Json<LoginResponse>)), VerifyMfaError> {
+    let trust_device = request.trust_device;
     let email = Email::parse(request.email).or(Err(VerifyMfaError::InvalidEmail))?;
 
     let login_attempt_id = LoginAttemptId::parse(request.login_attempt_id)
@@ -19,51 +26,178 @@ pub async fn verify_mfa(
 
     let two_fa_code = TwoFACode::parse(request.mfa_code).or(Err(VerifyMfaError::InvalidMFACode))?;
 
+    consume_2fa_code(
+        &state,
+        &email,
+        &login_attempt_id,
+        &two_fa_code,
+        TwoFAPurpose::Login,
+    )
+    .await?;
+
+    // The account may have been disabled or suspended since the code was sent.
+    let user = state
+        .user_store
+        .read()
+        .await
+        .get_user(email.clone())
+        .await
+        .map_err(|e| match e {
+            UserStoreError::UserNotFound => VerifyMfaError::OldCode,
+            _ => VerifyMfaError::InternalServerError,
+        })?;
+    AuthService::ensure_active(&user)?;
+    if !user.email_verified && state.config.read().await.require_verified_email() {
+        return Err(LoginError::EmailNotVerified.into());
+    }
+
+    let issued = state
+        .token_service
+        .write()
+        .await
+        .issue_initial_session(email.as_ref())
+        .await
+        .map_err(|_| VerifyMfaError::InternalServerError)?;
+
+    let jar = {
+        let config = state.config.read().await;
+        jar.add(access_cookie(
+            config.access_cookie_name(),
+            &issued.access_token,
+            config.token_ttl_seconds(),
+        ))
+        .add(refresh_cookie(
+            config.refresh_cookie_name(),
+            &issued.refresh_token,
+            config.refresh_token_ttl_seconds(),
+        ))
+    };
+
+    let jar = match trust_device {
+        true => remember_device(&state, &email, jar).await?,
+        false => jar,
+    };
+
+    Ok((
+        jar,
+        (
+            StatusCode::OK,
+            Json(LoginResponse {
+                message: "MFA verification successful".to_string(),
+            }),
+        ),
+    ))
+}
+
+/// Check `two_fa_code` against the pending challenge `login_attempt_id` of
+/// `email`, sent for `purpose`, and consume it on success. Wrong codes count
+/// against both the attempt and the account; once either limit is hit the
+/// attempt is dropped.
+pub(crate) async fn consume_2fa_code(
+    state: &AppState,
+    email: &Email,
+    login_attempt_id: &LoginAttemptId,
+    two_fa_code: &TwoFACode,
+    purpose: TwoFAPurpose,
+) -> Result<(), VerifyMfaError> {
+    let (max_attempts, account_max_failures, account_window) = {
+        let config = state.config.read().await;
+        (
+            config.mfa_max_attempts(),
+            config.mfa_account_max_failures(),
+            Duration::seconds(config.mfa_account_window_seconds()),
+        )
+    };
+    let now = Utc::now();
+
     let mut twofa_token_store = state.twofa_token_store.write().await;
-    let get_code = twofa_token_store.get_code(&email);
-
-    match get_code.await {
-        Ok(v) => {
-            if v.0 == login_attempt_id && v.1 == two_fa_code {
-                let issued = state
-                    .token_service
-                    .write()
-                    .await
-                    .issue_initial_session(email.as_ref())
-                    .await
-                    .map_err(|_| VerifyMfaError::InternalServerError)?;
-
-                let jar = {
-                    let config = state.config.read().await;
-                    jar.add(access_cookie(
-                        config.access_cookie_name(),
-                        &issued.access_token,
-                        config.token_ttl_seconds(),
-                    ))
-                    .add(refresh_cookie(
-                        config.refresh_cookie_name(),
-                        &issued.refresh_token,
-                        config.refresh_token_ttl_seconds(),
-                    ))
-                };
-
-                twofa_token_store
-                    .remove_code(&email)
-                    .await
-                    .map_err(|_| VerifyMfaError::InternalServerError)?;
-                Ok((
-                    jar,
-                    (
-                        StatusCode::OK,
-                        Json(LoginResponse {
-                            message: "MFA verification successful".to_string(),
-                        }),
-                    ),
-                ))
-            } else {
-                Err(VerifyMfaError::OldCode)
-            }
+
+    let (stored_email, stored_code) =
+        match twofa_token_store.get_code(login_attempt_id, purpose).await {
+            Ok(v) => v.clone(),
+            Err(_) => return Err(VerifyMfaError::OldCode),
+        };
+
+    // The challenge is bound to the email that started the login.
+    if stored_email != *email {
+        return Err(VerifyMfaError::OldCode);
+    }
+
+    // Throttle the account as a whole, so restarting login does not buy more guesses.
+    if twofa_token_store
+        .account_failures(email, now, account_window)
+        .await
+        >= account_max_failures
+    {
+        twofa_token_store
+            .remove_code(login_attempt_id)
+            .await
+            .map_err(|_| VerifyMfaError::InternalServerError)?;
+        return Err(VerifyMfaError::TooManyAttempts);
+    }
+
+    if stored_code != *two_fa_code {
+        let attempt_failures = twofa_token_store
+            .record_failed_attempt(login_attempt_id)
+            .await
+            .map_err(|_| VerifyMfaError::InternalServerError)?;
+        let account_failures = twofa_token_store
+            .record_account_failure(email, now, account_window)
+            .await
+            .map_err(|_| VerifyMfaError::InternalServerError)?;
+
+        if attempt_failures >= max_attempts || account_failures >= account_max_failures {
+            // Invalidate the attempt; the client has to restart login for a new code.
+            twofa_token_store
+                .remove_code(login_attempt_id)
+                .await
+                .map_err(|_| VerifyMfaError::InternalServerError)?;
+            return Err(VerifyMfaError::TooManyAttempts);
         }
-        Err(_) => Err(VerifyMfaError::OldCode),
+        return Err(VerifyMfaError::OldCode);
     }
+
+    twofa_token_store
+        .remove_code(login_attempt_id)
+        .await
+        .map_err(|_| VerifyMfaError::InternalServerError)?;
+    twofa_token_store
+        .clear_account_failures(email)
+        .await
+        .map_err(|_| VerifyMfaError::InternalServerError)
+}
+
+/// Record a trusted device for `email` and hand the browser its signed cookie.
+async fn remember_device(
+    state: &AppState,
+    email: &Email,
+    jar: CookieJar,
+) -> Result<CookieJar, VerifyMfaError> {
+    let (cookie_name, ttl_seconds, key) = {
+        let config = state.config.read().await;
+        (
+            config.trusted_device_cookie_name().to_owned(),
+            config.trusted_device_ttl_seconds(),
+            *config.trusted_device_key(),
+        )
+    };
+
+    let now = Utc::now();
+    let device = TrustedDevice {
+        device_id: Uuid::new_v4(),
+        user_id: email.as_ref().to_owned(),
+        created_at: now,
+        expires_at: now + Duration::seconds(ttl_seconds),
+    };
+    let token = sign_device_token(&key, device.device_id);
+
+    state
+        .trusted_device_store
+        .write()
+        .await
+        .add_device(device)
+        .await
+        .map_err(|_| VerifyMfaError::InternalServerError)?;
+
+    Ok(jar.add(trusted_device_cookie(&cookie_name, &token, ttl_seconds)))
 }
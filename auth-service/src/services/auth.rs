@@ -1,6 +1,12 @@
+use chrono::{Duration, Utc};
+
 use crate::app_state::AppState;
-use crate::domain::{Email, Password, User, UserStoreError};
+use crate::domain::{
+    hash_one_time_token, sign_email_verification_token, AccountStatus, Email, InvitationStoreError,
+    LoginLockout, Password, TermsAcceptance, User, UserStore, UserStoreError,
+};
 use crate::errors::{LoginError, SignupError};
+use crate::services::password_hashing;
 
 pub struct AuthService {}
 impl AuthService {
@@ -13,35 +19,319 @@ impl AuthService {
         email: Email,
         password: Password,
         requires_mfa: bool,
+        invitation_code: Option<String>,
+        accepted_terms: Option<TermsAcceptance>,
     ) -> Result<(), SignupError> {
+        Self::check_email_domain(&state, &email).await?;
+        if let Some(current) = state.config.read().await.terms_version() {
+            if accepted_terms.as_ref().map(|a| a.version.as_str()) != Some(current) {
+                return Err(SignupError::TermsNotAccepted(current.to_owned()));
+            }
+        }
+        let invitation = Self::redeem_invitation(&state, &email, invitation_code).await?;
+
         let user = User::new(email.clone(), password, requires_mfa);
         let result = state.user_store.write().await.add_user(user).await;
+        if let (Err(_), Some(code_hash)) = (&result, invitation) {
+            // Hand the use back so a failed signup does not burn the invite
+            let _ = state
+                .invitation_store
+                .write()
+                .await
+                .release(&code_hash)
+                .await;
+        }
         result.map_err(|e| match e {
             UserStoreError::UserAlreadyExists => {
-                SignupError::UserAlreadyExists(email.as_ref().to_string())
+                SignupError::UserAlreadyExists(email.display().to_string())
             }
             _ => SignupError::InternalServerError,
         })?;
+
+        // The account exists at this point. Should the acceptance not be
+        // stored, login asks for it again before handing out a session.
+        if let Some(acceptance) = accepted_terms {
+            let _ = state
+                .terms_store
+                .write()
+                .await
+                .record_acceptance(email.as_ref(), acceptance)
+                .await;
+        }
+
+        // If the email does not go out the user can ask for another one
+        // through the resend endpoint.
+        let _ = Self::send_verification_email(&state, &email).await;
         Ok(())
     }
 
+    /// Refuse domains outside the configured signup domain policy and
+    /// throwaway-mailbox providers.
+    async fn check_email_domain(state: &AppState, email: &Email) -> Result<(), SignupError> {
+        if !state.permits_email_domain(email).await {
+            return Err(SignupError::EmailDomainNotAllowed);
+        }
+        Ok(())
+    }
+
+    /// In invite-only mode, take one use of the invitation for `email` and
+    /// return its hash so the use can be released if signup fails. Outside
+    /// that mode the code is ignored.
+    async fn redeem_invitation(
+        state: &AppState,
+        email: &Email,
+        invitation_code: Option<String>,
+    ) -> Result<Option<[u8; 32]>, SignupError> {
+        let code_hash = {
+            let config = state.config.read().await;
+            if !config.invite_only_signup() {
+                return Ok(None);
+            }
+            let code = invitation_code
+                .map(|code| code.trim().to_owned())
+                .filter(|code| !code.is_empty())
+                .ok_or(SignupError::InvitationRequired)?;
+            hash_one_time_token(config.invitation_key(), &code)
+        };
+
+        state
+            .invitation_store
+            .write()
+            .await
+            .redeem(&code_hash, email.as_ref(), Utc::now())
+            .await
+            .map_err(|e| match e {
+                InvitationStoreError::InvitationNotFound => SignupError::InvalidInvitation,
+                InvitationStoreError::Expired => SignupError::InvitationExpired,
+                InvitationStoreError::Exhausted => SignupError::InvitationUsed,
+                InvitationStoreError::EmailMismatch => SignupError::InvitationEmailMismatch,
+                InvitationStoreError::UnexpectedError => SignupError::InternalServerError,
+            })?;
+        Ok(Some(code_hash))
+    }
+
+    /// Sign `email` in with `password`, normalized by `Password::parse`.
+    /// `typed_password` is the input as typed when that differs; it is tried
+    /// when the normalized form does not match, and on a match the stored
+    /// hash is replaced with one of the normalized form.
     pub async fn login(
         state: AppState,
         email: Email,
         password: Password,
+        typed_password: Option<Password>,
     ) -> Result<User, LoginError> {
-        match state
-            .user_store
-            .write()
+        let (user, matched_typed) =
+            Self::check_password(&state, &email, password.clone(), typed_password).await?;
+
+        Self::ensure_active(&user)?;
+
+        if !user.email_verified && state.config.read().await.require_verified_email() {
+            return Err(LoginError::EmailNotVerified);
+        }
+
+        // Only accounts that are let in get upgraded. Best effort: the login
+        // stands even if the upgrade cannot be saved, and the next login
+        // tries again. The new hash is made outside the
+        // lock and only saved if the password did not change meanwhile.
+        let params = state.user_store.read().await.argon2_params();
+        let upgrade = matched_typed
+            || params.as_ref().is_some_and(|params| {
+                password_hashing::needs_rehash(user.password.as_ref(), params)
+            });
+        if upgrade {
+            if let Ok(new_hash) = password_hashing::stored_form(password.as_ref(), params).await {
+                let _ = state
+                    .user_store
+                    .write()
+                    .await
+                    .replace_password_hash(
+                        email.clone(),
+                        user.password.clone(),
+                        Password::from_hash(new_hash),
+                    )
+                    .await;
+            }
+        }
+        Ok(user)
+    }
+
+    /// Check the password of `email` with login's lockout accounting: a
+    /// locked account is refused, a wrong password counts towards a lockout
+    /// and a right one clears the failures. Returns the user and whether it
+    /// was `typed_password` that matched.
+    pub async fn check_password(
+        state: &AppState,
+        email: &Email,
+        password: Password,
+        typed_password: Option<Password>,
+    ) -> Result<(User, bool), LoginError> {
+        let now = Utc::now();
+        let (max_failures, lockout_seconds, lockout_max_seconds) = {
+            let config = state.config.read().await;
+            (
+                config.login_max_failures(),
+                config.login_lockout_seconds(),
+                config.login_lockout_max_seconds(),
+            )
+        };
+
+        // The hash is checked under the read lock so other logins are not
+        // held up by it. Each outcome is then settled under the write lock
+        // against the lockout as it stands by then.
+        let (verified, matched_typed) = {
+            let user_store = state.user_store.read().await;
+            let lockout = Self::current_lockout(&*user_store, email).await?;
+            if let Some(retry_after) = lockout.retry_after(now) {
+                return Err(LoginError::AccountLocked(retry_after));
+            }
+            let verified = user_store
+                .validate_user(email.clone(), password.clone())
+                .await;
+            match (verified, typed_password) {
+                (Err(UserStoreError::InvalidCredentials), Some(typed)) => {
+                    let verified = user_store.validate_user(email.clone(), typed).await;
+                    let matched_typed = verified.is_ok();
+                    (verified, matched_typed)
+                }
+                (verified, _) => (verified, false),
+            }
+        };
+
+        let mut user_store = state.user_store.write().await;
+        let mut lockout = Self::current_lockout(&*user_store, email).await?;
+        // Concurrent wrong guesses may have locked the account meanwhile
+        if let Some(retry_after) = lockout.retry_after(now) {
+            return Err(LoginError::AccountLocked(retry_after));
+        }
+
+        let user = match verified {
+            Ok(user) => user,
+            Err(UserStoreError::InvalidCredentials) => {
+                let locked =
+                    lockout.record_failure(now, max_failures, lockout_seconds, lockout_max_seconds);
+                user_store
+                    .set_lockout(email.clone(), lockout.clone())
+                    .await
+                    .map_err(|_| LoginError::InternalServerError)?;
+                drop(user_store);
+
+                return match lockout.retry_after(now) {
+                    Some(retry_after) if locked => {
+                        let _ = Self::send_lockout_email(state, email, retry_after).await;
+                        Err(LoginError::AccountLocked(retry_after))
+                    }
+                    _ => Err(LoginError::IncorrectCredentials),
+                };
+            }
+            Err(UserStoreError::UserNotFound) => {
+                return Err(LoginError::UserNotFound(email.as_ref().to_string()))
+            }
+            Err(_) => return Err(LoginError::InternalServerError),
+        };
+
+        if lockout != LoginLockout::default() {
+            user_store
+                .set_lockout(email.clone(), LoginLockout::default())
+                .await
+                .map_err(|_| LoginError::InternalServerError)?;
+        }
+        Ok((user, matched_typed))
+    }
+
+    async fn current_lockout(
+        user_store: &dyn UserStore,
+        email: &Email,
+    ) -> Result<LoginLockout, LoginError> {
+        user_store
+            .get_lockout(email.clone())
             .await
-            .validate_user(email.clone(), password)
+            .map_err(|e| match e {
+                UserStoreError::UserNotFound => {
+                    LoginError::UserNotFound(email.as_ref().to_string())
+                }
+                _ => LoginError::InternalServerError,
+            })
+    }
+
+    /// The terms version `email` still has to accept before being signed
+    /// in, if any. An acceptance of the current version sent along with the
+    /// login is recorded first.
+    pub async fn pending_terms_version(
+        state: &AppState,
+        email: &Email,
+        accepted_terms: Option<TermsAcceptance>,
+    ) -> Result<Option<String>, LoginError> {
+        let Some(current) = state.config.read().await.terms_version().map(str::to_owned) else {
+            return Ok(None);
+        };
+
+        let mut terms_store = state.terms_store.write().await;
+        let latest = terms_store
+            .latest_acceptance(email.as_ref())
             .await
-        {
-            Err(UserStoreError::UserNotFound) => {
-                Err(LoginError::UserNotFound(email.as_ref().to_string()))
+            .map_err(|_| LoginError::InternalServerError)?;
+        if latest.is_some_and(|acceptance| acceptance.version == current) {
+            return Ok(None);
+        }
+        match accepted_terms {
+            Some(acceptance) if acceptance.version == current => {
+                terms_store
+                    .record_acceptance(email.as_ref(), acceptance)
+                    .await
+                    .map_err(|_| LoginError::InternalServerError)?;
+                Ok(None)
             }
-            Err(_) => Err(LoginError::InternalServerError),
-            Ok(user) => Ok(user),
+            _ => Ok(Some(current)),
+        }
+    }
+
+    /// Refuse sign-in for disabled and currently suspended accounts.
+    pub fn ensure_active(user: &User) -> Result<(), LoginError> {
+        match &user.status {
+            status if status.is_active(Utc::now()) => Ok(()),
+            AccountStatus::Suspended { until } => Err(LoginError::AccountSuspended(*until)),
+            _ => Err(LoginError::AccountDisabled),
         }
     }
+
+    async fn send_lockout_email(
+        state: &AppState,
+        email: &Email,
+        retry_after: i64,
+    ) -> Result<(), String> {
+        let content = format!(
+            "Your account was locked after too many failed login attempts. You can try again in {} minute(s), or reset your password.",
+            (retry_after + 59) / 60
+        );
+
+        state
+            .email_client
+            .read()
+            .await
+            .send_email(email, "your account has been locked", &content)
+            .await
+    }
+
+    /// Email `email` a signed link that confirms the address.
+    pub async fn send_verification_email(state: &AppState, email: &Email) -> Result<(), String> {
+        let link = {
+            let config = state.config.read().await;
+            let expires_at =
+                Utc::now() + Duration::seconds(config.email_verification_ttl_seconds());
+            let token =
+                sign_email_verification_token(config.email_verification_key(), email, expires_at);
+            format!(
+                "{}/verify-email?token={}",
+                config.public_base_url().trim_end_matches('/'),
+                token
+            )
+        };
+
+        state
+            .email_client
+            .read()
+            .await
+            .send_email(email, "confirm your email address", &link)
+            .await
+    }
 }
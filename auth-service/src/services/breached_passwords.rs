@@ -0,0 +1,243 @@
+//! Offline screening of passwords against a breached-password corpus.
+//!
+//! The corpus is stored as a Bloom filter over the first 16 bytes of each
+//! password's SHA-1 digest, the same digests published by Have I Been Pwned,
+//! so a filter can be built straight from their hash list without ever
+//! seeing a plaintext. Lookups can return false positives at the rate chosen
+//! when the filter was built, never false negatives.
+//!
+//! On-disk layout (little endian):
+//! - magic `BPWF`
+//! - format version (`u8`, currently 1)
+//! - number of hash functions (`u8`)
+//! - two reserved zero bytes
+//! - number of bits (`u64`)
+//! - the bit array, `ceil(bits / 8)` bytes
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use sha1::{Digest, Sha1};
+use thiserror::Error;
+
+const MAGIC: &[u8; 4] = b"BPWF";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = 16;
+const MAX_HASHES: u8 = 32;
+
+#[derive(Error, Debug)]
+pub enum BreachedPasswordFilterError {
+    #[error("could not read or write breached password filter: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid breached password filter: {0}")]
+    InvalidFormat(&'static str),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BreachedPasswordFilter {
+    hashes: u8,
+    bit_count: u64,
+    bits: Vec<u8>,
+}
+
+impl BreachedPasswordFilter {
+    /// Empty filter sized for `expected_items` entries at the given false
+    /// positive rate.
+    pub fn new(expected_items: usize, false_positive_rate: f64) -> Self {
+        let n = expected_items.max(1) as f64;
+        let p = false_positive_rate.clamp(1e-9, 0.5);
+        let ln2 = std::f64::consts::LN_2;
+        let bit_count = ((-n * p.ln()) / (ln2 * ln2)).ceil().max(8.0) as u64;
+        let hashes = ((bit_count as f64 / n) * ln2)
+            .round()
+            .clamp(1.0, MAX_HASHES as f64) as u8;
+        Self {
+            hashes,
+            bit_count,
+            bits: vec![0; bit_count.div_ceil(8) as usize],
+        }
+    }
+
+    /// Add a raw SHA-1 digest.
+    pub fn insert_sha1(&mut self, digest: &[u8; 20]) {
+        for bit in bit_positions(self.hashes, self.bit_count, digest) {
+            self.bits[(bit / 8) as usize] |= 1 << (bit % 8);
+        }
+    }
+
+    /// Add a hex SHA-1 digest, accepting Have I Been Pwned lines such as
+    /// `5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:9545824`.
+    pub fn insert_sha1_hex(&mut self, line: &str) -> Result<(), BreachedPasswordFilterError> {
+        let hex_digest = line.split(':').next().unwrap_or_default().trim();
+        let mut digest = [0u8; 20];
+        hex::decode_to_slice(hex_digest, &mut digest).map_err(|_| {
+            BreachedPasswordFilterError::InvalidFormat("expected a SHA-1 hex digest")
+        })?;
+        self.insert_sha1(&digest);
+        Ok(())
+    }
+
+    /// Add every line of a Have I Been Pwned SHA-1 hash list, skipping blank
+    /// lines. Returns how many digests were added.
+    pub fn insert_sha1_hex_lines(
+        &mut self,
+        reader: impl BufRead,
+    ) -> Result<usize, BreachedPasswordFilterError> {
+        let mut inserted = 0;
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            self.insert_sha1_hex(&line)?;
+            inserted += 1;
+        }
+        Ok(inserted)
+    }
+
+    pub fn insert_password(&mut self, password: &str) {
+        self.insert_sha1(&Sha1::digest(password.as_bytes()).into());
+    }
+
+    /// Whether `password` is (probably) in the corpus.
+    pub fn contains_password(&self, password: &str) -> bool {
+        let digest: [u8; 20] = Sha1::digest(password.as_bytes()).into();
+        bit_positions(self.hashes, self.bit_count, &digest)
+            .all(|bit| self.bits[(bit / 8) as usize] & (1 << (bit % 8)) != 0)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, BreachedPasswordFilterError> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), BreachedPasswordFilterError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn read_from(mut reader: impl Read) -> Result<Self, BreachedPasswordFilterError> {
+        let mut header = [0u8; HEADER_LEN];
+        reader.read_exact(&mut header)?;
+        if &header[0..4] != MAGIC {
+            return Err(BreachedPasswordFilterError::InvalidFormat("bad magic"));
+        }
+        if header[4] != VERSION {
+            return Err(BreachedPasswordFilterError::InvalidFormat(
+                "unsupported version",
+            ));
+        }
+        let hashes = header[5];
+        if hashes == 0 || hashes > MAX_HASHES {
+            return Err(BreachedPasswordFilterError::InvalidFormat(
+                "bad hash function count",
+            ));
+        }
+        let bit_count = u64::from_le_bytes(header[8..16].try_into().expect("8 byte slice"));
+        if bit_count == 0 {
+            return Err(BreachedPasswordFilterError::InvalidFormat(
+                "empty bit array",
+            ));
+        }
+
+        let mut bits = Vec::new();
+        reader.read_to_end(&mut bits)?;
+        if bits.len() as u64 != bit_count.div_ceil(8) {
+            return Err(BreachedPasswordFilterError::InvalidFormat(
+                "bit array length does not match header",
+            ));
+        }
+
+        Ok(Self {
+            hashes,
+            bit_count,
+            bits,
+        })
+    }
+
+    pub fn write_to(&self, mut writer: impl Write) -> Result<(), BreachedPasswordFilterError> {
+        let mut header = [0u8; HEADER_LEN];
+        header[0..4].copy_from_slice(MAGIC);
+        header[4] = VERSION;
+        header[5] = self.hashes;
+        header[8..16].copy_from_slice(&self.bit_count.to_le_bytes());
+        writer.write_all(&header)?;
+        writer.write_all(&self.bits)?;
+        Ok(())
+    }
+}
+
+/// Double hashing over the first 16 digest bytes; SHA-1 output is already
+/// uniform, so no further mixing is needed.
+fn bit_positions(hashes: u8, bit_count: u64, digest: &[u8; 20]) -> impl Iterator<Item = u64> {
+    let h1 = u64::from_le_bytes(digest[0..8].try_into().expect("8 byte slice"));
+    let h2 = u64::from_le_bytes(digest[8..16].try_into().expect("8 byte slice")) | 1;
+    (0..hashes as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % bit_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finds_inserted_passwords_only() {
+        let mut filter = BreachedPasswordFilter::new(100, 0.001);
+        filter.insert_password("Password1!");
+        filter
+            .insert_sha1_hex("5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:9545824")
+            .unwrap();
+
+        assert!(filter.contains_password("Password1!"));
+        // SHA-1 of "password"
+        assert!(filter.contains_password("password"));
+        assert!(!filter.contains_password("Tr0ub4dor&3-horse-staple"));
+    }
+
+    #[test]
+    fn test_rejects_malformed_hex_lines() {
+        let mut filter = BreachedPasswordFilter::new(10, 0.01);
+        assert!(filter.insert_sha1_hex("not-a-digest:12").is_err());
+        assert!(filter.insert_sha1_hex("5BAA61E4").is_err());
+    }
+
+    #[test]
+    fn test_builds_from_a_hash_list() {
+        // SHA-1 of "password" and "123456", the second without a count
+        let list = "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:9545824\r\n\n\
+                    7C4A8D09CA3762AF61E59520943DC26494F8941B\n";
+        let mut filter = BreachedPasswordFilter::new(2, 0.001);
+
+        assert_eq!(filter.insert_sha1_hex_lines(list.as_bytes()).unwrap(), 2);
+        assert!(filter.contains_password("password"));
+        assert!(filter.contains_password("123456"));
+        assert!(!filter.contains_password("Tr0ub4dor&3-horse-staple"));
+        assert!(filter
+            .insert_sha1_hex_lines("5BAA61E4\n".as_bytes())
+            .is_err());
+    }
+
+    #[test]
+    fn test_round_trips_through_bytes() {
+        let mut filter = BreachedPasswordFilter::new(1000, 0.01);
+        filter.insert_password("Qwerty123!");
+
+        let mut bytes = Vec::new();
+        filter.write_to(&mut bytes).unwrap();
+        let loaded = BreachedPasswordFilter::read_from(bytes.as_slice()).unwrap();
+        assert_eq!(filter, loaded);
+        assert!(loaded.contains_password("Qwerty123!"));
+    }
+
+    #[test]
+    fn test_rejects_truncated_or_foreign_files() {
+        let filter = BreachedPasswordFilter::new(1000, 0.01);
+        let mut bytes = Vec::new();
+        filter.write_to(&mut bytes).unwrap();
+
+        assert!(BreachedPasswordFilter::read_from(&bytes[..bytes.len() - 1]).is_err());
+        assert!(BreachedPasswordFilter::read_from(&bytes[..4]).is_err());
+        bytes[0] = b'X';
+        assert!(BreachedPasswordFilter::read_from(bytes.as_slice()).is_err());
+    }
+}
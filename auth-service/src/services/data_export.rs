@@ -0,0 +1,92 @@
+//! Personal data exports for data-subject access requests.
+//!
+//! The whole `DataExport` document is gathered before anything is sent, so a
+//! store failing part way answers with an error instead of a truncated
+//! download. Each store is only read, and locked, while its own section is
+//! produced; one account's export is small enough to hold in memory.
+//!
+//! The service keeps no audit log, so there are no audit events to include.
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::Utc;
+use thiserror::Error;
+
+use crate::app_state::AppState;
+use crate::domain::{
+    DataExport, ExportConsent, ExportLoginLockout, ExportMfa, ExportProfile, ExportSession, User,
+};
+
+#[derive(Error, Debug)]
+pub enum DataExportError {
+    #[error("could not read {0} for the data export")]
+    Store(&'static str),
+}
+
+/// A download response with the export of `user`.
+pub async fn export_response(state: &AppState, user: &User) -> Result<Response, DataExportError> {
+    let export = collect_export(state, user).await?;
+    let filename = format!(
+        "account-data-{}.json",
+        export.exported_at.format("%Y-%m-%d")
+    );
+
+    Ok((
+        StatusCode::OK,
+        [(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", filename),
+        )],
+        Json(export),
+    )
+        .into_response())
+}
+
+async fn collect_export(state: &AppState, user: &User) -> Result<DataExport, DataExportError> {
+    let exported_at = Utc::now();
+    let (lockout, status_reason) = {
+        let user_store = state.user_store.read().await;
+        let lockout = user_store
+            .get_lockout(user.email.clone())
+            .await
+            .map_err(|_| DataExportError::Store("login lockout"))?;
+        let status_reason = user_store
+            .get_status_reason(user.email.clone())
+            .await
+            .map_err(|_| DataExportError::Store("account status"))?;
+        (lockout, status_reason)
+    };
+    let sessions = state
+        .token_service
+        .read()
+        .await
+        .session_history(user.email.as_ref())
+        .await
+        .map_err(|_| DataExportError::Store("sessions"))?;
+    let devices = state
+        .trusted_device_store
+        .read()
+        .await
+        .list_devices(user.email.as_ref(), exported_at)
+        .await
+        .map_err(|_| DataExportError::Store("trusted devices"))?;
+    let acceptances = state
+        .terms_store
+        .read()
+        .await
+        .list_acceptances(user.email.as_ref())
+        .await
+        .map_err(|_| DataExportError::Store("consent records"))?;
+
+    Ok(DataExport {
+        exported_at,
+        profile: ExportProfile::new(user, status_reason),
+        login_lockout: ExportLoginLockout::from(lockout),
+        sessions: sessions.into_iter().map(ExportSession::from).collect(),
+        mfa: ExportMfa {
+            email_code_enabled: user.requires_mfa,
+            trusted_devices: devices.into_iter().map(Into::into).collect(),
+        },
+        consents: acceptances.into_iter().map(ExportConsent::from).collect(),
+    })
+}
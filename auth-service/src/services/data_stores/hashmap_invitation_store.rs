@@ -0,0 +1,112 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+use crate::domain::data_stores::{Invitation, InvitationStore, InvitationStoreError};
+
+#[derive(Default)]
+pub struct HashmapInvitationStore {
+    invitations: HashMap<[u8; 32], Invitation>,
+}
+
+#[async_trait::async_trait]
+impl InvitationStore for HashmapInvitationStore {
+    async fn add_invitation(&mut self, invitation: Invitation) -> Result<(), InvitationStoreError> {
+        let _ = self.invitations.insert(invitation.code_hash, invitation);
+        Ok(())
+    }
+
+    async fn redeem(
+        &mut self,
+        code_hash: &[u8; 32],
+        email: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), InvitationStoreError> {
+        let invitation = self
+            .invitations
+            .get_mut(code_hash)
+            .ok_or(InvitationStoreError::InvitationNotFound)?;
+        if invitation.expires_at <= now {
+            return Err(InvitationStoreError::Expired);
+        }
+        if invitation
+            .email
+            .as_deref()
+            .is_some_and(|bound| bound != email)
+        {
+            return Err(InvitationStoreError::EmailMismatch);
+        }
+        if invitation.uses >= invitation.max_uses {
+            return Err(InvitationStoreError::Exhausted);
+        }
+        invitation.uses += 1;
+        Ok(())
+    }
+
+    async fn release(&mut self, code_hash: &[u8; 32]) -> Result<(), InvitationStoreError> {
+        if let Some(invitation) = self.invitations.get_mut(code_hash) {
+            invitation.uses = invitation.uses.saturating_sub(1);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn invitation(code: u8, email: Option<&str>, max_uses: u32) -> Invitation {
+        let now = Utc::now();
+        Invitation {
+            code_hash: [code; 32],
+            email: email.map(str::to_owned),
+            max_uses,
+            uses: 0,
+            created_at: now,
+            expires_at: now + Duration::days(1),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_redeems_up_to_max_uses() {
+        let mut store = HashmapInvitationStore::default();
+        store.add_invitation(invitation(1, None, 2)).await.unwrap();
+        let now = Utc::now();
+
+        assert_eq!(Ok(()), store.redeem(&[1; 32], "a@example.com", now).await);
+        assert_eq!(Ok(()), store.redeem(&[1; 32], "b@example.com", now).await);
+        assert_eq!(
+            Err(InvitationStoreError::Exhausted),
+            store.redeem(&[1; 32], "c@example.com", now).await
+        );
+
+        store.release(&[1; 32]).await.unwrap();
+        assert_eq!(Ok(()), store.redeem(&[1; 32], "c@example.com", now).await);
+    }
+
+    #[tokio::test]
+    async fn test_refuses_unknown_expired_and_foreign_redemptions() {
+        let mut store = HashmapInvitationStore::default();
+        store
+            .add_invitation(invitation(1, Some("a@example.com"), 1))
+            .await
+            .unwrap();
+        let now = Utc::now();
+
+        assert_eq!(
+            Err(InvitationStoreError::InvitationNotFound),
+            store.redeem(&[2; 32], "a@example.com", now).await
+        );
+        assert_eq!(
+            Err(InvitationStoreError::EmailMismatch),
+            store.redeem(&[1; 32], "b@example.com", now).await
+        );
+        assert_eq!(
+            Err(InvitationStoreError::Expired),
+            store
+                .redeem(&[1; 32], "a@example.com", now + Duration::days(2))
+                .await
+        );
+        assert_eq!(Ok(()), store.redeem(&[1; 32], "a@example.com", now).await);
+    }
+}
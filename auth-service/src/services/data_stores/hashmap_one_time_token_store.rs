@@ -0,0 +1,130 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+use crate::domain::data_stores::{OneTimeTokenStore, OneTimeTokenStoreError, TokenPurpose};
+
+#[derive(Default)]
+pub struct HashmapOneTimeTokenStore {
+    tokens: HashMap<(TokenPurpose, [u8; 32]), (String, DateTime<Utc>)>,
+}
+
+#[async_trait::async_trait]
+impl OneTimeTokenStore for HashmapOneTimeTokenStore {
+    async fn add_token(
+        &mut self,
+        purpose: TokenPurpose,
+        token_hash: [u8; 32],
+        subject: String,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), OneTimeTokenStoreError> {
+        let _ = self
+            .tokens
+            .insert((purpose, token_hash), (subject, expires_at));
+        Ok(())
+    }
+
+    async fn peek_token(
+        &self,
+        purpose: TokenPurpose,
+        token_hash: &[u8; 32],
+        now: DateTime<Utc>,
+    ) -> Result<String, OneTimeTokenStoreError> {
+        match self.tokens.get(&(purpose, *token_hash)) {
+            Some((subject, expires_at)) if *expires_at > now => Ok(subject.clone()),
+            _ => Err(OneTimeTokenStoreError::TokenNotFound),
+        }
+    }
+
+    async fn take_token(
+        &mut self,
+        purpose: TokenPurpose,
+        token_hash: &[u8; 32],
+        now: DateTime<Utc>,
+    ) -> Result<String, OneTimeTokenStoreError> {
+        match self.tokens.remove(&(purpose, *token_hash)) {
+            Some((subject, expires_at)) if expires_at > now => Ok(subject),
+            _ => Err(OneTimeTokenStoreError::TokenNotFound),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[tokio::test]
+    async fn test_token_is_single_use() {
+        let mut store = HashmapOneTimeTokenStore::default();
+        let now = Utc::now();
+        let _ = store
+            .add_token(
+                TokenPurpose::MagicLink,
+                [1u8; 32],
+                "lads@tst.com".to_owned(),
+                now + Duration::minutes(5),
+            )
+            .await;
+
+        assert_eq!(
+            Ok("lads@tst.com".to_owned()),
+            store
+                .take_token(TokenPurpose::MagicLink, &[1u8; 32], now)
+                .await
+        );
+        assert_eq!(
+            Err(OneTimeTokenStoreError::TokenNotFound),
+            store
+                .take_token(TokenPurpose::MagicLink, &[1u8; 32], now)
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_peek_leaves_token_in_place() {
+        let mut store = HashmapOneTimeTokenStore::default();
+        let now = Utc::now();
+        let _ = store
+            .add_token(
+                TokenPurpose::PasswordReset,
+                [1u8; 32],
+                "lads@tst.com".to_owned(),
+                now + Duration::minutes(5),
+            )
+            .await;
+
+        assert_eq!(
+            Ok("lads@tst.com".to_owned()),
+            store
+                .peek_token(TokenPurpose::PasswordReset, &[1u8; 32], now)
+                .await
+        );
+        assert_eq!(
+            Ok("lads@tst.com".to_owned()),
+            store
+                .take_token(TokenPurpose::PasswordReset, &[1u8; 32], now)
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_expired_token_is_rejected() {
+        let mut store = HashmapOneTimeTokenStore::default();
+        let now = Utc::now();
+        let _ = store
+            .add_token(
+                TokenPurpose::MagicLink,
+                [1u8; 32],
+                "lads@tst.com".to_owned(),
+                now - Duration::seconds(1),
+            )
+            .await;
+
+        assert_eq!(
+            Err(OneTimeTokenStoreError::TokenNotFound),
+            store
+                .take_token(TokenPurpose::MagicLink, &[1u8; 32], now)
+                .await
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use crate::domain::data_stores::{TermsAcceptance, TermsStore, TermsStoreError};
+
+#[derive(Default)]
+pub struct HashmapTermsStore {
+    acceptances: HashMap<String, Vec<TermsAcceptance>>,
+}
+
+#[async_trait::async_trait]
+impl TermsStore for HashmapTermsStore {
+    async fn record_acceptance(
+        &mut self,
+        email: &str,
+        acceptance: TermsAcceptance,
+    ) -> Result<(), TermsStoreError> {
+        self.acceptances
+            .entry(email.to_owned())
+            .or_default()
+            .push(acceptance);
+        Ok(())
+    }
+
+    async fn latest_acceptance(
+        &self,
+        email: &str,
+    ) -> Result<Option<TermsAcceptance>, TermsStoreError> {
+        Ok(self
+            .acceptances
+            .get(email)
+            .and_then(|acceptances| acceptances.last())
+            .cloned())
+    }
+
+    async fn list_acceptances(&self, email: &str) -> Result<Vec<TermsAcceptance>, TermsStoreError> {
+        Ok(self.acceptances.get(email).cloned().unwrap_or_default())
+    }
+
+    async fn change_email(
+        &mut self,
+        old_email: &str,
+        new_email: &str,
+    ) -> Result<(), TermsStoreError> {
+        if let Some(acceptances) = self.acceptances.remove(old_email) {
+            self.acceptances.insert(new_email.to_owned(), acceptances);
+        }
+        Ok(())
+    }
+
+    async fn remove_for(&mut self, email: &str) -> Result<(), TermsStoreError> {
+        self.acceptances.remove(email);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn acceptance(version: &str) -> TermsAcceptance {
+        TermsAcceptance {
+            version: version.to_owned(),
+            accepted_at: Utc::now(),
+            ip_address: Some("192.0.2.1".to_owned()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_returns_the_latest_acceptance() {
+        let mut store = HashmapTermsStore::default();
+        assert_eq!(Ok(None), store.latest_acceptance("a@example.com").await);
+
+        store
+            .record_acceptance("a@example.com", acceptance("2024-01"))
+            .await
+            .unwrap();
+        store
+            .record_acceptance("a@example.com", acceptance("2025-06"))
+            .await
+            .unwrap();
+
+        let latest = store.latest_acceptance("a@example.com").await.unwrap();
+        assert_eq!(latest.map(|a| a.version).as_deref(), Some("2025-06"));
+        let versions: Vec<String> = store
+            .list_acceptances("a@example.com")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|a| a.version)
+            .collect();
+        assert_eq!(versions, ["2024-01", "2025-06"]);
+        assert_eq!(Ok(None), store.latest_acceptance("b@example.com").await);
+    }
+
+    #[tokio::test]
+    async fn test_follows_email_changes_and_deletion() {
+        let mut store = HashmapTermsStore::default();
+        store
+            .record_acceptance("a@example.com", acceptance("2025-06"))
+            .await
+            .unwrap();
+
+        store
+            .change_email("a@example.com", "b@example.com")
+            .await
+            .unwrap();
+        assert_eq!(Ok(None), store.latest_acceptance("a@example.com").await);
+        assert_eq!(
+            store.list_acceptances("b@example.com").await.unwrap().len(),
+            1
+        );
+
+        store.remove_for("b@example.com").await.unwrap();
+        assert_eq!(Ok(None), store.latest_acceptance("b@example.com").await);
+    }
+}
@@ -0,0 +1,119 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::domain::data_stores::{TrustedDevice, TrustedDeviceStore, TrustedDeviceStoreError};
+
+#[derive(Default)]
+pub struct HashmapTrustedDeviceStore {
+    devices: HashMap<Uuid, TrustedDevice>,
+}
+
+#[async_trait::async_trait]
+impl TrustedDeviceStore for HashmapTrustedDeviceStore {
+    async fn add_device(&mut self, device: TrustedDevice) -> Result<(), TrustedDeviceStoreError> {
+        let _ = self.devices.insert(device.device_id, device);
+        Ok(())
+    }
+
+    async fn get_device(
+        &self,
+        device_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<TrustedDevice, TrustedDeviceStoreError> {
+        self.devices
+            .get(&device_id)
+            .filter(|d| d.expires_at > now)
+            .cloned()
+            .ok_or(TrustedDeviceStoreError::DeviceNotFound)
+    }
+
+    async fn list_devices(
+        &self,
+        user_id: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<TrustedDevice>, TrustedDeviceStoreError> {
+        let mut devices: Vec<TrustedDevice> = self
+            .devices
+            .values()
+            .filter(|d| d.user_id == user_id && d.expires_at > now)
+            .cloned()
+            .collect();
+        devices.sort_by_key(|d| d.created_at);
+        Ok(devices)
+    }
+
+    async fn revoke_device(
+        &mut self,
+        user_id: &str,
+        device_id: Uuid,
+    ) -> Result<(), TrustedDeviceStoreError> {
+        match self.devices.get(&device_id) {
+            Some(d) if d.user_id == user_id => {
+                let _ = self.devices.remove(&device_id);
+                Ok(())
+            }
+            _ => Err(TrustedDeviceStoreError::DeviceNotFound),
+        }
+    }
+
+    async fn revoke_all(&mut self, user_id: &str) -> Result<(), TrustedDeviceStoreError> {
+        self.devices.retain(|_, d| d.user_id != user_id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn device(user_id: &str, expires_in: Duration) -> TrustedDevice {
+        let now = Utc::now();
+        TrustedDevice {
+            device_id: Uuid::new_v4(),
+            user_id: user_id.to_string(),
+            created_at: now,
+            expires_at: now + expires_in,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_expired_devices_are_not_returned() {
+        let mut store = HashmapTrustedDeviceStore::default();
+        let live = device("lads@tst.com", Duration::days(30));
+        let expired = device("lads@tst.com", Duration::seconds(-1));
+        let _ = store.add_device(live.clone()).await;
+        let _ = store.add_device(expired.clone()).await;
+
+        assert_eq!(
+            Ok(live.clone()),
+            store.get_device(live.device_id, Utc::now()).await
+        );
+        assert_eq!(
+            Err(TrustedDeviceStoreError::DeviceNotFound),
+            store.get_device(expired.device_id, Utc::now()).await
+        );
+        assert_eq!(
+            Ok(vec![live]),
+            store.list_devices("lads@tst.com", Utc::now()).await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_revoke_device_only_for_owner() {
+        let mut store = HashmapTrustedDeviceStore::default();
+        let d = device("lads@tst.com", Duration::days(30));
+        let _ = store.add_device(d.clone()).await;
+
+        assert_eq!(
+            Err(TrustedDeviceStoreError::DeviceNotFound),
+            store.revoke_device("other@tst.com", d.device_id).await
+        );
+        assert_eq!(
+            Ok(()),
+            store.revoke_device("lads@tst.com", d.device_id).await
+        );
+        assert!(store.get_device(d.device_id, Utc::now()).await.is_err());
+    }
+}
@@ -1,13 +1,40 @@
-use std::collections::HashMap;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, VecDeque};
 
 use crate::domain::{
     data_stores::{TwoFACodeStore, TwoFAError},
-    Email, LoginAttemptId, TwoFACode,
+    Email, LoginAttemptId, TwoFACode, TwoFAPurpose,
 };
 
 #[derive(Default)]
 pub struct HashmapTwoFACodeStore {
-    codes: HashMap<Email, (LoginAttemptId, TwoFACode)>,
+    codes: HashMap<LoginAttemptId, (Email, TwoFACode)>,
+    // what each pending code was sent for
+    purposes: HashMap<LoginAttemptId, TwoFAPurpose>,
+    // outstanding attempts per email, oldest first
+    pending_by_email: HashMap<Email, VecDeque<LoginAttemptId>>,
+    // wrong codes entered for each pending attempt
+    attempt_failures: HashMap<LoginAttemptId, u32>,
+    // when the current code of each attempt was issued and how often it was resent
+    deliveries: HashMap<LoginAttemptId, (DateTime<Utc>, u32)>,
+    // timestamps of wrong codes per email, across attempts
+    account_failures: HashMap<Email, Vec<DateTime<Utc>>>,
+}
+
+impl HashmapTwoFACodeStore {
+    fn forget_attempt(&mut self, login_attempt_id: &LoginAttemptId) {
+        let _ = self.attempt_failures.remove(login_attempt_id);
+        let _ = self.deliveries.remove(login_attempt_id);
+        let _ = self.purposes.remove(login_attempt_id);
+        if let Some((email, _)) = self.codes.remove(login_attempt_id) {
+            if let Some(pending) = self.pending_by_email.get_mut(&email) {
+                pending.retain(|id| id != login_attempt_id);
+                if pending.is_empty() {
+                    let _ = self.pending_by_email.remove(&email);
+                }
+            }
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -17,22 +44,393 @@ impl TwoFACodeStore for HashmapTwoFACodeStore {
         email: Email,
         login_attempt_id: LoginAttemptId,
         code: TwoFACode,
+        purpose: TwoFAPurpose,
+        max_pending: usize,
+    ) -> Result<(), TwoFAError> {
+        // make room by dropping the oldest outstanding attempts of this user
+        loop {
+            let oldest = match self.pending_by_email.get(&email) {
+                Some(pending) if pending.len() >= max_pending.max(1) => pending.front().cloned(),
+                _ => None,
+            };
+            match oldest {
+                Some(id) => self.forget_attempt(&id),
+                None => break,
+            }
+        }
+
+        self.pending_by_email
+            .entry(email.clone())
+            .or_default()
+            .push_back(login_attempt_id.clone());
+        let _ = self
+            .deliveries
+            .insert(login_attempt_id.clone(), (Utc::now(), 0));
+        let _ = self.purposes.insert(login_attempt_id.clone(), purpose);
+        let _ = self.codes.insert(login_attempt_id, (email, code));
+        Ok(())
+    }
+
+    async fn resend_code(
+        &mut self,
+        login_attempt_id: &LoginAttemptId,
+        code: TwoFACode,
+        now: DateTime<Utc>,
+        cooldown: Duration,
+        max_resends: u32,
     ) -> Result<(), TwoFAError> {
-        //if there's an old value for this email address it'll be returned, but we don't care about that
-        let _ = self.codes.insert(email, (login_attempt_id, code));
+        let (email, _) = self
+            .codes
+            .get(login_attempt_id)
+            .cloned()
+            .ok_or(TwoFAError::LoginAttemptIdNotFound)?;
+        let (issued_at, resends) = self
+            .deliveries
+            .get(login_attempt_id)
+            .copied()
+            .ok_or(TwoFAError::LoginAttemptIdNotFound)?;
+
+        if resends >= max_resends {
+            return Err(TwoFAError::ResendLimitReached);
+        }
+        if now < issued_at + cooldown {
+            return Err(TwoFAError::ResendCooldown);
+        }
+
+        let _ = self
+            .deliveries
+            .insert(login_attempt_id.clone(), (now, resends + 1));
+        let _ = self.codes.insert(login_attempt_id.clone(), (email, code));
         Ok(())
     }
 
-    async fn remove_code(&mut self, email: &Email) -> Result<(), TwoFAError> {
-        //returns the value matching that key, if exists, but we don't care about that
-        let _ = self.codes.remove(email);
+    async fn remove_code(&mut self, login_attempt_id: &LoginAttemptId) -> Result<(), TwoFAError> {
+        self.forget_attempt(login_attempt_id);
         Ok(())
     }
 
-    async fn get_code(&self, email: &Email) -> Result<&(LoginAttemptId, TwoFACode), TwoFAError> {
-        match self.codes.get(email) {
+    async fn remove_codes_for(&mut self, email: &Email) -> Result<(), TwoFAError> {
+        let pending = self.pending_by_email.remove(email).unwrap_or_default();
+        for login_attempt_id in &pending {
+            self.forget_attempt(login_attempt_id);
+        }
+        let _ = self.account_failures.remove(email);
+        Ok(())
+    }
+
+    async fn get_code(
+        &self,
+        login_attempt_id: &LoginAttemptId,
+        purpose: TwoFAPurpose,
+    ) -> Result<&(Email, TwoFACode), TwoFAError> {
+        if self.purposes.get(login_attempt_id) != Some(&purpose) {
+            return Err(TwoFAError::LoginAttemptIdNotFound);
+        }
+        match self.codes.get(login_attempt_id) {
             Some(v) => Ok(v),
             None => Err(TwoFAError::LoginAttemptIdNotFound),
         }
     }
+
+    async fn record_failed_attempt(
+        &mut self,
+        login_attempt_id: &LoginAttemptId,
+    ) -> Result<u32, TwoFAError> {
+        if !self.codes.contains_key(login_attempt_id) {
+            return Err(TwoFAError::LoginAttemptIdNotFound);
+        }
+        let failures = self
+            .attempt_failures
+            .entry(login_attempt_id.clone())
+            .or_insert(0);
+        *failures += 1;
+        Ok(*failures)
+    }
+
+    async fn record_account_failure(
+        &mut self,
+        email: &Email,
+        now: DateTime<Utc>,
+        window: Duration,
+    ) -> Result<u32, TwoFAError> {
+        let failures = self.account_failures.entry(email.clone()).or_default();
+        failures.retain(|at| *at > now - window);
+        failures.push(now);
+        Ok(failures.len() as u32)
+    }
+
+    async fn account_failures(&self, email: &Email, now: DateTime<Utc>, window: Duration) -> u32 {
+        self.account_failures
+            .get(email)
+            .map(|failures| failures.iter().filter(|at| **at > now - window).count() as u32)
+            .unwrap_or(0)
+    }
+
+    async fn clear_account_failures(&mut self, email: &Email) -> Result<(), TwoFAError> {
+        let _ = self.account_failures.remove(email);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn email() -> Email {
+        Email::parse("lads@tst.com".to_string()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_attempts_do_not_overwrite_each_other() {
+        let mut store = HashmapTwoFACodeStore::default();
+        let (laptop, phone) = (LoginAttemptId::default(), LoginAttemptId::default());
+        let (laptop_code, phone_code) = (TwoFACode::default(), TwoFACode::default());
+
+        let _ = store
+            .add_code(
+                email(),
+                laptop.clone(),
+                laptop_code.clone(),
+                TwoFAPurpose::Login,
+                5,
+            )
+            .await;
+        let _ = store
+            .add_code(
+                email(),
+                phone.clone(),
+                phone_code.clone(),
+                TwoFAPurpose::Login,
+                5,
+            )
+            .await;
+
+        assert_eq!(
+            Ok(&(email(), laptop_code)),
+            store.get_code(&laptop, TwoFAPurpose::Login).await
+        );
+        assert_eq!(
+            Ok(&(email(), phone_code)),
+            store.get_code(&phone, TwoFAPurpose::Login).await
+        );
+
+        let _ = store.remove_code(&laptop).await;
+        assert_eq!(
+            Err(TwoFAError::LoginAttemptIdNotFound),
+            store.get_code(&laptop, TwoFAPurpose::Login).await
+        );
+        assert!(store.get_code(&phone, TwoFAPurpose::Login).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_remove_codes_for_only_touches_that_email() {
+        let mut store = HashmapTwoFACodeStore::default();
+        let other = Email::parse("other@tst.com".to_string()).unwrap();
+        let (mine, theirs) = (LoginAttemptId::default(), LoginAttemptId::default());
+        let _ = store
+            .add_code(
+                email(),
+                mine.clone(),
+                TwoFACode::default(),
+                TwoFAPurpose::Login,
+                5,
+            )
+            .await;
+        let _ = store
+            .add_code(
+                other,
+                theirs.clone(),
+                TwoFACode::default(),
+                TwoFAPurpose::Login,
+                5,
+            )
+            .await;
+
+        assert_eq!(Ok(()), store.remove_codes_for(&email()).await);
+        assert!(store.get_code(&mine, TwoFAPurpose::Login).await.is_err());
+        assert!(store.get_code(&theirs, TwoFAPurpose::Login).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_get_code_only_for_the_stored_purpose() {
+        let mut store = HashmapTwoFACodeStore::default();
+        let (login, step_up) = (LoginAttemptId::default(), LoginAttemptId::default());
+        let _ = store
+            .add_code(
+                email(),
+                login.clone(),
+                TwoFACode::default(),
+                TwoFAPurpose::Login,
+                5,
+            )
+            .await;
+        let _ = store
+            .add_code(
+                email(),
+                step_up.clone(),
+                TwoFACode::default(),
+                TwoFAPurpose::DisableMfa,
+                5,
+            )
+            .await;
+
+        assert!(store.get_code(&login, TwoFAPurpose::Login).await.is_ok());
+        assert_eq!(
+            Err(TwoFAError::LoginAttemptIdNotFound),
+            store.get_code(&login, TwoFAPurpose::DisableMfa).await
+        );
+        assert!(store
+            .get_code(&step_up, TwoFAPurpose::DisableMfa)
+            .await
+            .is_ok());
+        assert_eq!(
+            Err(TwoFAError::LoginAttemptIdNotFound),
+            store.get_code(&step_up, TwoFAPurpose::Login).await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_code_evicts_oldest_attempt_over_cap() {
+        let mut store = HashmapTwoFACodeStore::default();
+        let attempts: Vec<LoginAttemptId> = (0..3).map(|_| LoginAttemptId::default()).collect();
+        for id in &attempts {
+            let _ = store
+                .add_code(
+                    email(),
+                    id.clone(),
+                    TwoFACode::default(),
+                    TwoFAPurpose::Login,
+                    2,
+                )
+                .await;
+        }
+
+        assert!(store
+            .get_code(&attempts[0], TwoFAPurpose::Login)
+            .await
+            .is_err());
+        assert!(store
+            .get_code(&attempts[1], TwoFAPurpose::Login)
+            .await
+            .is_ok());
+        assert!(store
+            .get_code(&attempts[2], TwoFAPurpose::Login)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_resend_code_enforces_cooldown_and_limit() {
+        let mut store = HashmapTwoFACodeStore::default();
+        let id = LoginAttemptId::default();
+        let cooldown = Duration::seconds(30);
+        let _ = store
+            .add_code(
+                email(),
+                id.clone(),
+                TwoFACode::default(),
+                TwoFAPurpose::Login,
+                5,
+            )
+            .await;
+
+        assert_eq!(
+            Err(TwoFAError::ResendCooldown),
+            store
+                .resend_code(&id, TwoFACode::default(), Utc::now(), cooldown, 1)
+                .await
+        );
+
+        let later = Utc::now() + Duration::seconds(31);
+        let new_code = TwoFACode::default();
+        assert_eq!(
+            Ok(()),
+            store
+                .resend_code(&id, new_code.clone(), later, cooldown, 1)
+                .await
+        );
+        assert_eq!(
+            Ok(&(email(), new_code)),
+            store.get_code(&id, TwoFAPurpose::Login).await
+        );
+
+        assert_eq!(
+            Err(TwoFAError::ResendLimitReached),
+            store
+                .resend_code(&id, TwoFACode::default(), later + cooldown, cooldown, 1)
+                .await
+        );
+        assert_eq!(
+            Err(TwoFAError::LoginAttemptIdNotFound),
+            store
+                .resend_code(
+                    &LoginAttemptId::default(),
+                    TwoFACode::default(),
+                    later,
+                    cooldown,
+                    1
+                )
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_record_failed_attempt_counts_per_attempt() {
+        let mut store = HashmapTwoFACodeStore::default();
+        let (first, second) = (LoginAttemptId::default(), LoginAttemptId::default());
+        assert_eq!(
+            Err(TwoFAError::LoginAttemptIdNotFound),
+            store.record_failed_attempt(&first).await
+        );
+
+        let _ = store
+            .add_code(
+                email(),
+                first.clone(),
+                TwoFACode::default(),
+                TwoFAPurpose::Login,
+                5,
+            )
+            .await;
+        let _ = store
+            .add_code(
+                email(),
+                second.clone(),
+                TwoFACode::default(),
+                TwoFAPurpose::Login,
+                5,
+            )
+            .await;
+        assert_eq!(Ok(1), store.record_failed_attempt(&first).await);
+        assert_eq!(Ok(2), store.record_failed_attempt(&first).await);
+        assert_eq!(Ok(1), store.record_failed_attempt(&second).await);
+    }
+
+    #[tokio::test]
+    async fn test_account_failures_survive_new_attempts_within_window() {
+        let mut store = HashmapTwoFACodeStore::default();
+        let window = Duration::seconds(60);
+        let now = Utc::now();
+
+        let _ = store
+            .record_account_failure(&email(), now - Duration::seconds(120), window)
+            .await;
+        let _ = store
+            .add_code(
+                email(),
+                LoginAttemptId::default(),
+                TwoFACode::default(),
+                TwoFAPurpose::Login,
+                5,
+            )
+            .await;
+        assert_eq!(
+            Ok(1),
+            store.record_account_failure(&email(), now, window).await
+        );
+        assert_eq!(1, store.account_failures(&email(), now, window).await);
+
+        let _ = store.clear_account_failures(&email()).await;
+        assert_eq!(0, store.account_failures(&email(), now, window).await);
+    }
 }
@@ -1,26 +1,39 @@
+use argon2::Params;
 use axum::async_trait;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::domain::data_stores::UserStore;
 use crate::domain::data_stores::UserStoreError;
 use crate::domain::email::Email;
+use crate::domain::AccountStatus;
+use crate::domain::LoginLockout;
 use crate::domain::Password;
 use crate::domain::User;
+use crate::services::password_hashing;
 
 pub struct HashmapUserStore {
     users: HashMap<Email, User>,
+    lockouts: HashMap<Email, LoginLockout>,
+    status_reasons: HashMap<Email, String>,
 }
 
 impl HashmapUserStore {
     pub fn new() -> Self {
         HashmapUserStore {
             users: HashMap::new(),
+            lockouts: HashMap::new(),
+            status_reasons: HashMap::new(),
         }
     }
 
     pub fn get_user_count(&self) -> usize {
         self.users.len()
     }
+
+    /// Why the account was last given its status, if a reason was recorded.
+    pub fn status_reason(&self, email: &Email) -> Option<&str> {
+        self.status_reasons.get(email).map(String::as_str)
+    }
 }
 
 #[async_trait]
@@ -33,6 +46,57 @@ impl UserStore for HashmapUserStore {
         Ok(())
     }
 
+    /// Passwords are compared verbatim here, so an imported hash only
+    /// matches itself; the store is for tests and local runs.
+    async fn import_user(&mut self, user: User) -> Result<(), UserStoreError> {
+        if !password_hashing::is_supported_hash(user.password.as_ref()) {
+            return Err(UserStoreError::UnsupportedPasswordHash);
+        }
+        self.add_user(user).await
+    }
+
+    async fn import_users(
+        &mut self,
+        users: Vec<User>,
+        dry_run: bool,
+    ) -> Result<Vec<Result<(), UserStoreError>>, UserStoreError> {
+        let mut seen = HashSet::new();
+        let mut results = Vec::with_capacity(users.len());
+        for user in users {
+            if !password_hashing::is_supported_hash(user.password.as_ref()) {
+                results.push(Err(UserStoreError::UnsupportedPasswordHash));
+                continue;
+            }
+            if self.users.contains_key(&user.email) || !seen.insert(user.email.clone()) {
+                results.push(Err(UserStoreError::UserAlreadyExists));
+                continue;
+            }
+            if !dry_run {
+                self.users.insert(user.email.clone(), user);
+            }
+            results.push(Ok(()));
+        }
+        Ok(results)
+    }
+
+    async fn list_users(
+        &self,
+        after: Option<Email>,
+        limit: usize,
+    ) -> Result<Vec<User>, UserStoreError> {
+        let mut users: Vec<&User> = self
+            .users
+            .values()
+            .filter(|user| {
+                after
+                    .as_ref()
+                    .is_none_or(|after| user.email.as_ref() > after.as_ref())
+            })
+            .collect();
+        users.sort_by(|a, b| a.email.as_ref().cmp(b.email.as_ref()));
+        Ok(users.into_iter().take(limit).cloned().collect())
+    }
+
     async fn get_user(&self, email: Email) -> Result<User, UserStoreError> {
         self.users
             .get(&email)
@@ -40,7 +104,100 @@ impl UserStore for HashmapUserStore {
             .cloned()
     }
 
+    async fn update_user(&mut self, user: User) -> Result<User, UserStoreError> {
+        let stored = self
+            .users
+            .get_mut(&user.email)
+            .ok_or(UserStoreError::UserNotFound)?;
+        stored.requires_mfa = user.requires_mfa;
+        stored.email_verified = user.email_verified;
+        stored.profile = user.profile;
+        Ok(stored.clone())
+    }
+
+    async fn update_password(
+        &mut self,
+        email: Email,
+        password: Password,
+    ) -> Result<(), UserStoreError> {
+        let stored = self
+            .users
+            .get_mut(&email)
+            .ok_or(UserStoreError::UserNotFound)?;
+        stored.password = password;
+        Ok(())
+    }
+
+    async fn change_email(
+        &mut self,
+        email: Email,
+        new_email: Email,
+    ) -> Result<User, UserStoreError> {
+        if self.users.contains_key(&new_email) {
+            return Err(UserStoreError::UserAlreadyExists);
+        }
+        let mut user = self
+            .users
+            .remove(&email)
+            .ok_or(UserStoreError::UserNotFound)?;
+        user.email = new_email.clone();
+        if let Some(lockout) = self.lockouts.remove(&email) {
+            self.lockouts.insert(new_email.clone(), lockout);
+        }
+        if let Some(reason) = self.status_reasons.remove(&email) {
+            self.status_reasons.insert(new_email.clone(), reason);
+        }
+        self.users.insert(new_email, user.clone());
+        Ok(user)
+    }
+
+    async fn get_lockout(&self, email: Email) -> Result<LoginLockout, UserStoreError> {
+        if !self.users.contains_key(&email) {
+            return Err(UserStoreError::UserNotFound);
+        }
+        Ok(self.lockouts.get(&email).cloned().unwrap_or_default())
+    }
+
+    async fn get_status_reason(&self, email: Email) -> Result<Option<String>, UserStoreError> {
+        if !self.users.contains_key(&email) {
+            return Err(UserStoreError::UserNotFound);
+        }
+        Ok(self.status_reasons.get(&email).cloned())
+    }
+
+    async fn set_lockout(
+        &mut self,
+        email: Email,
+        lockout: LoginLockout,
+    ) -> Result<(), UserStoreError> {
+        if !self.users.contains_key(&email) {
+            return Err(UserStoreError::UserNotFound);
+        }
+        self.lockouts.insert(email, lockout);
+        Ok(())
+    }
+
+    async fn set_status(
+        &mut self,
+        email: Email,
+        status: AccountStatus,
+        reason: Option<String>,
+    ) -> Result<User, UserStoreError> {
+        let stored = self
+            .users
+            .get_mut(&email)
+            .ok_or(UserStoreError::UserNotFound)?;
+        stored.status = status;
+        match reason {
+            Some(reason) => self.status_reasons.insert(email, reason),
+            None => self.status_reasons.remove(&email),
+        };
+        Ok(stored.clone())
+    }
+
     async fn delete_user(&mut self, email: Email) -> Result<User, UserStoreError> {
+        self.lockouts.remove(&email);
+        self.status_reasons.remove(&email);
         self.users
             .remove(&email)
             .ok_or(UserStoreError::UserNotFound)
@@ -59,6 +216,28 @@ impl UserStore for HashmapUserStore {
         }
         Err(UserStoreError::UserNotFound)
     }
+
+    fn argon2_params(&self) -> Option<Params> {
+        // Passwords are kept as given here
+        None
+    }
+
+    async fn replace_password_hash(
+        &mut self,
+        email: Email,
+        current_hash: Password,
+        new_hash: Password,
+    ) -> Result<bool, UserStoreError> {
+        let stored = self
+            .users
+            .get_mut(&email)
+            .ok_or(UserStoreError::UserNotFound)?;
+        if stored.password != current_hash {
+            return Ok(false);
+        }
+        stored.password = new_hash;
+        Ok(true)
+    }
 }
 
 // TODO: Add unit tests for your `HashmapUserStore` implementation
@@ -119,6 +298,253 @@ mod tests {
         assert_eq!(0 as usize, hashmap_user_store.get_user_count());
     }
 
+    #[tokio::test]
+    async fn test_update_user() {
+        let mut hashmap_user_store = HashmapUserStore::new();
+        let user = User::new(
+            Email::parse("lads@tst.com".to_string()).unwrap(),
+            Password::parse("Lads123!".to_string()).unwrap(),
+            false,
+        );
+        let _ = hashmap_user_store.add_user(user.clone()).await;
+
+        let mut changed = user.clone();
+        changed.requires_mfa = true;
+        changed.password = Password::parse("Other123!".to_string()).unwrap();
+        let updated = hashmap_user_store.update_user(changed).await.unwrap();
+        assert!(updated.requires_mfa);
+        assert_eq!(user.password, updated.password);
+
+        let missing = User::new(
+            Email::parse("nobody@tst.com".to_string()).unwrap(),
+            Password::parse("Lads123!".to_string()).unwrap(),
+            true,
+        );
+        assert_eq!(
+            Err(UserStoreError::UserNotFound),
+            hashmap_user_store.update_user(missing).await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_users_after_email() {
+        let mut hashmap_user_store = HashmapUserStore::new();
+        for address in ["a@tst.com", "b@tst.com", "c@tst.com"] {
+            let user = User::new(
+                Email::parse(address.to_string()).unwrap(),
+                Password::parse("Lads123!".to_string()).unwrap(),
+                false,
+            );
+            let _ = hashmap_user_store.add_user(user).await;
+        }
+
+        let first = hashmap_user_store.list_users(None, 2).await.unwrap();
+        assert_eq!(2, first.len());
+
+        // Removing a listed user does not shift the next page
+        let _ = hashmap_user_store.delete_user(first[0].email.clone()).await;
+        let rest = hashmap_user_store
+            .list_users(Some(first[1].email.clone()), 2)
+            .await
+            .unwrap();
+        assert_eq!(1, rest.len());
+        assert_eq!("c@tst.com", rest[0].email.as_ref());
+    }
+
+    #[tokio::test]
+    async fn test_update_password() {
+        let mut hashmap_user_store = HashmapUserStore::new();
+        let email = Email::parse("lads@tst.com".to_string()).unwrap();
+        let user = User::new(
+            email.clone(),
+            Password::parse("Lads123!".to_string()).unwrap(),
+            false,
+        );
+        let _ = hashmap_user_store.add_user(user).await;
+
+        let new_password = Password::parse("Other123!".to_string()).unwrap();
+        assert_eq!(
+            Ok(()),
+            hashmap_user_store
+                .update_password(email.clone(), new_password.clone())
+                .await
+        );
+        assert!(hashmap_user_store
+            .validate_user(email, new_password)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_replace_password_hash_only_over_current_hash() {
+        let mut hashmap_user_store = HashmapUserStore::new();
+        let email = Email::parse("lads@tst.com".to_string()).unwrap();
+        let current = Password::parse("Lads123!".to_string()).unwrap();
+        let user = User::new(email.clone(), current.clone(), false);
+        let _ = hashmap_user_store.add_user(user).await;
+
+        let stale = Password::parse("Stale123!".to_string()).unwrap();
+        let new_password = Password::parse("Other123!".to_string()).unwrap();
+        assert_eq!(
+            Ok(false),
+            hashmap_user_store
+                .replace_password_hash(email.clone(), stale, new_password.clone())
+                .await
+        );
+        assert_eq!(
+            Ok(true),
+            hashmap_user_store
+                .replace_password_hash(email.clone(), current, new_password.clone())
+                .await
+        );
+        assert!(hashmap_user_store
+            .validate_user(email, new_password)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_change_email() {
+        let mut hashmap_user_store = HashmapUserStore::new();
+        let old_email = Email::parse("lads@tst.com".to_string()).unwrap();
+        let new_email = Email::parse("new@tst.com".to_string()).unwrap();
+        let taken_email = Email::parse("taken@tst.com".to_string()).unwrap();
+        let password = Password::parse("Lads123!".to_string()).unwrap();
+        let _ = hashmap_user_store
+            .add_user(User::new(old_email.clone(), password.clone(), false))
+            .await;
+        let _ = hashmap_user_store
+            .add_user(User::new(taken_email.clone(), password, false))
+            .await;
+
+        assert_eq!(
+            Err(UserStoreError::UserAlreadyExists),
+            hashmap_user_store
+                .change_email(old_email.clone(), taken_email)
+                .await
+        );
+
+        let user = hashmap_user_store
+            .change_email(old_email.clone(), new_email.clone())
+            .await
+            .unwrap();
+        assert_eq!(user.email, new_email);
+        assert_eq!(
+            Err(UserStoreError::UserNotFound),
+            hashmap_user_store.get_user(old_email).await
+        );
+        assert!(hashmap_user_store.get_user(new_email).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_lockout_round_trip() {
+        let mut hashmap_user_store = HashmapUserStore::new();
+        let email = Email::parse("lads@tst.com".to_string()).unwrap();
+        assert_eq!(
+            Err(UserStoreError::UserNotFound),
+            hashmap_user_store.get_lockout(email.clone()).await
+        );
+
+        let user = User::new(
+            email.clone(),
+            Password::parse("Lads123!".to_string()).unwrap(),
+            false,
+        );
+        let _ = hashmap_user_store.add_user(user).await;
+        assert_eq!(
+            Ok(LoginLockout::default()),
+            hashmap_user_store.get_lockout(email.clone()).await
+        );
+
+        let lockout = LoginLockout {
+            failed_attempts: 2,
+            lockouts: 1,
+            locked_until: None,
+        };
+        assert_eq!(
+            Ok(()),
+            hashmap_user_store
+                .set_lockout(email.clone(), lockout.clone())
+                .await
+        );
+        assert_eq!(Ok(lockout), hashmap_user_store.get_lockout(email).await);
+    }
+
+    #[tokio::test]
+    async fn test_set_status() {
+        let mut hashmap_user_store = HashmapUserStore::new();
+        let email = Email::parse("lads@tst.com".to_string()).unwrap();
+        let user = User::new(
+            email.clone(),
+            Password::parse("Lads123!".to_string()).unwrap(),
+            false,
+        );
+        let _ = hashmap_user_store.add_user(user).await;
+
+        let user = hashmap_user_store
+            .set_status(
+                email.clone(),
+                AccountStatus::Disabled,
+                Some("fraud review".to_string()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(user.status, AccountStatus::Disabled);
+        assert_eq!(
+            hashmap_user_store
+                .get_user(email.clone())
+                .await
+                .unwrap()
+                .status,
+            AccountStatus::Disabled
+        );
+        assert_eq!(
+            hashmap_user_store.status_reason(&email),
+            Some("fraud review")
+        );
+
+        hashmap_user_store
+            .set_status(email.clone(), AccountStatus::Active, None)
+            .await
+            .unwrap();
+        assert_eq!(hashmap_user_store.status_reason(&email), None);
+    }
+
+    #[tokio::test]
+    async fn test_import_users_rejects_unsupported_hashes() {
+        let mut hashmap_user_store = HashmapUserStore::new();
+        let imported = |email: &str, hash: &str| {
+            User::new(
+                Email::parse(email.to_string()).unwrap(),
+                Password::from_hash(hash.to_string()),
+                false,
+            )
+        };
+        let bcrypt_hash = bcrypt::hash("Lads123!", 4).unwrap();
+
+        let results = hashmap_user_store
+            .import_users(
+                vec![
+                    imported("lads@tst.com", &bcrypt_hash),
+                    imported("plain@tst.com", "Lads123!"),
+                    imported("costly@tst.com", &bcrypt_hash.replacen("$04$", "$31$", 1)),
+                ],
+                false,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            results,
+            vec![
+                Ok(()),
+                Err(UserStoreError::UnsupportedPasswordHash),
+                Err(UserStoreError::UnsupportedPasswordHash),
+            ]
+        );
+        assert_eq!(1, hashmap_user_store.get_user_count());
+    }
+
     #[tokio::test]
     async fn test_validate_user() {
         let mut hashmap_user_store = HashmapUserStore::new();
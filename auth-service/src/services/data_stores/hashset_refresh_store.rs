@@ -1,9 +1,9 @@
 use async_trait::async_trait;
 use chrono::{DateTime, Duration, Utc};
-use std::collections::{HashMap, HashSet};
+use std::collections::{hash_map::Entry, HashMap, HashSet};
 use uuid::Uuid;
 
-use crate::domain::{hash_refresh, RefreshError, RefreshRecord, RefreshStore};
+use crate::domain::{hash_refresh, RefreshError, RefreshRecord, RefreshStore, SessionSummary};
 
 #[derive(Default)]
 pub struct HashsetRefreshStore {
@@ -23,6 +23,18 @@ impl RefreshStore for HashsetRefreshStore {
         Ok(())
     }
 
+    async fn find_record(
+        &self,
+        presented_plain: &str,
+        hash_key: &[u8; 32],
+    ) -> Result<RefreshRecord, RefreshError> {
+        let hash = hash_refresh(hash_key, presented_plain).await;
+        self.by_hash
+            .get(&hash)
+            .cloned()
+            .ok_or(RefreshError::NotFoundOrExpired)
+    }
+
     async fn rotate(
         &mut self,
         presented_plain: &str,
@@ -91,4 +103,124 @@ impl RefreshStore for HashsetRefreshStore {
     async fn is_session_revoked(&self, session_id: Uuid) -> bool {
         self.revoked_sessions.contains(&session_id)
     }
+
+    async fn user_sessions(
+        &self,
+        user_id: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Uuid>, RefreshError> {
+        let sessions: HashSet<Uuid> = self
+            .by_hash
+            .values()
+            .filter(|r| {
+                r.user_id == user_id
+                    && r.revoked_at.is_none()
+                    && r.expires_at > now
+                    && !self.revoked_sessions.contains(&r.session_id)
+            })
+            .map(|r| r.session_id)
+            .collect();
+        Ok(sessions.into_iter().collect())
+    }
+
+    async fn session_history(&self, user_id: &str) -> Result<Vec<SessionSummary>, RefreshError> {
+        let mut sessions: HashMap<Uuid, SessionSummary> = HashMap::new();
+        for r in self.by_hash.values().filter(|r| r.user_id == user_id) {
+            match sessions.entry(r.session_id) {
+                Entry::Vacant(entry) => {
+                    entry.insert(SessionSummary {
+                        session_id: r.session_id,
+                        created_at: r.created_at,
+                        last_refreshed_at: r.parent_hash.map(|_| r.created_at),
+                        expires_at: r.expires_at,
+                        revoked_at: r.revoked_at,
+                    });
+                }
+                Entry::Occupied(mut entry) => {
+                    let summary = entry.get_mut();
+                    summary.created_at = summary.created_at.min(r.created_at);
+                    if r.parent_hash.is_some() {
+                        summary.last_refreshed_at =
+                            summary.last_refreshed_at.max(Some(r.created_at));
+                    }
+                    summary.expires_at = summary.expires_at.max(r.expires_at);
+                    summary.revoked_at = match (summary.revoked_at, r.revoked_at) {
+                        (Some(a), Some(b)) => Some(a.min(b)),
+                        (a, b) => a.or(b),
+                    };
+                }
+            }
+        }
+        let mut sessions: Vec<SessionSummary> = sessions.into_values().collect();
+        sessions.sort_by_key(|s| s.created_at);
+        Ok(sessions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: [u8; 32] = [7; 32];
+
+    async fn record(user_id: &str, plain: &str, now: DateTime<Utc>) -> RefreshRecord {
+        RefreshRecord {
+            token_hash: hash_refresh(&KEY, plain).await,
+            user_id: user_id.to_owned(),
+            session_id: Uuid::new_v4(),
+            created_at: now,
+            expires_at: now + Duration::days(1),
+            parent_hash: None,
+            replaced_by_hash: None,
+            used_at: None,
+            revoked_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_session_history_keeps_revoked_sessions() {
+        let mut store = HashsetRefreshStore::default();
+        let start = Utc::now() - Duration::hours(2);
+        let first = record("a@example.com", "first", start).await;
+        let second = record("a@example.com", "second", start + Duration::hours(1)).await;
+        let other = record("b@example.com", "other", start).await;
+        let (first_id, second_id) = (first.session_id, second.session_id);
+        for r in [first, second, other] {
+            store.insert_initial(r).await.unwrap();
+        }
+
+        let refreshed_at = start + Duration::minutes(30);
+        store
+            .rotate("first", "first-2", refreshed_at, Duration::days(1), &KEY)
+            .await
+            .unwrap();
+        let revoked_at = start + Duration::minutes(90);
+        store.revoke_session(second_id, revoked_at).await;
+
+        let history = store.session_history("a@example.com").await.unwrap();
+        assert_eq!(
+            history,
+            vec![
+                SessionSummary {
+                    session_id: first_id,
+                    created_at: start,
+                    last_refreshed_at: Some(refreshed_at),
+                    expires_at: refreshed_at + Duration::days(1),
+                    revoked_at: None,
+                },
+                SessionSummary {
+                    session_id: second_id,
+                    created_at: start + Duration::hours(1),
+                    last_refreshed_at: None,
+                    expires_at: start + Duration::hours(1) + Duration::days(1),
+                    revoked_at: Some(revoked_at),
+                },
+            ]
+        );
+        assert!(store
+            .session_history("c@example.com")
+            .await
+            .unwrap()
+            .is_empty());
+    }
 }
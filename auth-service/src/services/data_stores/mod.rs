@@ -1,17 +1,33 @@
+pub mod hashmap_invitation_store;
+pub mod hashmap_one_time_token_store;
+pub mod hashmap_terms_store;
+pub mod hashmap_trusted_device_store;
 pub mod hashmap_two_fa_code_store;
 pub mod hashmap_user_store;
 pub mod hashset_banned_token_store;
 pub mod hashset_refresh_store;
 pub mod mock_email_client;
+pub mod redis_one_time_token_store;
 pub mod redis_refresh_store;
 pub mod redis_service;
+pub mod redis_trusted_device_store;
+pub mod sql_invitation_store;
+pub mod sql_terms_store;
 pub mod sql_users_store;
 
+pub use hashmap_invitation_store::*;
+pub use hashmap_one_time_token_store::*;
+pub use hashmap_terms_store::*;
+pub use hashmap_trusted_device_store::*;
 pub use hashmap_two_fa_code_store::*;
 pub use hashmap_user_store::*;
 pub use hashset_banned_token_store::*;
 pub use hashset_refresh_store::*;
 pub use mock_email_client::*;
+pub use redis_one_time_token_store::*;
 pub use redis_refresh_store::*;
 pub use redis_service::*;
+pub use redis_trusted_device_store::*;
+pub use sql_invitation_store::*;
+pub use sql_terms_store::*;
 pub use sql_users_store::*;
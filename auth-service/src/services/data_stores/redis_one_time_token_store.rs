@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+use crate::{
+    domain::{OneTimeTokenStore, OneTimeTokenStoreError, TokenPurpose},
+    services::RedisService,
+};
+
+pub struct RedisOneTimeTokenStore {
+    redis_service: Arc<RedisService>,
+}
+
+impl RedisOneTimeTokenStore {
+    pub fn new(redis_service: Arc<RedisService>) -> Self {
+        Self { redis_service }
+    }
+
+    fn redis_key(purpose: TokenPurpose, token_hash: &[u8; 32]) -> String {
+        format!(
+            "one_time_token:{}:{}",
+            purpose.as_str(),
+            blake3::Hash::from(*token_hash).to_hex()
+        )
+    }
+}
+
+#[async_trait::async_trait]
+impl OneTimeTokenStore for RedisOneTimeTokenStore {
+    async fn add_token(
+        &mut self,
+        purpose: TokenPurpose,
+        token_hash: [u8; 32],
+        subject: String,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), OneTimeTokenStoreError> {
+        let ttl_seconds = (expires_at - Utc::now()).num_seconds().max(1) as usize;
+
+        self.redis_service
+            .set_key_value(
+                &Self::redis_key(purpose, &token_hash),
+                &subject,
+                ttl_seconds,
+            )
+            .await
+            .map(|_| ())
+            .map_err(|_| OneTimeTokenStoreError::UnexpectedError)
+    }
+
+    async fn peek_token(
+        &self,
+        purpose: TokenPurpose,
+        token_hash: &[u8; 32],
+        _now: DateTime<Utc>,
+    ) -> Result<String, OneTimeTokenStoreError> {
+        self.redis_service
+            .get(&Self::redis_key(purpose, token_hash))
+            .await
+            .map_err(|_| OneTimeTokenStoreError::UnexpectedError)?
+            .ok_or(OneTimeTokenStoreError::TokenNotFound)
+    }
+
+    async fn take_token(
+        &mut self,
+        purpose: TokenPurpose,
+        token_hash: &[u8; 32],
+        _now: DateTime<Utc>,
+    ) -> Result<String, OneTimeTokenStoreError> {
+        // GETDEL makes redemption atomic; expiry is left to the key TTL
+        self.redis_service
+            .get_del(&Self::redis_key(purpose, token_hash))
+            .await
+            .map_err(|_| OneTimeTokenStoreError::UnexpectedError)?
+            .ok_or(OneTimeTokenStoreError::TokenNotFound)
+    }
+}
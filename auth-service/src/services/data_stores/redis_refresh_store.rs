@@ -4,8 +4,11 @@ use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::{
-    domain::{hash_refresh, AsRedisHashArgs, RefreshError, RefreshRecord, RefreshStore},
+    domain::{
+        hash_refresh, AsRedisHashArgs, RefreshError, RefreshRecord, RefreshStore, SessionSummary,
+    },
     services::RedisService,
+    validation::canonicalize_email,
 };
 
 pub struct RedisRefreshStore {
@@ -76,6 +79,149 @@ impl RedisRefreshStore {
             .map_err(|_| RefreshError::Internal)
     }
 
+    fn user_sessions_key(user_id: &str) -> String {
+        format!("user_sessions:{}", user_id)
+    }
+
+    /// Revoke the sessions indexed under an address saved before emails
+    /// were canonicalized. Nothing looks those indexes up any more, so their
+    /// sessions would survive a password reset or account deletion. Their
+    /// history moves to the canonical address. Returns how many sessions
+    /// were revoked; running it again finds nothing left to do.
+    pub async fn revoke_legacy_sessions(&self, now: DateTime<Utc>) -> Result<usize, RefreshError> {
+        let keys = self
+            .redis_service
+            .scan_keys(&Self::user_sessions_key("*"))
+            .await
+            .map_err(|_| RefreshError::Internal)?;
+
+        let mut revoked = 0;
+        for key in keys {
+            let user_id = key.trim_start_matches(&Self::user_sessions_key(""));
+            let Some(canonical) = canonicalize_email(user_id).filter(|c| c != user_id) else {
+                continue;
+            };
+            let members = self
+                .redis_service
+                .set_members(&key)
+                .await
+                .map_err(|_| RefreshError::Internal)?;
+            for member in members {
+                if let Ok(session_id) = Uuid::parse_str(&member) {
+                    self.mark_session_revoked(session_id, now).await?;
+                    revoked += 1;
+                }
+            }
+
+            let history_key = Self::session_history_key(user_id);
+            let history = self
+                .redis_service
+                .set_members(&history_key)
+                .await
+                .map_err(|_| RefreshError::Internal)?;
+            for member in history {
+                self.redis_service
+                    .add_to_set(&Self::session_history_key(&canonical), &member, None)
+                    .await
+                    .map_err(|_| RefreshError::Internal)?;
+            }
+            for key in [key.as_str(), history_key.as_str()] {
+                self.redis_service
+                    .delete_key(key)
+                    .await
+                    .map_err(|_| RefreshError::Internal)?;
+            }
+        }
+        Ok(revoked)
+    }
+
+    /// Sessions kept for `session_history`; unlike `user_sessions_key` this
+    /// index is not pruned when a session is revoked.
+    fn session_history_key(user_id: &str) -> String {
+        format!("user_session_history:{}", user_id)
+    }
+
+    fn session_summary_key(session_id: Uuid) -> String {
+        format!("session:{}", session_id)
+    }
+
+    /// Index the record's session under its user and update the session's
+    /// summary; both live as long as the user's newest refresh token.
+    async fn index_session(
+        &self,
+        record: &RefreshRecord,
+        ttl_seconds: usize,
+    ) -> Result<(), RefreshError> {
+        let session_id = record.session_id.to_string();
+        for key in [
+            Self::user_sessions_key(&record.user_id),
+            Self::session_history_key(&record.user_id),
+        ] {
+            self.redis_service
+                .add_to_set(&key, &session_id, Some(ttl_seconds))
+                .await
+                .map_err(|_| RefreshError::Internal)?;
+        }
+
+        let issued_at = record.created_at.timestamp().to_string();
+        let mut fields = vec![(
+            "expires_at".to_owned(),
+            record.expires_at.timestamp().to_string(),
+        )];
+        if record.parent_hash.is_some() {
+            fields.push(("last_refreshed_at".to_owned(), issued_at));
+        } else {
+            fields.push(("created_at".to_owned(), issued_at));
+        }
+        self.redis_service
+            .set_hash_multiple(
+                &Self::session_summary_key(record.session_id),
+                &fields,
+                Some(ttl_seconds),
+            )
+            .await
+            .map_err(|_| RefreshError::Internal)
+    }
+
+    /// The summary of `session_id`, or `None` once its tokens have expired.
+    async fn session_summary(
+        &self,
+        session_id: Uuid,
+    ) -> Result<Option<SessionSummary>, RefreshError> {
+        let fields = self
+            .redis_service
+            .get_hash_all(&Self::session_summary_key(session_id))
+            .await
+            .map_err(|_| RefreshError::Internal)?;
+        let timestamp = |name: &str| {
+            fields
+                .iter()
+                .find(|(key, _)| key == name)
+                .and_then(|(_, value)| value.parse::<i64>().ok())
+                .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        };
+        let (Some(created_at), Some(expires_at)) =
+            (timestamp("created_at"), timestamp("expires_at"))
+        else {
+            return Ok(None);
+        };
+        let revoked_at = self
+            .redis_service
+            .get(&format!("revoked_session:{}", session_id))
+            .await
+            .map_err(|_| RefreshError::Internal)?
+            .and_then(|value| value.parse::<i64>().ok())
+            .and_then(|secs| DateTime::from_timestamp(secs, 0));
+
+        Ok(Some(SessionSummary {
+            session_id,
+            created_at,
+            last_refreshed_at: timestamp("last_refreshed_at"),
+            expires_at,
+            revoked_at,
+        }))
+    }
+
     /// Check if a session is revoked by looking up in Redis
     async fn is_session_revoked_internal(&self, session_id: Uuid) -> Result<bool, RefreshError> {
         let revoked_key = format!("revoked_session:{}", session_id);
@@ -85,12 +231,16 @@ impl RedisRefreshStore {
             .map_err(|_| RefreshError::Internal)
     }
 
-    /// Mark a session as revoked in Redis
-    async fn mark_session_revoked(&self, session_id: Uuid) -> Result<(), RefreshError> {
+    /// Mark a session as revoked in Redis, remembering when
+    async fn mark_session_revoked(
+        &self,
+        session_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<(), RefreshError> {
         let revoked_key = format!("revoked_session:{}", session_id);
         // Store with 30 days TTL
         self.redis_service
-            .set_key_value(&revoked_key, "1", 86400 * 30)
+            .set_key_value(&revoked_key, &now.timestamp().to_string(), 86400 * 30)
             .await
             .map_err(|_| RefreshError::Internal)?;
         Ok(())
@@ -117,7 +267,19 @@ impl RefreshStore for RedisRefreshStore {
         let ttl_seconds = (record.expires_at - now).num_seconds() as usize;
 
         // Store the record as a Redis hash
-        self.store_record(&record, Some(ttl_seconds)).await
+        self.store_record(&record, Some(ttl_seconds)).await?;
+        self.index_session(&record, ttl_seconds).await
+    }
+
+    async fn find_record(
+        &self,
+        presented_plain: &str,
+        hash_key: &[u8; 32],
+    ) -> Result<RefreshRecord, RefreshError> {
+        let hash = hash_refresh(hash_key, presented_plain).await;
+        self.get_record_by_hash(&hash)
+            .await?
+            .ok_or(RefreshError::NotFoundOrExpired)
     }
 
     async fn rotate(
@@ -183,6 +345,7 @@ impl RefreshStore for RedisRefreshStore {
         let new_ttl_seconds = ttl.num_seconds() as usize;
         self.store_record(&new_record, Some(new_ttl_seconds))
             .await?;
+        self.index_session(&new_record, new_ttl_seconds).await?;
 
         Ok((old, new_record))
     }
@@ -191,9 +354,9 @@ impl RefreshStore for RedisRefreshStore {
         let _ = self.revoke_session_internal(session_id, now).await;
     }
 
-    async fn revoke_session_internal(&mut self, session_id: Uuid, _now: DateTime<Utc>) {
+    async fn revoke_session_internal(&mut self, session_id: Uuid, now: DateTime<Utc>) {
         // Mark the session as revoked in Redis
-        let _ = self.mark_session_revoked(session_id).await;
+        let _ = self.mark_session_revoked(session_id, now).await;
     }
 
     async fn is_session_revoked(&self, session_id: Uuid) -> bool {
@@ -201,4 +364,56 @@ impl RefreshStore for RedisRefreshStore {
             .await
             .unwrap_or(false)
     }
+
+    async fn user_sessions(
+        &self,
+        user_id: &str,
+        _now: DateTime<Utc>,
+    ) -> Result<Vec<Uuid>, RefreshError> {
+        let key = Self::user_sessions_key(user_id);
+        let members = self
+            .redis_service
+            .set_members(&key)
+            .await
+            .map_err(|_| RefreshError::Internal)?;
+
+        let mut sessions = Vec::with_capacity(members.len());
+        for member in members {
+            let Ok(session_id) = Uuid::parse_str(&member) else {
+                continue;
+            };
+            if self.is_session_revoked_internal(session_id).await? {
+                // Revoked sessions never come back; drop them from the index
+                let _ = self.redis_service.remove_from_set(&key, &member).await;
+            } else {
+                sessions.push(session_id);
+            }
+        }
+        Ok(sessions)
+    }
+
+    async fn session_history(&self, user_id: &str) -> Result<Vec<SessionSummary>, RefreshError> {
+        let key = Self::session_history_key(user_id);
+        let members = self
+            .redis_service
+            .set_members(&key)
+            .await
+            .map_err(|_| RefreshError::Internal)?;
+
+        let mut sessions = Vec::with_capacity(members.len());
+        for member in members {
+            let Ok(session_id) = Uuid::parse_str(&member) else {
+                continue;
+            };
+            match self.session_summary(session_id).await? {
+                Some(summary) => sessions.push(summary),
+                // The session's tokens have expired
+                None => {
+                    let _ = self.redis_service.remove_from_set(&key, &member).await;
+                }
+            }
+        }
+        sessions.sort_by_key(|s| s.created_at);
+        Ok(sessions)
+    }
 }
@@ -80,6 +80,12 @@ impl RedisService {
         conn.get(key).await.map_err(crud)
     }
 
+    /// Read and delete a key in one step.
+    pub async fn get_del(&self, key: &str) -> Result<Option<String>, RedisServiceErr> {
+        let mut conn = self.get_connection().await?;
+        conn.get_del(key).await.map_err(crud)
+    }
+
     pub async fn set_hash_multiple(
         &self,
         key: &str,
@@ -111,9 +117,54 @@ impl RedisService {
 
     // hash_exists removed; use exists() for key presence checks.
 
+    pub async fn add_to_set(
+        &self,
+        key: &str,
+        member: &str,
+        ttl: Option<usize>,
+    ) -> Result<(), RedisServiceErr> {
+        let mut conn = self.get_connection().await?;
+
+        conn.sadd::<_, _, ()>(key, member).await.map_err(crud)?;
+
+        if let Some(ttl_seconds) = ttl {
+            let ttl_seconds: Seconds = if ttl_seconds == 0 {
+                1
+            } else {
+                ttl_seconds as Seconds
+            };
+            conn.expire::<_, ()>(key, ttl_seconds).await.map_err(crud)?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn set_members(&self, key: &str) -> Result<Vec<String>, RedisServiceErr> {
+        let mut conn = self.get_connection().await?;
+        conn.smembers(key).await.map_err(crud)
+    }
+
+    pub async fn remove_from_set(&self, key: &str, member: &str) -> Result<bool, RedisServiceErr> {
+        let mut conn = self.get_connection().await?;
+        let removed: i32 = conn.srem(key, member).await.map_err(crud)?;
+        Ok(removed > 0)
+    }
+
     pub async fn delete_key(&self, key: &str) -> Result<bool, RedisServiceErr> {
         let mut conn = self.get_connection().await?;
         let deleted: i32 = conn.del(key).await.map_err(crud)?;
         Ok(deleted > 0)
     }
+
+    /// Every key matching the glob `pattern`, gathered with SCAN so the
+    /// server is not blocked.
+    pub async fn scan_keys(&self, pattern: &str) -> Result<Vec<String>, RedisServiceErr> {
+        let mut conn = self.get_connection().await?;
+        let mut iter = conn.scan_match::<_, String>(pattern).await.map_err(crud)?;
+        let mut keys = Vec::new();
+        while let Some(key) = iter.next_item().await {
+            keys.push(key);
+        }
+        Ok(keys)
+    }
 }
@@ -0,0 +1,179 @@
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    domain::{AsRedisHashArgs, TrustedDevice, TrustedDeviceStore, TrustedDeviceStoreError},
+    services::RedisService,
+    validation::canonicalize_email,
+};
+
+pub struct RedisTrustedDeviceStore {
+    redis_service: Arc<RedisService>,
+}
+
+impl RedisTrustedDeviceStore {
+    pub fn new(redis_service: Arc<RedisService>) -> Self {
+        Self { redis_service }
+    }
+
+    /// Revoke the devices trusted by an address saved before emails were
+    /// canonicalized. Login no longer matches them to their user, and
+    /// `revoke_all` would not find them. Returns how many were revoked;
+    /// running it again finds nothing left to do.
+    pub async fn revoke_legacy_devices(&mut self) -> Result<usize, TrustedDeviceStoreError> {
+        let keys = self
+            .redis_service
+            .scan_keys(&TrustedDevice::user_set_key("*"))
+            .await
+            .map_err(|_| TrustedDeviceStoreError::UnexpectedError)?;
+
+        let mut revoked = 0;
+        for key in keys {
+            let user_id = key.trim_start_matches(&TrustedDevice::user_set_key(""));
+            if canonicalize_email(user_id).is_some_and(|canonical| canonical != user_id) {
+                revoked += self
+                    .redis_service
+                    .set_members(&key)
+                    .await
+                    .map_err(|_| TrustedDeviceStoreError::UnexpectedError)?
+                    .len();
+                self.revoke_all(user_id).await?;
+            }
+        }
+        Ok(revoked)
+    }
+
+    /// Load a device record; `None` once Redis has expired it
+    async fn get_record(
+        &self,
+        device_id: Uuid,
+    ) -> Result<Option<TrustedDevice>, TrustedDeviceStoreError> {
+        let fields = self
+            .redis_service
+            .get_hash_all(&TrustedDevice::redis_key(device_id))
+            .await
+            .map_err(|_| TrustedDeviceStoreError::UnexpectedError)?;
+
+        if fields.is_empty() {
+            return Ok(None);
+        }
+
+        TrustedDevice::from_redis_hash(fields)
+            .map(Some)
+            .map_err(|_| TrustedDeviceStoreError::UnexpectedError)
+    }
+}
+
+#[async_trait::async_trait]
+impl TrustedDeviceStore for RedisTrustedDeviceStore {
+    async fn add_device(&mut self, device: TrustedDevice) -> Result<(), TrustedDeviceStoreError> {
+        let ttl_seconds = (device.expires_at - Utc::now()).num_seconds().max(1) as usize;
+
+        self.redis_service
+            .set_hash_multiple(
+                &TrustedDevice::redis_key(device.device_id),
+                &device.as_redis_hash_args(),
+                Some(ttl_seconds),
+            )
+            .await
+            .map_err(|_| TrustedDeviceStoreError::UnexpectedError)?;
+
+        // The per-user index lives as long as the newest device in it
+        self.redis_service
+            .add_to_set(
+                &TrustedDevice::user_set_key(&device.user_id),
+                &device.device_id.to_string(),
+                Some(ttl_seconds),
+            )
+            .await
+            .map_err(|_| TrustedDeviceStoreError::UnexpectedError)
+    }
+
+    async fn get_device(
+        &self,
+        device_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<TrustedDevice, TrustedDeviceStoreError> {
+        match self.get_record(device_id).await? {
+            Some(device) if device.expires_at > now => Ok(device),
+            _ => Err(TrustedDeviceStoreError::DeviceNotFound),
+        }
+    }
+
+    async fn list_devices(
+        &self,
+        user_id: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<TrustedDevice>, TrustedDeviceStoreError> {
+        let set_key = TrustedDevice::user_set_key(user_id);
+        let members = self
+            .redis_service
+            .set_members(&set_key)
+            .await
+            .map_err(|_| TrustedDeviceStoreError::UnexpectedError)?;
+
+        let mut devices = Vec::with_capacity(members.len());
+        for member in members {
+            let Ok(device_id) = Uuid::parse_str(&member) else {
+                continue;
+            };
+            match self.get_record(device_id).await? {
+                Some(device) if device.expires_at > now => devices.push(device),
+                // Drop index entries whose record has already expired
+                _ => {
+                    let _ = self.redis_service.remove_from_set(&set_key, &member).await;
+                }
+            }
+        }
+        devices.sort_by_key(|d| d.created_at);
+        Ok(devices)
+    }
+
+    async fn revoke_device(
+        &mut self,
+        user_id: &str,
+        device_id: Uuid,
+    ) -> Result<(), TrustedDeviceStoreError> {
+        match self.get_record(device_id).await? {
+            Some(device) if device.user_id == user_id => {}
+            _ => return Err(TrustedDeviceStoreError::DeviceNotFound),
+        }
+
+        self.redis_service
+            .delete_key(&TrustedDevice::redis_key(device_id))
+            .await
+            .map_err(|_| TrustedDeviceStoreError::UnexpectedError)?;
+        self.redis_service
+            .remove_from_set(
+                &TrustedDevice::user_set_key(user_id),
+                &device_id.to_string(),
+            )
+            .await
+            .map_err(|_| TrustedDeviceStoreError::UnexpectedError)?;
+        Ok(())
+    }
+
+    async fn revoke_all(&mut self, user_id: &str) -> Result<(), TrustedDeviceStoreError> {
+        let set_key = TrustedDevice::user_set_key(user_id);
+        let members = self
+            .redis_service
+            .set_members(&set_key)
+            .await
+            .map_err(|_| TrustedDeviceStoreError::UnexpectedError)?;
+
+        for member in members {
+            if let Ok(device_id) = Uuid::parse_str(&member) {
+                self.redis_service
+                    .delete_key(&TrustedDevice::redis_key(device_id))
+                    .await
+                    .map_err(|_| TrustedDeviceStoreError::UnexpectedError)?;
+            }
+        }
+        self.redis_service
+            .delete_key(&set_key)
+            .await
+            .map_err(|_| TrustedDeviceStoreError::UnexpectedError)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+use chrono::{DateTime, Utc};
+use welds::connections::any::AnyClient;
+use welds::Client;
+
+use crate::domain::data_stores::{Invitation, InvitationStore, InvitationStoreError};
+use crate::domain::InvitationModel;
+
+pub struct SqlInvitationStore {
+    client: AnyClient,
+}
+
+impl SqlInvitationStore {
+    pub fn new(client: AnyClient) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait::async_trait]
+impl InvitationStore for SqlInvitationStore {
+    async fn add_invitation(&mut self, invitation: Invitation) -> Result<(), InvitationStoreError> {
+        let mut model = InvitationModel::new();
+        model.code_hash = hex::encode(invitation.code_hash);
+        model.email = invitation.email;
+        model.max_uses = i32::try_from(invitation.max_uses)
+            .map_err(|_e| InvitationStoreError::UnexpectedError)?;
+        model.uses =
+            i32::try_from(invitation.uses).map_err(|_e| InvitationStoreError::UnexpectedError)?;
+        model.expires_at = invitation.expires_at.timestamp();
+        model.created_at = invitation.created_at.timestamp();
+        model
+            .save(&self.client)
+            .await
+            .map_err(|_e| InvitationStoreError::UnexpectedError)
+    }
+
+    async fn redeem(
+        &mut self,
+        code_hash: &[u8; 32],
+        email: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), InvitationStoreError> {
+        let code_hash = hex::encode(code_hash);
+        let invitation = InvitationModel::where_col(|i| i.code_hash.equal(code_hash.as_str()))
+            .limit(1)
+            .run(&self.client)
+            .await
+            .map_err(|_e| InvitationStoreError::UnexpectedError)?
+            .pop()
+            .ok_or(InvitationStoreError::InvitationNotFound)?
+            .into_inner();
+        if invitation.expires_at <= now.timestamp() {
+            return Err(InvitationStoreError::Expired);
+        }
+        if invitation
+            .email
+            .as_deref()
+            .is_some_and(|bound| bound != email)
+        {
+            return Err(InvitationStoreError::EmailMismatch);
+        }
+
+        // The use is claimed by the UPDATE itself, so two signups racing for
+        // the last use cannot both get it.
+        let claimed = self
+            .client
+            .execute(
+                "UPDATE invitations SET uses = uses + 1 WHERE code_hash = $1 AND uses < max_uses",
+                &[&code_hash],
+            )
+            .await
+            .map_err(|_e| InvitationStoreError::UnexpectedError)?;
+        match claimed.rows_affected() {
+            0 => Err(InvitationStoreError::Exhausted),
+            _ => Ok(()),
+        }
+    }
+
+    async fn release(&mut self, code_hash: &[u8; 32]) -> Result<(), InvitationStoreError> {
+        self.client
+            .execute(
+                "UPDATE invitations SET uses = uses - 1 WHERE code_hash = $1 AND uses > 0",
+                &[&hex::encode(code_hash)],
+            )
+            .await
+            .map_err(|_e| InvitationStoreError::UnexpectedError)?;
+        Ok(())
+    }
+}
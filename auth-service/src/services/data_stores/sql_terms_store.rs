@@ -0,0 +1,106 @@
+use chrono::DateTime;
+use welds::connections::any::AnyClient;
+
+use crate::domain::data_stores::{TermsAcceptance, TermsStore, TermsStoreError};
+use crate::domain::{TermsAcceptanceModel, UserModel};
+
+pub struct SqlTermsStore {
+    client: AnyClient,
+}
+
+impl SqlTermsStore {
+    pub fn new(client: AnyClient) -> Self {
+        Self { client }
+    }
+
+    async fn user_id(&self, email: &str) -> Result<i64, TermsStoreError> {
+        UserModel::where_col(|u| u.email.equal(email))
+            .limit(1)
+            .run(&self.client)
+            .await
+            .map_err(|_e| TermsStoreError::UnexpectedError)?
+            .pop()
+            .map(|user_model| user_model.id)
+            .ok_or(TermsStoreError::UserNotFound)
+    }
+}
+
+#[async_trait::async_trait]
+impl TermsStore for SqlTermsStore {
+    async fn record_acceptance(
+        &mut self,
+        email: &str,
+        acceptance: TermsAcceptance,
+    ) -> Result<(), TermsStoreError> {
+        let mut model = TermsAcceptanceModel::new();
+        model.user_id = self.user_id(email).await?;
+        model.terms_version = acceptance.version;
+        model.accepted_at = acceptance.accepted_at.timestamp();
+        model.ip_address = acceptance.ip_address;
+        model
+            .save(&self.client)
+            .await
+            .map_err(|_e| TermsStoreError::UnexpectedError)
+    }
+
+    async fn latest_acceptance(
+        &self,
+        email: &str,
+    ) -> Result<Option<TermsAcceptance>, TermsStoreError> {
+        let user_id = self.user_id(email).await?;
+        let Some(model) = TermsAcceptanceModel::where_col(|t| t.user_id.equal(user_id))
+            .order_by_desc(|t| t.id)
+            .limit(1)
+            .run(&self.client)
+            .await
+            .map_err(|_e| TermsStoreError::UnexpectedError)?
+            .pop()
+        else {
+            return Ok(None);
+        };
+        acceptance(model.into_inner()).map(Some)
+    }
+
+    async fn list_acceptances(&self, email: &str) -> Result<Vec<TermsAcceptance>, TermsStoreError> {
+        let user_id = self.user_id(email).await?;
+        TermsAcceptanceModel::where_col(|t| t.user_id.equal(user_id))
+            .order_by_asc(|t| t.id)
+            .run(&self.client)
+            .await
+            .map_err(|_e| TermsStoreError::UnexpectedError)?
+            .into_iter()
+            .map(|model| acceptance(model.into_inner()))
+            .collect()
+    }
+
+    async fn change_email(
+        &mut self,
+        _old_email: &str,
+        _new_email: &str,
+    ) -> Result<(), TermsStoreError> {
+        // Rows point at `users.id`, which the email change keeps
+        Ok(())
+    }
+
+    async fn remove_for(&mut self, email: &str) -> Result<(), TermsStoreError> {
+        let user_id = match self.user_id(email).await {
+            Ok(user_id) => user_id,
+            Err(TermsStoreError::UserNotFound) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        TermsAcceptanceModel::where_col(|t| t.user_id.equal(user_id))
+            .delete(&self.client)
+            .await
+            .map(|_| ())
+            .map_err(|_e| TermsStoreError::UnexpectedError)
+    }
+}
+
+fn acceptance(model: TermsAcceptanceModel) -> Result<TermsAcceptance, TermsStoreError> {
+    Ok(TermsAcceptance {
+        version: model.terms_version,
+        accepted_at: DateTime::from_timestamp(model.accepted_at, 0)
+            .ok_or(TermsStoreError::UnexpectedError)?,
+        ip_address: model.ip_address,
+    })
+}
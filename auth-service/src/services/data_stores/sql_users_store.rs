@@ -1,14 +1,16 @@
 use crate::domain::data_stores::{
     BaseRepository, FindableRepository, RepositoryError, UserStore, UserStoreError,
 };
-use crate::domain::{Email, Password, User, UserModel};
-use argon2::{
-    password_hash::{rand_core::OsRng, SaltString},
-    Algorithm, Argon2, Params, PasswordHash, PasswordHasher, PasswordVerifier, Version,
-};
+use crate::domain::{AccountStatus, Email, LoginLockout, Password, User, UserModel, UserProfile};
+use crate::services::password_hashing;
+use argon2::Params;
 use axum::async_trait;
+use std::collections::HashSet;
 use welds::connections::any::AnyClient;
+use welds::errors::{ConnError, WeldsError};
 use welds::prelude::DbState;
+use welds::Client;
+use welds::TransactStart;
 
 // User-specific criteria for finding users
 #[derive(Clone)]
@@ -17,49 +19,62 @@ pub struct UserFindCriteria {
     pub id: Option<i32>,
 }
 
+/// Whether the database rejected a write for breaking a unique index. The only
+/// unique column on `users` besides the key is `email`. Uses the driver's
+/// error kind, so it holds for every backend sqlx supports.
+fn is_unique_violation(error: &WeldsError) -> bool {
+    match error {
+        WeldsError::Database(ConnError::Sqlx(sqlx::Error::Database(e))) => e.is_unique_violation(),
+        _ => false,
+    }
+}
+
+/// How often `import_users` checks and writes a batch before giving up on
+/// accounts being created alongside it.
+const IMPORT_ATTEMPTS: usize = 3;
+
 // SqlUserStore that implements the generic repository pattern
 pub struct SqlUserStore {
     client: AnyClient,
+    argon2_params: Params,
 }
 
 impl SqlUserStore {
     pub fn new(client: AnyClient) -> Self {
-        Self { client }
+        Self {
+            client,
+            argon2_params: Params::new(15000, 2, 1, None).expect("default argon2 params are valid"),
+        }
+    }
+
+    /// Hash new passwords with `params`. Hashes weaker than this are
+    /// upgraded on the user's next successful login.
+    pub fn with_argon2_params(mut self, params: Params) -> Self {
+        self.argon2_params = params;
+        self
     }
 
     // Helper method to hash passwords
     async fn hash_password(&self, password: &str) -> Result<String, RepositoryError> {
         let password_clone = password.to_owned();
+        let params = self.argon2_params.clone();
         tokio::task::spawn_blocking(move || {
-            let argon2 = Argon2::new(
-                Algorithm::Argon2id,
-                Version::V0x13,
-                Params::new(15000, 2, 1, None).map_err(|_| RepositoryError::UnexpectedError)?,
-            );
-            let salt = SaltString::generate(&mut OsRng);
-            let password_hash = argon2
-                .hash_password(password_clone.as_bytes(), &salt)
-                .map_err(|_| RepositoryError::UnexpectedError)?
-                .to_string();
-            Ok(password_hash)
+            password_hashing::hash_password(&password_clone, params)
+                .map_err(|_| RepositoryError::UnexpectedError)
         })
         .await
         .map_err(|_e| RepositoryError::UnexpectedError)?
     }
 
-    // Helper method to verify passwords
+    /// Verify `password` against `hash`, which may be in any format
+    /// `password_hashing` accepts, on the blocking pool.
     async fn verify_password(&self, password: &str, hash: &str) -> Result<bool, RepositoryError> {
         let password_clone = password.to_owned();
         let hash_clone = hash.to_owned();
 
         tokio::task::spawn_blocking(move || {
-            let parsed_hash =
-                PasswordHash::new(&hash_clone).map_err(|_| RepositoryError::UnexpectedError)?;
-            let argon2 = Argon2::default();
-            match argon2.verify_password(password_clone.as_bytes(), &parsed_hash) {
-                Ok(()) => Ok(true),
-                Err(_) => Ok(false),
-            }
+            password_hashing::verify_password(&password_clone, &hash_clone)
+                .map_err(|_| RepositoryError::UnexpectedError)
         })
         .await
         .map_err(|_e| RepositoryError::UnexpectedError)?
@@ -68,27 +83,66 @@ impl SqlUserStore {
     // Convert domain User to database UserModel
     async fn to_user_model(&self, user: &User) -> Result<DbState<UserModel>, RepositoryError> {
         let hashed_password = self.hash_password(user.password.as_ref()).await?;
+        Ok(self.to_user_model_with_hash(user, hashed_password))
+    }
+
+    // Same as `to_user_model`, for a password that is already hashed
+    fn to_user_model_with_hash(&self, user: &User, password_hash: String) -> DbState<UserModel> {
         let now = chrono::Utc::now().timestamp();
         let mut user_model = UserModel::new();
         user_model.email = user.email.as_ref().to_string();
-        user_model.password_hash = hashed_password;
+        user_model.email_display = Some(user.email.display().to_owned());
+        user_model.password_hash = password_hash;
         user_model.requires_mfa = user.requires_mfa;
+        user_model.email_verified = user.email_verified;
+        user_model.display_name = user.profile.display_name.clone();
+        user_model.locale = user.profile.locale.clone();
+        user_model.timezone = user.profile.timezone.clone();
+        user_model.avatar_url = user.profile.avatar_url.clone();
+        user_model.status = user.status.as_str().to_owned();
+        user_model.suspended_until = user.status.suspended_until().map(|until| until.timestamp());
         user_model.created_at = now;
         user_model.updated_at = now;
 
-        Ok(user_model)
+        user_model
+    }
+
+    async fn insert(
+        &self,
+        mut user_model: DbState<UserModel>,
+    ) -> Result<DbState<UserModel>, RepositoryError> {
+        match user_model.save(&self.client).await {
+            Ok(_) => Ok(user_model),
+            Err(e) if is_unique_violation(&e) => Err(RepositoryError::AlreadyExists),
+            Err(e) => Err(RepositoryError::DatabaseError(e.to_string())),
+        }
     }
 
     // Convert database UserModel to domain User
     fn from_user_model(&self, user_model: UserModel) -> Result<User, RepositoryError> {
-        let email = Email::parse(user_model.email)
+        let email = Email::parse(user_model.email_display.unwrap_or(user_model.email))
             .map_err(|_| RepositoryError::InvalidData("Invalid email in database".to_string()))?;
         let password = Password::from_hash(user_model.password_hash);
+        let suspended_until = user_model
+            .suspended_until
+            .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0));
+        let status =
+            AccountStatus::from_parts(&user_model.status, suspended_until).ok_or_else(|| {
+                RepositoryError::InvalidData("Invalid status in database".to_string())
+            })?;
 
         Ok(User {
             email,
             password,
             requires_mfa: user_model.requires_mfa,
+            email_verified: user_model.email_verified,
+            profile: UserProfile {
+                display_name: user_model.display_name,
+                locale: user_model.locale,
+                timezone: user_model.timezone,
+                avatar_url: user_model.avatar_url,
+            },
+            status,
         })
     }
 }
@@ -99,19 +153,8 @@ impl BaseRepository<DbState<UserModel>, User> for SqlUserStore {
     type Id = i32;
 
     async fn create(&mut self, user: User) -> Result<DbState<UserModel>, RepositoryError> {
-        let mut user_model = self.to_user_model(&user).await?;
-
-        match user_model.save(&self.client).await {
-            Ok(_) => Ok(user_model),
-            Err(e) => {
-                let e_string = e.to_string();
-                if e_string.contains("UNIQUE constraint failed: users.email") {
-                    return Err(RepositoryError::AlreadyExists);
-                }
-
-                Err(RepositoryError::DatabaseError(e_string))
-            }
-        }
+        let user_model = self.to_user_model(&user).await?;
+        self.insert(user_model).await
     }
 
     async fn get_by_id(&self, id: Self::Id) -> Result<DbState<UserModel>, RepositoryError> {
@@ -122,19 +165,28 @@ impl BaseRepository<DbState<UserModel>, User> for SqlUserStore {
     }
 
     async fn update(&mut self, user: User) -> Result<DbState<UserModel>, RepositoryError> {
-        let user_model = self.to_user_model(&user).await?;
+        let criteria = UserFindCriteria {
+            email: Some(user.email.clone()),
+            id: None,
+        };
+        let mut user_model = self.find_by(criteria).await?;
 
-        // TODO: Implement actual database update with welds
-        // Example:
-        // let updated_model = user_model.update(&self.client).await
-        //     .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
-        // self.from_user_model(updated_model)
+        // The domain user carries the stored hash once loaded, so the password
+        // column is deliberately not rewritten here.
+        user_model.requires_mfa = user.requires_mfa;
+        user_model.email_verified = user.email_verified;
+        user_model.display_name = user.profile.display_name;
+        user_model.locale = user.profile.locale;
+        user_model.timezone = user.profile.timezone;
+        user_model.avatar_url = user.profile.avatar_url;
+        user_model.updated_at = chrono::Utc::now().timestamp();
+
+        user_model
+            .save(&self.client)
+            .await
+            .map_err(|e| RepositoryError::DatabaseError(e.to_string()))?;
 
-        println!(
-            "TODO: Implement user update with welds for user: {}",
-            user_model.email
-        );
-        Err(RepositoryError::UnexpectedError)
+        Ok(user_model)
     }
 
     async fn delete(&mut self, id: Self::Id) -> Result<DbState<UserModel>, RepositoryError> {
@@ -237,6 +289,112 @@ impl UserStore for SqlUserStore {
             .map_err(UserStoreError::from)
     }
 
+    async fn import_user(&mut self, user: User) -> Result<(), UserStoreError> {
+        let password_hash = user.password.as_ref().to_owned();
+        if !password_hashing::is_supported_hash(&password_hash) {
+            return Err(UserStoreError::UnsupportedPasswordHash);
+        }
+        let user_model = self.to_user_model_with_hash(&user, password_hash);
+        self.insert(user_model)
+            .await
+            .map(|_| ())
+            .map_err(UserStoreError::from)
+    }
+
+    async fn import_users(
+        &mut self,
+        users: Vec<User>,
+        dry_run: bool,
+    ) -> Result<Vec<Result<(), UserStoreError>>, UserStoreError> {
+        let emails: Vec<String> = users
+            .iter()
+            .map(|user| user.email.as_ref().to_owned())
+            .collect();
+        // An account created between the duplicate check and the inserts
+        // aborts the transaction, so the batch is checked again from scratch
+        for _ in 0..IMPORT_ATTEMPTS {
+            let transaction = self
+                .client
+                .begin()
+                .await
+                .map_err(|_e| UserStoreError::UnexpectedError)?;
+            let mut taken: HashSet<String> = UserModel::where_col(|u| u.email.in_list(&emails))
+                .run(&transaction)
+                .await
+                .map_err(|_e| UserStoreError::UnexpectedError)?
+                .into_iter()
+                .map(|user_model| user_model.into_inner().email)
+                .collect();
+
+            let mut results = Vec::with_capacity(users.len());
+            let mut user_models = Vec::new();
+            for user in &users {
+                let password_hash = user.password.as_ref().to_owned();
+                if !password_hashing::is_supported_hash(&password_hash) {
+                    results.push(Err(UserStoreError::UnsupportedPasswordHash));
+                } else if !taken.insert(user.email.as_ref().to_owned()) {
+                    results.push(Err(UserStoreError::UserAlreadyExists));
+                } else {
+                    user_models.push(self.to_user_model_with_hash(user, password_hash));
+                    results.push(Ok(()));
+                }
+            }
+            if dry_run || user_models.is_empty() {
+                // Dropped without committing, so nothing is written
+                return Ok(results);
+            }
+
+            let mut conflicted = false;
+            for mut user_model in user_models {
+                // Dropping the transaction on error rolls the batch back
+                match user_model.save(&transaction).await {
+                    Ok(_) => {}
+                    Err(e) if is_unique_violation(&e) => {
+                        conflicted = true;
+                        break;
+                    }
+                    Err(_) => return Err(UserStoreError::UnexpectedError),
+                }
+            }
+            if conflicted {
+                continue;
+            }
+            transaction
+                .commit()
+                .await
+                .map_err(|_e| UserStoreError::UnexpectedError)?;
+            return Ok(results);
+        }
+        Err(UserStoreError::UnexpectedError)
+    }
+
+    async fn list_users(
+        &self,
+        after: Option<Email>,
+        limit: usize,
+    ) -> Result<Vec<User>, UserStoreError> {
+        // Paged by email rather than by offset, so users added or removed
+        // while paging do not shift later pages.
+        let query = match after {
+            Some(after) => {
+                UserModel::all().where_manual(|u| u.email, " > ?", (after.as_ref().to_owned(),))
+            }
+            None => UserModel::all(),
+        };
+        query
+            .order_by_asc(|u| u.email)
+            .limit(limit as i64)
+            .run(&self.client)
+            .await
+            .map_err(|_e| UserStoreError::UnexpectedError)?
+            .into_iter()
+            .map(|user_model| {
+                self.from_user_model(user_model.into_inner())
+                    .map_err(UserStoreError::from)
+            })
+            .collect()
+    }
+
     async fn get_user(&self, email: Email) -> Result<User, UserStoreError> {
         let criteria = UserFindCriteria {
             email: Some(email),
@@ -253,6 +411,147 @@ impl UserStore for SqlUserStore {
             .map_err(UserStoreError::from)?)
     }
 
+    async fn update_user(&mut self, user: User) -> Result<User, UserStoreError> {
+        let user_model = self.update(user).await.map_err(UserStoreError::from)?;
+
+        self.from_user_model(user_model.into_inner())
+            .map_err(UserStoreError::from)
+    }
+
+    async fn update_password(
+        &mut self,
+        email: Email,
+        password: Password,
+    ) -> Result<(), UserStoreError> {
+        let criteria = UserFindCriteria {
+            email: Some(email),
+            id: None,
+        };
+        let mut user_model = self.find_by(criteria).await.map_err(UserStoreError::from)?;
+
+        user_model.password_hash = self
+            .hash_password(password.as_ref())
+            .await
+            .map_err(UserStoreError::from)?;
+        user_model.updated_at = chrono::Utc::now().timestamp();
+
+        user_model
+            .save(&self.client)
+            .await
+            .map_err(|_e| UserStoreError::UnexpectedError)
+    }
+
+    async fn change_email(
+        &mut self,
+        email: Email,
+        new_email: Email,
+    ) -> Result<User, UserStoreError> {
+        let taken = UserFindCriteria {
+            email: Some(new_email.clone()),
+            id: None,
+        };
+        if self.find_by(taken).await.is_ok() {
+            return Err(UserStoreError::UserAlreadyExists);
+        }
+
+        let criteria = UserFindCriteria {
+            email: Some(email),
+            id: None,
+        };
+        let mut user_model = self.find_by(criteria).await.map_err(UserStoreError::from)?;
+
+        user_model.email = new_email.as_ref().to_string();
+        user_model.email_display = Some(new_email.display().to_owned());
+        user_model.updated_at = chrono::Utc::now().timestamp();
+
+        // The unique index still guards against a concurrent signup taking the address
+        user_model.save(&self.client).await.map_err(|e| {
+            if is_unique_violation(&e) {
+                UserStoreError::UserAlreadyExists
+            } else {
+                UserStoreError::UnexpectedError
+            }
+        })?;
+
+        self.from_user_model(user_model.into_inner())
+            .map_err(UserStoreError::from)
+    }
+
+    async fn get_lockout(&self, email: Email) -> Result<LoginLockout, UserStoreError> {
+        let criteria = UserFindCriteria {
+            email: Some(email),
+            id: None,
+        };
+        let user_model = self.find_by(criteria).await.map_err(UserStoreError::from)?;
+
+        Ok(LoginLockout {
+            failed_attempts: user_model.failed_login_attempts.max(0) as u32,
+            lockouts: user_model.lockout_count.max(0) as u32,
+            locked_until: user_model
+                .locked_until
+                .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0)),
+        })
+    }
+
+    async fn get_status_reason(&self, email: Email) -> Result<Option<String>, UserStoreError> {
+        let criteria = UserFindCriteria {
+            email: Some(email),
+            id: None,
+        };
+        let user_model = self.find_by(criteria).await.map_err(UserStoreError::from)?;
+        Ok(user_model.status_reason.clone())
+    }
+
+    async fn set_lockout(
+        &mut self,
+        email: Email,
+        lockout: LoginLockout,
+    ) -> Result<(), UserStoreError> {
+        let criteria = UserFindCriteria {
+            email: Some(email),
+            id: None,
+        };
+        let mut user_model = self.find_by(criteria).await.map_err(UserStoreError::from)?;
+
+        user_model.failed_login_attempts = lockout.failed_attempts.min(i32::MAX as u32) as i32;
+        user_model.lockout_count = lockout.lockouts.min(i32::MAX as u32) as i32;
+        // Rounded up so a lock never ends earlier than computed
+        user_model.locked_until = lockout
+            .locked_until
+            .map(|until| until.timestamp() + i64::from(until.timestamp_subsec_nanos() > 0));
+
+        user_model
+            .save(&self.client)
+            .await
+            .map_err(|_e| UserStoreError::UnexpectedError)
+    }
+
+    async fn set_status(
+        &mut self,
+        email: Email,
+        status: AccountStatus,
+        reason: Option<String>,
+    ) -> Result<User, UserStoreError> {
+        let criteria = UserFindCriteria {
+            email: Some(email),
+            id: None,
+        };
+        let mut user_model = self.find_by(criteria).await.map_err(UserStoreError::from)?;
+
+        user_model.status = status.as_str().to_owned();
+        user_model.suspended_until = status.suspended_until().map(|until| until.timestamp());
+        user_model.status_reason = reason;
+        user_model.updated_at = chrono::Utc::now().timestamp();
+
+        user_model
+            .save(&self.client)
+            .await
+            .map_err(|_e| UserStoreError::UnexpectedError)?;
+
+        self.from_user_model(user_model.into_inner())
+            .map_err(UserStoreError::from)
+    }
+
     async fn delete_user(&mut self, email: Email) -> Result<User, UserStoreError> {
         let criteria = UserFindCriteria {
             email: Some(email),
@@ -260,7 +559,26 @@ impl UserStore for SqlUserStore {
         };
 
         let mut user = self.find_by(criteria).await.map_err(UserStoreError::from)?;
-        user.delete(&self.client)
+
+        // Terms acceptances hang off `users.id` and go in the same
+        // transaction, so a failed delete keeps the account's history.
+        let transaction = self
+            .client
+            .begin()
+            .await
+            .map_err(|_e| UserStoreError::UnexpectedError)?;
+        transaction
+            .execute(
+                "DELETE FROM terms_acceptances WHERE user_id = $1",
+                &[&user.id],
+            )
+            .await
+            .map_err(|_e| UserStoreError::UnexpectedError)?;
+        user.delete(&transaction)
+            .await
+            .map_err(|_e| UserStoreError::UnexpectedError)?;
+        transaction
+            .commit()
             .await
             .map_err(|_e| UserStoreError::UnexpectedError)?;
 
@@ -277,14 +595,43 @@ impl UserStore for SqlUserStore {
         let user = self.get_user(email).await?;
 
         // Verify password
-        if self
+        let verified = self
             .verify_password(password.as_ref(), user.password.as_ref())
             .await
-            .map_err(|_| UserStoreError::UnexpectedError)?
-        {
-            Ok(user)
-        } else {
-            Err(UserStoreError::InvalidCredentials)
+            .map_err(|_| UserStoreError::UnexpectedError)?;
+        if !verified {
+            return Err(UserStoreError::InvalidCredentials);
         }
+        Ok(user)
+    }
+
+    fn argon2_params(&self) -> Option<Params> {
+        Some(self.argon2_params.clone())
+    }
+
+    /// Used both for login's rehash and for real password changes, so it
+    /// bumps `updated_at` like every other write to the row.
+    async fn replace_password_hash(
+        &mut self,
+        email: Email,
+        current_hash: Password,
+        new_hash: Password,
+    ) -> Result<bool, UserStoreError> {
+        // The comparison is part of the UPDATE itself, so a password changed
+        // since `current_hash` was read is never overwritten.
+        let replaced = self
+            .client
+            .execute(
+                "UPDATE users SET password_hash = $1, updated_at = $2 WHERE email = $3 AND password_hash = $4",
+                &[
+                    &new_hash.as_ref().to_owned(),
+                    &chrono::Utc::now().timestamp(),
+                    &email.as_ref().to_owned(),
+                    &current_hash.as_ref().to_owned(),
+                ],
+            )
+            .await
+            .map_err(|_e| UserStoreError::UnexpectedError)?;
+        Ok(replaced.rows_affected() > 0)
     }
 }
@@ -0,0 +1,101 @@
+//! Throwaway-mailbox providers refused at signup.
+//!
+//! The list file has one domain per line, as in the widely used
+//! disposable-email-domains lists; blank lines and `#` comments are skipped.
+//! A listed domain also covers its subdomains.
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::validation::canonicalize_domain;
+
+#[derive(Error, Debug)]
+pub enum DisposableDomainListError {
+    #[error("could not read disposable domain list: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid domain on line {0} of the disposable domain list")]
+    InvalidDomain(usize),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DisposableDomainList {
+    domains: HashSet<String>,
+}
+
+impl DisposableDomainList {
+    pub fn insert_domain(&mut self, domain: &str) -> bool {
+        match canonicalize_domain(domain) {
+            Some(domain) => {
+                self.domains.insert(domain);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether `domain`, in canonical form, or any domain above it is listed.
+    pub fn contains_domain(&self, domain: &str) -> bool {
+        let mut rest = domain;
+        loop {
+            if self.domains.contains(rest) {
+                return true;
+            }
+            match rest.split_once('.') {
+                Some((_, parent)) => rest = parent,
+                None => return false,
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.domains.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.domains.is_empty()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, DisposableDomainListError> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+
+    pub fn read_from(reader: impl BufRead) -> Result<Self, DisposableDomainListError> {
+        let mut list = Self::default();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let domain = line.split('#').next().unwrap_or_default().trim();
+            if !domain.is_empty() && !list.insert_domain(domain) {
+                return Err(DisposableDomainListError::InvalidDomain(index + 1));
+            }
+        }
+        Ok(list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_list_and_matches_subdomains() {
+        let file = "# throwaway providers\nMailinator.com\n\n  guerrillamail.com  # and friends\n";
+        let list = DisposableDomainList::read_from(file.as_bytes()).unwrap();
+        assert_eq!(list.len(), 2);
+        assert!(list.contains_domain("mailinator.com"));
+        assert!(list.contains_domain("eu.mailinator.com"));
+        assert!(!list.contains_domain("notmailinator.com"));
+        assert!(!list.contains_domain("com"));
+    }
+
+    #[test]
+    fn test_reports_the_line_of_a_bad_domain() {
+        let file = "mailinator.com\nnot a domain\n";
+        assert!(matches!(
+            DisposableDomainList::read_from(file.as_bytes()),
+            Err(DisposableDomainListError::InvalidDomain(2))
+        ));
+    }
+}
@@ -1,7 +1,14 @@
 pub mod auth;
+pub mod breached_passwords;
+pub mod data_export;
 pub mod data_stores;
+pub mod disposable_domains;
+pub mod password_hashing;
 pub mod token_service;
+pub mod user_transfer;
 
 pub use auth::*;
+pub use breached_passwords::*;
 pub use data_stores::*;
+pub use disposable_domains::*;
 pub use token_service::*;
@@ -0,0 +1,359 @@
+//! Password hash formats the user store understands.
+//!
+//! New hashes are always argon2id. For verification we also accept what
+//! accounts imported from older systems carry:
+//! - PHC strings for argon2 (`$argon2id$...`), PBKDF2 (`$pbkdf2-sha256$i=...`)
+//!   and scrypt (`$scrypt$...`)
+//! - modular-crypt bcrypt (`$2a$`, `$2b$`, `$2x$`, `$2y$`)
+//! - passlib's modular-crypt PBKDF2-SHA256 (`$pbkdf2-sha256$<rounds>$<salt>$<hash>`)
+//!
+//! Anything that is not argon2id at the configured cost reports
+//! `needs_rehash`, so it is replaced on the user's next successful login.
+//!
+//! Imported hashes must also stay within the `MAX_*` cost limits below, so
+//! a crafted hash cannot make every login attempt for its account burn
+//! seconds of CPU or gigabytes of memory.
+use argon2::password_hash::{rand_core::OsRng, PasswordHash, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, PasswordHasher, Version};
+use base64::engine::general_purpose::STANDARD_NO_PAD;
+use base64::Engine;
+use pbkdf2::Pbkdf2;
+use scrypt::Scrypt;
+use sha2::Sha256;
+use thiserror::Error;
+
+/// bcrypt cost factor (log2 of the rounds)
+pub const MAX_BCRYPT_COST: u32 = 14;
+pub const MAX_PBKDF2_ROUNDS: u32 = 2_000_000;
+/// scrypt CPU/memory cost as log2(N); with `r = 8` this is 128 MiB
+pub const MAX_SCRYPT_LOG_N: u8 = 17;
+pub const MAX_SCRYPT_R: u32 = 16;
+pub const MAX_SCRYPT_P: u32 = 16;
+/// argon2 memory cost in KiB (256 MiB)
+pub const MAX_ARGON2_M_COST: u32 = 262_144;
+pub const MAX_ARGON2_T_COST: u32 = 16;
+pub const MAX_ARGON2_P_COST: u32 = 16;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum PasswordHashError {
+    #[error("unsupported password hash format")]
+    UnsupportedFormat,
+    #[error("could not hash password")]
+    HashingFailed,
+}
+
+/// Hash `password` with argon2id v1.3 at `params`.
+pub fn hash_password(password: &str, params: Params) -> Result<String, PasswordHashError> {
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+    let salt = SaltString::generate(&mut OsRng);
+    Ok(argon2
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|_| PasswordHashError::HashingFailed)?
+        .to_string())
+}
+
+/// What a store hashing new passwords at `params` saves for `password`,
+/// computed on the blocking pool so callers can do it before taking any
+/// lock. Stores without params keep passwords as given.
+pub async fn stored_form(
+    password: &str,
+    params: Option<Params>,
+) -> Result<String, PasswordHashError> {
+    let Some(params) = params else {
+        return Ok(password.to_owned());
+    };
+    let password = password.to_owned();
+    tokio::task::spawn_blocking(move || hash_password(&password, params))
+        .await
+        .map_err(|_| PasswordHashError::HashingFailed)?
+}
+
+/// Check `password` against a stored hash in any supported format.
+pub fn verify_password(password: &str, hash: &str) -> Result<bool, PasswordHashError> {
+    match parse(hash)? {
+        StoredHash::Bcrypt { .. } => {
+            bcrypt::verify(password, hash).map_err(|_| PasswordHashError::UnsupportedFormat)
+        }
+        StoredHash::PasslibPbkdf2Sha256 { rounds, salt, hash } => {
+            let mut derived = vec![0u8; hash.len()];
+            pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), &salt, rounds, &mut derived);
+            // blake3::Hash compares in constant time
+            Ok(blake3::hash(&derived) == blake3::hash(&hash))
+        }
+        StoredHash::Phc(parsed) => {
+            let verified = match parsed.algorithm.as_str() {
+                "argon2id" | "argon2i" | "argon2d" => {
+                    // Verification reads the cost from the hash string itself
+                    Argon2::default().verify_password(password.as_bytes(), &parsed)
+                }
+                "pbkdf2-sha256" => Pbkdf2.verify_password(password.as_bytes(), &parsed),
+                "scrypt" => Scrypt.verify_password(password.as_bytes(), &parsed),
+                _ => return Err(PasswordHashError::UnsupportedFormat),
+            };
+            Ok(verified.is_ok())
+        }
+    }
+}
+
+/// Whether `hash` is in a format `verify_password` accepts, with costs
+/// within the `MAX_*` limits.
+pub fn is_supported_hash(hash: &str) -> bool {
+    match parse(hash) {
+        Ok(StoredHash::Bcrypt { cost }) => cost <= MAX_BCRYPT_COST,
+        Ok(StoredHash::PasslibPbkdf2Sha256 { rounds, .. }) => rounds <= MAX_PBKDF2_ROUNDS,
+        Ok(StoredHash::Phc(parsed)) => match parsed.algorithm.as_str() {
+            "argon2id" | "argon2i" | "argon2d" => Params::try_from(&parsed).is_ok_and(|params| {
+                params.m_cost() <= MAX_ARGON2_M_COST
+                    && params.t_cost() <= MAX_ARGON2_T_COST
+                    && params.p_cost() <= MAX_ARGON2_P_COST
+            }),
+            "pbkdf2-sha256" => pbkdf2::Params::try_from(&parsed)
+                .is_ok_and(|params| params.rounds <= MAX_PBKDF2_ROUNDS),
+            "scrypt" => scrypt::Params::try_from(&parsed).is_ok_and(|params| {
+                params.log_n() <= MAX_SCRYPT_LOG_N
+                    && params.r() <= MAX_SCRYPT_R
+                    && params.p() <= MAX_SCRYPT_P
+            }),
+            _ => false,
+        },
+        Err(_) => false,
+    }
+}
+
+/// True unless `hash` is argon2id v1.3 with every cost at least `params`.
+pub fn needs_rehash(hash: &str, params: &Params) -> bool {
+    let Ok(StoredHash::Phc(parsed)) = parse(hash) else {
+        return true;
+    };
+    if parsed.algorithm != Algorithm::Argon2id.ident()
+        || parsed.version != Some(Version::V0x13.into())
+    {
+        return true;
+    }
+    match Params::try_from(&parsed) {
+        Ok(current) => {
+            current.m_cost() < params.m_cost()
+                || current.t_cost() < params.t_cost()
+                || current.p_cost() < params.p_cost()
+        }
+        Err(_) => true,
+    }
+}
+
+enum StoredHash<'a> {
+    Bcrypt {
+        cost: u32,
+    },
+    PasslibPbkdf2Sha256 {
+        rounds: u32,
+        salt: Vec<u8>,
+        hash: Vec<u8>,
+    },
+    Phc(PasswordHash<'a>),
+}
+
+fn parse(hash: &str) -> Result<StoredHash<'_>, PasswordHashError> {
+    if hash.starts_with("$2") {
+        return hash
+            .parse::<bcrypt::HashParts>()
+            .map(|parts| StoredHash::Bcrypt {
+                cost: parts.get_cost(),
+            })
+            .map_err(|_| PasswordHashError::UnsupportedFormat);
+    }
+    if let Some(passlib) = parse_passlib_pbkdf2(hash) {
+        return Ok(passlib);
+    }
+    PasswordHash::new(hash)
+        .map(StoredHash::Phc)
+        .map_err(|_| PasswordHashError::UnsupportedFormat)
+}
+
+/// `$pbkdf2-sha256$<rounds>$<salt>$<hash>`, where salt and hash use passlib's
+/// base64 variant (`.` instead of `+`, no padding).
+fn parse_passlib_pbkdf2(hash: &str) -> Option<StoredHash<'static>> {
+    let rest = hash.strip_prefix("$pbkdf2-sha256$")?;
+    let mut parts = rest.split('$');
+    let rounds = parts.next()?.parse::<u32>().ok().filter(|r| *r > 0)?;
+    let salt = decode_ab64(parts.next()?)?;
+    let hash = decode_ab64(parts.next()?)?;
+    if parts.next().is_some() || hash.is_empty() {
+        return None;
+    }
+    Some(StoredHash::PasslibPbkdf2Sha256 { rounds, salt, hash })
+}
+
+fn decode_ab64(value: &str) -> Option<Vec<u8>> {
+    STANDARD_NO_PAD.decode(value.replace('.', "+")).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSWORD: &str = "Password123!";
+
+    fn argon2(m_cost: u32, t_cost: u32, p_cost: u32) -> String {
+        hash_password(PASSWORD, Params::new(m_cost, t_cost, p_cost, None).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_verifies_argon2_hashes() {
+        let hash = argon2(4096, 1, 1);
+        assert_eq!(verify_password(PASSWORD, &hash), Ok(true));
+        assert_eq!(verify_password("Wrong123!", &hash), Ok(false));
+    }
+
+    #[test]
+    fn test_verifies_bcrypt_hashes() {
+        let hash = bcrypt::hash(PASSWORD, 4).unwrap();
+        assert!(hash.starts_with("$2b$"));
+        assert!(is_supported_hash(&hash));
+        assert_eq!(verify_password(PASSWORD, &hash), Ok(true));
+        assert_eq!(verify_password("Wrong123!", &hash), Ok(false));
+        // Same hash as written by PHP's password_hash
+        let php = hash.replacen("$2b$", "$2y$", 1);
+        assert_eq!(verify_password(PASSWORD, &php), Ok(true));
+    }
+
+    #[test]
+    fn test_verifies_pbkdf2_phc_hashes() {
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = Pbkdf2
+            .hash_password_customized(
+                PASSWORD.as_bytes(),
+                Some(pbkdf2::Algorithm::Pbkdf2Sha256.ident()),
+                None,
+                pbkdf2::Params {
+                    rounds: 1000,
+                    output_length: 32,
+                },
+                &salt,
+            )
+            .unwrap()
+            .to_string();
+        assert!(hash.starts_with("$pbkdf2-sha256$"));
+        assert_eq!(verify_password(PASSWORD, &hash), Ok(true));
+        assert_eq!(verify_password("Wrong123!", &hash), Ok(false));
+    }
+
+    #[test]
+    fn test_verifies_passlib_pbkdf2_hashes() {
+        // "password" with salt b"saltsalt" and 1000 rounds, derived with
+        // Python's hashlib.pbkdf2_hmac and written in passlib's layout
+        let hash = "$pbkdf2-sha256$1000$c2FsdHNhbHQ$E196ZhRPzw.wA84EjzHwJO1cv/MFJdO6C/sxmUeTYqY";
+        assert!(is_supported_hash(hash));
+        assert_eq!(verify_password("password", hash), Ok(true));
+        assert_eq!(verify_password("Password", hash), Ok(false));
+    }
+
+    #[test]
+    fn test_verifies_scrypt_hashes() {
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = Scrypt
+            .hash_password_customized(
+                PASSWORD.as_bytes(),
+                None,
+                None,
+                scrypt::Params::new(4, 8, 1, 32).unwrap(),
+                &salt,
+            )
+            .unwrap()
+            .to_string();
+        assert!(hash.starts_with("$scrypt$"));
+        assert_eq!(verify_password(PASSWORD, &hash), Ok(true));
+        assert_eq!(verify_password("Wrong123!", &hash), Ok(false));
+    }
+
+    #[test]
+    fn test_rejects_unknown_formats() {
+        for hash in [
+            "",
+            "plaintext",
+            "$1$abc$def",
+            "$2b$12$tooshort",
+            "$pbkdf2-sha256$x$salt$hash",
+        ] {
+            assert!(!is_supported_hash(hash), "{hash}");
+            assert_eq!(
+                verify_password(PASSWORD, hash),
+                Err(PasswordHashError::UnsupportedFormat)
+            );
+        }
+    }
+
+    #[test]
+    fn test_rejects_costs_above_the_limits() {
+        let argon2_hash = argon2(4096, 1, 1);
+        assert!(is_supported_hash(&argon2_hash));
+        let heavy_argon2 = argon2_hash.replacen("m=4096,t=1,p=1", "m=4194304,t=1,p=1", 1);
+        assert!(!is_supported_hash(&heavy_argon2));
+        let slow_argon2 = argon2_hash.replacen("m=4096,t=1,p=1", "m=4096,t=100,p=1", 1);
+        assert!(!is_supported_hash(&slow_argon2));
+
+        let bcrypt_hash = bcrypt::hash(PASSWORD, 4).unwrap();
+        assert!(!is_supported_hash(&bcrypt_hash.replacen("$04$", "$31$", 1)));
+
+        let passlib = "$pbkdf2-sha256$1000$c2FsdHNhbHQ$E196ZhRPzw.wA84EjzHwJO1cv/MFJdO6C/sxmUeTYqY";
+        assert!(!is_supported_hash(&passlib.replacen(
+            "$1000$",
+            "$4000000000$",
+            1
+        )));
+
+        let salt = SaltString::generate(&mut OsRng);
+        let pbkdf2_hash = Pbkdf2
+            .hash_password_customized(
+                PASSWORD.as_bytes(),
+                Some(pbkdf2::Algorithm::Pbkdf2Sha256.ident()),
+                None,
+                pbkdf2::Params {
+                    rounds: 1000,
+                    output_length: 32,
+                },
+                &salt,
+            )
+            .unwrap()
+            .to_string();
+        assert!(!is_supported_hash(&pbkdf2_hash.replacen(
+            "i=1000",
+            "i=4000000000",
+            1
+        )));
+
+        let scrypt_hash = Scrypt
+            .hash_password_customized(
+                PASSWORD.as_bytes(),
+                None,
+                None,
+                scrypt::Params::new(4, 8, 1, 32).unwrap(),
+                &salt,
+            )
+            .unwrap()
+            .to_string();
+        assert!(is_supported_hash(&scrypt_hash));
+        assert!(!is_supported_hash(
+            &scrypt_hash.replacen("ln=4", "ln=30", 1)
+        ));
+    }
+
+    #[test]
+    fn test_flags_everything_but_current_argon2id_for_rehash() {
+        let configured = Params::new(8192, 2, 1, None).unwrap();
+
+        assert!(!needs_rehash(&argon2(8192, 2, 1), &configured));
+        assert!(!needs_rehash(&argon2(16384, 2, 2), &configured));
+        assert!(needs_rehash(&argon2(4096, 2, 1), &configured));
+        assert!(needs_rehash(&argon2(8192, 1, 1), &configured));
+
+        let argon2i = Argon2::new(Algorithm::Argon2i, Version::V0x13, configured.clone())
+            .hash_password(PASSWORD.as_bytes(), &SaltString::generate(&mut OsRng))
+            .unwrap()
+            .to_string();
+        assert!(needs_rehash(&argon2i, &configured));
+        assert!(needs_rehash(
+            &bcrypt::hash(PASSWORD, 4).unwrap(),
+            &configured
+        ));
+    }
+}
@@ -6,6 +6,8 @@
 /// - Detection of refresh token reuse (and session revocation on reuse)
 /// - Validation (signature + claims + revocation) of access tokens
 /// - Explicit session revocation (logout)
+/// - Refusing refreshes for accounts that may no longer sign in, when a user
+///   store is attached
 ///
 /// Security model:
 /// 1. Each refresh token rotation produces a new refresh token and marks the
@@ -37,9 +39,11 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+use crate::app_state::UserStoreType;
 use crate::domain::data_stores::jwt_key_store::JwtKeyStore;
 use crate::domain::{
-    hash_refresh, AccessClaims, IssuedTokens, RefreshError, RefreshRecord, RefreshStore,
+    hash_refresh, AccessClaims, Email, IssuedTokens, RefreshError, RefreshRecord, RefreshStore,
+    SessionSummary, UserStoreError,
 };
 
 use crate::utils::config::Config;
@@ -60,6 +64,8 @@ pub struct TokenService {
     keys: Arc<JwtKeyStore>,
     // State that changes: refresh records and revoked sessions
     state: Arc<RwLock<Box<dyn RefreshStore + Send + Sync>>>,
+    // Optional: lets `refresh` check the account status of the session owner
+    users: Option<UserStoreType>,
 }
 
 #[derive(Debug)]
@@ -88,7 +94,19 @@ impl TokenService {
         };
 
         let state = Arc::new(RwLock::new(store));
-        Self { cfg, keys, state }
+        Self {
+            cfg,
+            keys,
+            state,
+            users: None,
+        }
+    }
+
+    /// Attach the user store so `refresh` refuses (and revokes) sessions of
+    /// accounts that are disabled, suspended or gone.
+    pub fn with_user_store(mut self, users: UserStoreType) -> Self {
+        self.users = Some(users);
+        self
     }
 
     // Create a short-lived access JWT for a given user and session.
@@ -203,6 +221,7 @@ impl TokenService {
     /// - `NotFoundOrExpired`: token hash not present or expired
     /// - `ReuseDetected`: reuse attempt (session revoked)
     /// - `Revoked`: session already revoked
+    /// - `AccountInactive`: the owner may not sign in (session revoked)
     /// - `Internal`: underlying store failure
     pub async fn refresh(&self, presented_refresh: &str) -> Result<IssuedTokens, RefreshError> {
         let now = Utc::now();
@@ -218,6 +237,22 @@ impl TokenService {
         let ttl = Duration::seconds(refresh_token_ttl_seconds);
         let next_plain = self.new_refresh_token_plain();
 
+        // Checked before rotating so a refused refresh leaves the token unused
+        let presented = self
+            .state
+            .read()
+            .await
+            .find_record(presented_refresh, &refresh_hash_key)
+            .await?;
+        if !self.is_account_active(&presented.user_id).await? {
+            self.state
+                .write()
+                .await
+                .revoke_session(presented.session_id, Utc::now())
+                .await;
+            return Err(RefreshError::AccountInactive);
+        }
+
         let (user_id, session_id) = {
             let mut st = self.state.write().await;
             let (_old, new_record) = st
@@ -297,4 +332,57 @@ impl TokenService {
         let mut st = self.state.write().await;
         st.revoke_session(session_id, now).await;
     }
+
+    async fn is_account_active(&self, user_id: &str) -> Result<bool, RefreshError> {
+        let Some(users) = &self.users else {
+            return Ok(true);
+        };
+        let Ok(email) = Email::parse(user_id.to_owned()) else {
+            return Ok(false);
+        };
+
+        match users.read().await.get_user(email).await {
+            Ok(user) => Ok(user.status.is_active(Utc::now())),
+            Err(UserStoreError::UserNotFound) => Ok(false),
+            Err(_) => Err(RefreshError::Internal),
+        }
+    }
+
+    /// Active session ids of `user_id`.
+    pub async fn user_sessions(&self, user_id: &str) -> Result<Vec<Uuid>, RefreshError> {
+        let st = self.state.read().await;
+        st.user_sessions(user_id, Utc::now()).await
+    }
+
+    /// Every session of `user_id` still on record, oldest first.
+    pub async fn session_history(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<SessionSummary>, RefreshError> {
+        let st = self.state.read().await;
+        st.session_history(user_id).await
+    }
+
+    /// Revoke every session of `user_id` except `keep` (typically the
+    /// caller's own session). Returns how many sessions were revoked; fails
+    /// if the sessions could not be listed or one of them stays live.
+    pub async fn revoke_user_sessions(
+        &self,
+        user_id: &str,
+        keep: Option<Uuid>,
+    ) -> Result<usize, RefreshError> {
+        let now = Utc::now();
+        let mut st = self.state.write().await;
+        let sessions = st.user_sessions(user_id, now).await?;
+
+        let mut revoked = 0;
+        for session_id in sessions.into_iter().filter(|sid| Some(*sid) != keep) {
+            st.revoke_session(session_id, now).await;
+            if !st.is_session_revoked(session_id).await {
+                return Err(RefreshError::Internal);
+            }
+            revoked += 1;
+        }
+        Ok(revoked)
+    }
 }
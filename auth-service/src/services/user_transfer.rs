@@ -0,0 +1,666 @@
+//! Streaming bulk import and export of users as JSON Lines or CSV.
+//!
+//! Both formats carry the same fields: `email`, `emailDisplay`, `passwordHash`,
+//! `requires2FA`, `emailVerified`, `displayName`, `locale`, `timezone`,
+//! `avatarUrl`, `status` and `suspendedUntil`. Only `email` and `passwordHash`
+//! are required; password hashes are stored as-is and must be in a format
+//! `password_hashing` can verify.
+//!
+//! Imports read one batch at a time and hand it to `UserStore::import_users`.
+//! A row that cannot be imported is written to the rejects stream, in the
+//! input's format, as `line`, `email` and `error`, and the job carries on.
+use std::collections::HashSet;
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::domain::{AccountStatus, Email, Password, User, UserProfile, UserStore, UserStoreError};
+use crate::validation::{
+    is_valid_avatar_url, is_valid_display_name, is_valid_locale, is_valid_timezone,
+};
+
+/// Upper bound for `ImportOptions::batch_size` and export pages, which keeps
+/// each batch's `IN (...)` lookup well inside database parameter limits.
+pub const MAX_BATCH_SIZE: usize = 1000;
+
+#[derive(Error, Debug)]
+pub enum UserTransferError {
+    #[error("could not read or write user records: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not read or write CSV: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("could not encode user record: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("could not load users: {0:?}")]
+    Store(UserStoreError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferFormat {
+    Jsonl,
+    Csv,
+}
+
+impl TransferFormat {
+    /// Guess the format from a file extension (`.jsonl`, `.ndjson`, `.csv`).
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()?.to_str()?.parse().ok()
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            TransferFormat::Jsonl => "jsonl",
+            TransferFormat::Csv => "csv",
+        }
+    }
+}
+
+impl FromStr for TransferFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "jsonl" | "ndjson" => Ok(TransferFormat::Jsonl),
+            "csv" => Ok(TransferFormat::Csv),
+            other => Err(format!("unknown format {:?}, expected jsonl or csv", other)),
+        }
+    }
+}
+
+/// One user as it appears in an import or export file.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct UserRecord {
+    pub email: String,
+    /// The address as the user typed it; must have the same canonical form
+    /// as `email`
+    #[serde(rename = "emailDisplay", default)]
+    pub email_display: Option<String>,
+    #[serde(rename = "passwordHash")]
+    pub password_hash: String,
+    #[serde(rename = "requires2FA", default)]
+    pub requires_mfa: bool,
+    #[serde(rename = "emailVerified", default)]
+    pub email_verified: bool,
+    #[serde(rename = "displayName", default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub locale: Option<String>,
+    #[serde(default)]
+    pub timezone: Option<String>,
+    #[serde(rename = "avatarUrl", default)]
+    pub avatar_url: Option<String>,
+    /// `active` when absent, `disabled` or `suspended`
+    #[serde(default)]
+    pub status: Option<String>,
+    /// When a suspension ends; required with `status` `suspended`
+    #[serde(rename = "suspendedUntil", default)]
+    pub suspended_until: Option<DateTime<Utc>>,
+}
+
+impl UserRecord {
+    /// Validate the record the way signup and `PATCH /me` would.
+    pub fn into_user(self) -> Result<User, &'static str> {
+        let email = Email::parse(self.email).map_err(|_| "invalid email address")?;
+        let email = match self.email_display {
+            Some(display) => match Email::parse(display) {
+                Ok(display) if display == email => display,
+                _ => return Err("emailDisplay does not match email"),
+            },
+            None => email,
+        };
+        let status = match self.status.as_deref() {
+            Some(status) => AccountStatus::from_parts(status, self.suspended_until)
+                .ok_or("invalid status or suspension end")?,
+            None => AccountStatus::Active,
+        };
+        let profile = UserProfile {
+            display_name: check(
+                self.display_name.map(|name| name.trim().to_owned()),
+                is_valid_display_name,
+                "invalid display name",
+            )?,
+            locale: check(self.locale, is_valid_locale, "invalid locale")?,
+            timezone: check(self.timezone, is_valid_timezone, "invalid timezone")?,
+            avatar_url: check(self.avatar_url, is_valid_avatar_url, "invalid avatar URL")?,
+        };
+        let mut user = User::new(
+            email,
+            Password::from_hash(self.password_hash),
+            self.requires_mfa,
+        );
+        user.email_verified = self.email_verified;
+        user.profile = profile;
+        user.status = status;
+        Ok(user)
+    }
+}
+
+impl From<User> for UserRecord {
+    /// `user` must come from the store, so its password holds the hash.
+    fn from(user: User) -> Self {
+        UserRecord {
+            email: user.email.as_ref().to_owned(),
+            email_display: Some(user.email.display().to_owned()),
+            password_hash: user.password.as_ref().to_owned(),
+            requires_mfa: user.requires_mfa,
+            email_verified: user.email_verified,
+            display_name: user.profile.display_name,
+            locale: user.profile.locale,
+            timezone: user.profile.timezone,
+            avatar_url: user.profile.avatar_url,
+            status: Some(user.status.as_str().to_owned()),
+            suspended_until: user.status.suspended_until(),
+        }
+    }
+}
+
+fn check(
+    value: Option<String>,
+    is_valid: fn(&str) -> bool,
+    error: &'static str,
+) -> Result<Option<String>, &'static str> {
+    match value {
+        Some(value) if !is_valid(&value) => Err(error),
+        value => Ok(value),
+    }
+}
+
+/// A row that was not imported, written to the rejects stream.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Rejection {
+    /// 1-based line in the input file
+    pub line: u64,
+    pub email: Option<String>,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ImportOptions {
+    /// Users per `import_users` call and transaction, at most `MAX_BATCH_SIZE`
+    pub batch_size: usize,
+    /// Check every row, including against existing accounts, without writing
+    pub dry_run: bool,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: 500,
+            dry_run: false,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ImportSummary {
+    /// Rows read from the input, excluding blank lines and the CSV header
+    pub read: usize,
+    /// Rows written, or that would have been in a dry run
+    pub imported: usize,
+    pub rejected: usize,
+}
+
+/// Import every row of `input` through `user_store`, writing the rows that
+/// could not be imported to `rejects`. Rows whose address `permits_email`
+/// refuses, normally the signup domain gate, are rejected. Only I/O failures
+/// end the job early.
+pub async fn import_users(
+    user_store: &mut dyn UserStore,
+    input: impl BufRead,
+    rejects: impl Write,
+    format: TransferFormat,
+    options: ImportOptions,
+    permits_email: impl Fn(&Email) -> bool,
+) -> Result<ImportSummary, UserTransferError> {
+    let batch_size = options.batch_size.clamp(1, MAX_BATCH_SIZE);
+    let mut reader = RecordReader::new(input, format)?;
+    let mut rejects = RecordWriter::new(rejects, format);
+    let mut summary = ImportSummary::default();
+    // Emails earlier in the file; in a dry run nothing is written, so the
+    // store alone could not spot a duplicate in a later batch
+    let mut seen = HashSet::new();
+
+    let mut end_of_input = false;
+    while !end_of_input {
+        let mut batch = Vec::with_capacity(batch_size);
+        let mut rejected = Vec::new();
+        while batch.len() < batch_size {
+            let Some((line, record)) = reader.next_record()? else {
+                end_of_input = true;
+                break;
+            };
+            summary.read += 1;
+            let email = record.as_ref().ok().map(|record| record.email.clone());
+            match record.and_then(|record| record.into_user().map_err(str::to_owned)) {
+                Ok(user) if !permits_email(&user.email) => {
+                    rejected.push(Rejection {
+                        line,
+                        email,
+                        error: "email domain is not allowed".to_owned(),
+                    });
+                }
+                Ok(user) if !seen.insert(user.email.as_ref().to_owned()) => {
+                    rejected.push(Rejection {
+                        line,
+                        email,
+                        error: "duplicate email in input".to_owned(),
+                    });
+                }
+                Ok(user) => batch.push((line, user)),
+                Err(error) => rejected.push(Rejection { line, email, error }),
+            }
+        }
+
+        if !batch.is_empty() {
+            let (lines, users): (Vec<u64>, Vec<User>) = batch.into_iter().unzip();
+            let emails: Vec<String> = users
+                .iter()
+                .map(|user| user.email.as_ref().to_owned())
+                .collect();
+            let results = match user_store.import_users(users, options.dry_run).await {
+                Ok(results) => results,
+                Err(_) => lines
+                    .iter()
+                    .map(|_| Err(UserStoreError::UnexpectedError))
+                    .collect(),
+            };
+            for ((line, email), result) in lines.into_iter().zip(emails).zip(results) {
+                let error = match result {
+                    Ok(()) => {
+                        summary.imported += 1;
+                        continue;
+                    }
+                    Err(UserStoreError::UserAlreadyExists) => "user already exists",
+                    Err(UserStoreError::UnsupportedPasswordHash) => {
+                        "unsupported password hash format or cost"
+                    }
+                    Err(_) => "batch could not be written",
+                };
+                rejected.push(Rejection {
+                    line,
+                    email: Some(email),
+                    error: error.to_owned(),
+                });
+            }
+        }
+
+        rejected.sort_by_key(|rejection| rejection.line);
+        summary.rejected += rejected.len();
+        for rejection in &rejected {
+            rejects.write(rejection)?;
+        }
+    }
+
+    rejects.finish()?;
+    Ok(summary)
+}
+
+/// Write every user in `user_store` to `output`, `batch_size` at a time.
+/// Returns how many users were written.
+pub async fn export_users(
+    user_store: &dyn UserStore,
+    output: impl Write,
+    format: TransferFormat,
+    batch_size: usize,
+) -> Result<usize, UserTransferError> {
+    let batch_size = batch_size.clamp(1, MAX_BATCH_SIZE);
+    let mut writer = RecordWriter::new(output, format);
+    let mut written = 0;
+    let mut after = None;
+    loop {
+        let users = user_store
+            .list_users(after, batch_size)
+            .await
+            .map_err(UserTransferError::Store)?;
+        let count = users.len();
+        after = users.last().map(|user| user.email.clone());
+        for user in users {
+            writer.write(&UserRecord::from(user))?;
+        }
+        written += count;
+        if count < batch_size {
+            break;
+        }
+    }
+    writer.finish()?;
+    Ok(written)
+}
+
+/// A row's line number and the record, or why it could not be parsed.
+type Row = (u64, Result<UserRecord, String>);
+
+enum RecordReader<R> {
+    Jsonl {
+        input: R,
+        line: u64,
+    },
+    Csv {
+        reader: csv::Reader<R>,
+        headers: csv::StringRecord,
+        row: csv::StringRecord,
+    },
+}
+
+impl<R: BufRead> RecordReader<R> {
+    fn new(input: R, format: TransferFormat) -> Result<Self, UserTransferError> {
+        Ok(match format {
+            TransferFormat::Jsonl => RecordReader::Jsonl { input, line: 0 },
+            TransferFormat::Csv => {
+                let mut reader = csv::ReaderBuilder::new()
+                    .trim(csv::Trim::All)
+                    .from_reader(input);
+                let headers = reader.headers()?.clone();
+                RecordReader::Csv {
+                    reader,
+                    headers,
+                    row: csv::StringRecord::new(),
+                }
+            }
+        })
+    }
+
+    /// The next row and its line number, or `None` at the end of the input.
+    /// Malformed rows come back as `Err`; only I/O errors are returned as such.
+    fn next_record(&mut self) -> Result<Option<Row>, UserTransferError> {
+        match self {
+            RecordReader::Jsonl { input, line } => {
+                let mut buffer = String::new();
+                loop {
+                    buffer.clear();
+                    if input.read_line(&mut buffer)? == 0 {
+                        return Ok(None);
+                    }
+                    *line += 1;
+                    let text = buffer.trim();
+                    if text.is_empty() {
+                        continue;
+                    }
+                    let record = serde_json::from_str::<UserRecord>(text)
+                        .map_err(|e| format!("malformed record: {}", e));
+                    return Ok(Some((*line, record)));
+                }
+            }
+            RecordReader::Csv {
+                reader,
+                headers,
+                row,
+            } => match reader.read_record(row) {
+                Ok(false) => Ok(None),
+                Ok(true) => {
+                    let line = row.position().map_or(0, |position| position.line());
+                    let record = row
+                        .deserialize::<UserRecord>(Some(headers))
+                        .map_err(|e| format!("malformed record: {}", e));
+                    Ok(Some((line, record)))
+                }
+                Err(e) if e.is_io_error() => Err(e.into()),
+                Err(e) => {
+                    let line = e.position().map_or(0, |position| position.line());
+                    Ok(Some((line, Err(format!("malformed record: {}", e)))))
+                }
+            },
+        }
+    }
+}
+
+enum RecordWriter<W: Write> {
+    Jsonl(W),
+    Csv(Box<csv::Writer<W>>),
+}
+
+impl<W: Write> RecordWriter<W> {
+    fn new(output: W, format: TransferFormat) -> Self {
+        match format {
+            TransferFormat::Jsonl => RecordWriter::Jsonl(output),
+            TransferFormat::Csv => RecordWriter::Csv(Box::new(csv::Writer::from_writer(output))),
+        }
+    }
+
+    fn write(&mut self, record: &impl Serialize) -> Result<(), UserTransferError> {
+        match self {
+            RecordWriter::Jsonl(output) => {
+                serde_json::to_writer(&mut *output, record)?;
+                output.write_all(b"\n")?;
+            }
+            RecordWriter::Csv(writer) => writer.serialize(record)?,
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<(), UserTransferError> {
+        match self {
+            RecordWriter::Jsonl(mut output) => output.flush()?,
+            RecordWriter::Csv(mut writer) => writer.flush()?,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::HashmapUserStore;
+
+    // passlib PBKDF2-SHA256 of "password", see `password_hashing` tests
+    const HASH: &str =
+        "$pbkdf2-sha256$1000$c2FsdHNhbHQ$E196ZhRPzw.wA84EjzHwJO1cv/MFJdO6C/sxmUeTYqY";
+
+    fn jsonl_user(email: &str) -> String {
+        format!(
+            r#"{{"email":"{}","passwordHash":"{}","requires2FA":true,"locale":"en-GB"}}"#,
+            email, HASH
+        )
+    }
+
+    async fn run_import(
+        store: &mut HashmapUserStore,
+        input: &str,
+        format: TransferFormat,
+        options: ImportOptions,
+    ) -> (ImportSummary, String) {
+        let mut rejects = Vec::new();
+        let permits_email = |email: &Email| email.domain() != "blocked.example";
+        let summary = import_users(
+            store,
+            input.as_bytes(),
+            &mut rejects,
+            format,
+            options,
+            permits_email,
+        )
+        .await
+        .unwrap();
+        (summary, String::from_utf8(rejects).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_imports_jsonl_and_rejects_bad_rows_without_stopping() {
+        let mut store = HashmapUserStore::new();
+        let input = [
+            jsonl_user("first@example.com"),
+            String::new(),
+            "{not json".to_owned(),
+            jsonl_user("not-an-email"),
+            jsonl_user("first@example.com"),
+            jsonl_user("second@example.com"),
+            jsonl_user("someone@blocked.example"),
+        ]
+        .join("\n");
+        let options = ImportOptions {
+            batch_size: 2,
+            ..ImportOptions::default()
+        };
+
+        let (summary, rejects) =
+            run_import(&mut store, &input, TransferFormat::Jsonl, options).await;
+
+        assert_eq!(
+            summary,
+            ImportSummary {
+                read: 6,
+                imported: 2,
+                rejected: 4
+            }
+        );
+        let rejects: Vec<Rejection> = rejects
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let lines: Vec<(u64, &str)> = rejects
+            .iter()
+            .map(|rejection| (rejection.line, rejection.error.as_str()))
+            .collect();
+        assert_eq!(lines[0].0, 3);
+        assert!(lines[0].1.starts_with("malformed record"));
+        assert_eq!(lines[1], (4, "invalid email address"));
+        assert_eq!(lines[2], (5, "duplicate email in input"));
+        assert_eq!(lines[3], (7, "email domain is not allowed"));
+
+        let user = store
+            .get_user(Email::parse("second@example.com".to_owned()).unwrap())
+            .await
+            .unwrap();
+        assert!(user.requires_mfa);
+        assert_eq!(user.password.as_ref(), HASH);
+        assert_eq!(user.profile.locale.as_deref(), Some("en-GB"));
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_checks_rows_without_writing() {
+        let mut store = HashmapUserStore::new();
+        let input = [jsonl_user("a@example.com"), jsonl_user("a@example.com")].join("\n");
+        let options = ImportOptions {
+            dry_run: true,
+            ..ImportOptions::default()
+        };
+
+        let (summary, _) = run_import(&mut store, &input, TransferFormat::Jsonl, options).await;
+
+        assert_eq!(summary.imported, 1);
+        assert_eq!(summary.rejected, 1);
+        assert_eq!(store.get_user_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_csv_round_trips_through_export() {
+        let mut store = HashmapUserStore::new();
+        let input = format!(
+            "email,passwordHash,requires2FA,displayName,avatarUrl\n\
+             one@example.com,{hash},false,One,\n\
+             two@example.com,{hash},true,,http://insecure.example.com/a.png\n\
+             three@example.com,{hash},true,,\n",
+            hash = HASH
+        );
+
+        let (summary, rejects) = run_import(
+            &mut store,
+            &input,
+            TransferFormat::Csv,
+            ImportOptions::default(),
+        )
+        .await;
+        assert_eq!(summary.imported, 2);
+        assert_eq!(
+            rejects,
+            "line,email,error\n3,two@example.com,invalid avatar URL\n"
+        );
+
+        let mut exported = Vec::new();
+        let written = export_users(&store, &mut exported, TransferFormat::Csv, 1)
+            .await
+            .unwrap();
+        assert_eq!(written, 2);
+
+        let mut copy = HashmapUserStore::new();
+        let exported = String::from_utf8(exported).unwrap();
+        let (summary, _) = run_import(
+            &mut copy,
+            &exported,
+            TransferFormat::Csv,
+            ImportOptions::default(),
+        )
+        .await;
+        assert_eq!(summary.imported, 2);
+        let one = copy
+            .get_user(Email::parse("one@example.com".to_owned()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(one.profile.display_name.as_deref(), Some("One"));
+        assert_eq!(one.profile.avatar_url, None);
+    }
+
+    #[tokio::test]
+    async fn test_carries_status_and_display_email_through_export() {
+        let mut store = HashmapUserStore::new();
+        let input = format!(
+            "email,emailDisplay,passwordHash,status,suspendedUntil\n\
+             mixed@example.com,Mixed@Example.com,{hash},suspended,2030-01-01T00:00:00Z\n\
+             off@example.com,,{hash},disabled,\n\
+             other@example.com,someone@example.com,{hash},,\n\
+             open@example.com,,{hash},suspended,\n",
+            hash = HASH
+        );
+
+        let (summary, rejects) = run_import(
+            &mut store,
+            &input,
+            TransferFormat::Csv,
+            ImportOptions::default(),
+        )
+        .await;
+        assert_eq!(summary.imported, 2);
+        assert_eq!(
+            rejects,
+            "line,email,error\n\
+             4,other@example.com,emailDisplay does not match email\n\
+             5,open@example.com,invalid status or suspension end\n"
+        );
+
+        let mut exported = Vec::new();
+        export_users(&store, &mut exported, TransferFormat::Jsonl, 10)
+            .await
+            .unwrap();
+        let mut copy = HashmapUserStore::new();
+        let exported = String::from_utf8(exported).unwrap();
+        let (summary, _) = run_import(
+            &mut copy,
+            &exported,
+            TransferFormat::Jsonl,
+            ImportOptions::default(),
+        )
+        .await;
+        assert_eq!(summary.imported, 2);
+
+        let mixed = copy
+            .get_user(Email::parse("mixed@example.com".to_owned()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(mixed.email.display(), "Mixed@Example.com");
+        assert_eq!(
+            mixed.status.suspended_until(),
+            Some("2030-01-01T00:00:00Z".parse().unwrap())
+        );
+        let off = copy
+            .get_user(Email::parse("off@example.com".to_owned()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(off.status, AccountStatus::Disabled);
+    }
+
+    #[test]
+    fn test_guesses_format_from_extension() {
+        assert_eq!(
+            TransferFormat::from_path(Path::new("users.csv")),
+            Some(TransferFormat::Csv)
+        );
+        assert_eq!(
+            TransferFormat::from_path(Path::new("users.NDJSON")),
+            Some(TransferFormat::Jsonl)
+        );
+        assert_eq!(TransferFormat::from_path(Path::new("users.txt")), None);
+    }
+}
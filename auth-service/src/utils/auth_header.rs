@@ -0,0 +1,39 @@
+use axum::http::HeaderMap;
+
+use crate::app_state::AppState;
+use crate::domain::AccessClaims;
+
+/// Extract the token from an `Authorization: Bearer <token>` header.
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+}
+
+/// Validate the bearer access token and return its claims, if any.
+pub async fn bearer_claims(state: &AppState, headers: &HeaderMap) -> Option<AccessClaims> {
+    let token = bearer_token(headers)?;
+
+    state
+        .token_service
+        .read()
+        .await
+        .validate_access(token)
+        .await
+        .ok()
+}
+
+/// True when the request carries the configured `ADMIN_API_TOKEN` as its
+/// bearer token. Always false while no admin token is configured.
+pub async fn is_admin(state: &AppState, headers: &HeaderMap) -> bool {
+    let Some(token) = bearer_token(headers) else {
+        return false;
+    };
+
+    match state.config.read().await.admin_api_token() {
+        // Compare digests so the check does not leak how much of the token matched
+        Some(expected) => blake3::hash(token.as_bytes()) == blake3::hash(expected.as_bytes()),
+        None => false,
+    }
+}
@@ -0,0 +1,55 @@
+use std::net::{IpAddr, SocketAddr};
+
+use axum::http::HeaderMap;
+
+/// Address a request came from: the last `X-Forwarded-For` entry when the
+/// proxy in front of us is trusted to set it, the peer address otherwise.
+/// The proxy appends the address it saw, so earlier entries are whatever the
+/// client chose to send and are ignored.
+pub fn client_ip(
+    headers: &HeaderMap,
+    peer: Option<SocketAddr>,
+    trust_x_forwarded_for: bool,
+) -> Option<String> {
+    let forwarded = trust_x_forwarded_for
+        .then(|| {
+            headers
+                .get_all("x-forwarded-for")
+                .iter()
+                .next_back()?
+                .to_str()
+                .ok()
+        })
+        .flatten()
+        .and_then(|value| value.rsplit(',').next())
+        .and_then(|last| last.trim().parse::<IpAddr>().ok());
+    forwarded
+        .or(peer.map(|peer| peer.ip()))
+        .map(|ip| ip.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uses_proxy_appended_forwarded_for_only_when_trusted() {
+        let mut headers = HeaderMap::new();
+        // The client sent a made-up first entry; the proxy appended the real one
+        headers.insert(
+            "x-forwarded-for",
+            "198.51.100.99, 203.0.113.7".parse().unwrap(),
+        );
+        let peer = Some(SocketAddr::from(([10, 0, 0, 2], 41000)));
+
+        assert_eq!(
+            client_ip(&headers, peer, true).as_deref(),
+            Some("203.0.113.7")
+        );
+        assert_eq!(
+            client_ip(&headers, peer, false).as_deref(),
+            Some("10.0.0.2")
+        );
+        assert_eq!(client_ip(&HeaderMap::new(), None, true), None);
+    }
+}
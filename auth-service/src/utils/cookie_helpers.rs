@@ -21,6 +21,16 @@ pub fn refresh_cookie(name: &str, token: &str, ttl_secs: i64) -> Cookie<'static>
         .build()
 }
 
+pub fn trusted_device_cookie(name: &str, token: &str, ttl_secs: i64) -> Cookie<'static> {
+    Cookie::build((name.to_string(), token.to_string()))
+        .path("/login")
+        .http_only(true)
+        .same_site(SameSite::Strict)
+        .secure(true)
+        .max_age(Duration::seconds(ttl_secs))
+        .build()
+}
+
 pub fn clear_cookie(name: &str, path: &str) -> Cookie<'static> {
     Cookie::build((name.to_owned(), String::new()))
         .path(path.to_owned())
@@ -1,7 +1,11 @@
+pub mod auth_header;
+pub mod client_ip;
 pub mod config;
 pub mod consts;
 pub mod cookie_helpers;
 
+pub use auth_header::*;
+pub use client_ip::*;
 pub use config::Config;
 pub use consts::*;
 pub use cookie_helpers::*;
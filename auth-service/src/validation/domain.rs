@@ -0,0 +1,131 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// A domain in an allow or deny list: `example.com` matches that domain
+/// only, `*.example.com` matches its subdomains at any depth but not
+/// `example.com` itself. Internationalized domains are compared in punycode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DomainPattern {
+    domain: String,
+    subdomains: bool,
+}
+
+impl DomainPattern {
+    /// Whether `domain`, in canonical form, matches the pattern.
+    pub fn matches(&self, domain: &str) -> bool {
+        if self.subdomains {
+            domain
+                .strip_suffix(self.domain.as_str())
+                .is_some_and(|prefix| prefix.len() > 1 && prefix.ends_with('.'))
+        } else {
+            domain == self.domain
+        }
+    }
+}
+
+impl FromStr for DomainPattern {
+    type Err = String;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        let pattern = pattern.trim();
+        let (domain, subdomains) = match pattern.strip_prefix("*.") {
+            Some(domain) => (domain, true),
+            None => (pattern, false),
+        };
+        canonicalize_domain(domain)
+            .map(|domain| Self { domain, subdomains })
+            .ok_or_else(|| format!("invalid domain pattern {}", pattern))
+    }
+}
+
+impl fmt::Display for DomainPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.subdomains {
+            f.write_str("*.")?;
+        }
+        f.write_str(&self.domain)
+    }
+}
+
+/// Which email domains may sign up. With an allow list, only matching
+/// domains may; a deny list match refuses a domain even if it is allowed.
+/// Both empty lets every domain through.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DomainPolicy {
+    pub allowed: Vec<DomainPattern>,
+    pub denied: Vec<DomainPattern>,
+}
+
+impl DomainPolicy {
+    pub fn permits(&self, domain: &str) -> bool {
+        (self.allowed.is_empty() || self.allowed.iter().any(|p| p.matches(domain)))
+            && !self.denied.iter().any(|p| p.matches(domain))
+    }
+}
+
+/// Parse a comma separated list of `DomainPattern`s, ignoring blank entries.
+pub fn parse_domain_patterns(list: &str) -> Result<Vec<DomainPattern>, String> {
+    list.split(',')
+        .filter(|pattern| !pattern.trim().is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+/// Lowercase ASCII form of a domain, with a trailing dot dropped. `None`
+/// unless every label is letters, digits and hyphens once converted.
+pub fn canonicalize_domain(domain: &str) -> Option<String> {
+    let domain = idna::domain_to_ascii(domain.trim().trim_end_matches('.')).ok()?;
+    let valid_label = |label: &str| {
+        !label.is_empty()
+            && label
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+    };
+    domain.split('.').all(valid_label).then_some(domain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(list: &str) -> Vec<DomainPattern> {
+        parse_domain_patterns(list).unwrap()
+    }
+
+    #[test]
+    fn test_wildcards_match_subdomains_only() {
+        let pattern: DomainPattern = "*.Example.com".parse().unwrap();
+        assert!(pattern.matches("eu.example.com"));
+        assert!(pattern.matches("mail.eu.example.com"));
+        assert!(!pattern.matches("example.com"));
+        assert!(!pattern.matches("badexample.com"));
+
+        let pattern: DomainPattern = "example.com".parse().unwrap();
+        assert!(pattern.matches("example.com"));
+        assert!(!pattern.matches("eu.example.com"));
+    }
+
+    #[test]
+    fn test_deny_list_wins_over_allow_list() {
+        let policy = DomainPolicy {
+            allowed: patterns("corp.example, *.corp.example"),
+            denied: patterns("contractors.corp.example"),
+        };
+        assert!(policy.permits("corp.example"));
+        assert!(policy.permits("eu.corp.example"));
+        assert!(!policy.permits("contractors.corp.example"));
+        assert!(!policy.permits("gmail.com"));
+        assert!(DomainPolicy::default().permits("gmail.com"));
+    }
+
+    #[test]
+    fn test_parses_idn_patterns_and_rejects_bad_ones() {
+        assert_eq!(
+            patterns(" *.Bücher.example ,, ")[0].to_string(),
+            "*.xn--bcher-kva.example"
+        );
+        assert!(parse_domain_patterns("exa mple.com").is_err());
+        assert!(parse_domain_patterns("*.*.example.com").is_err());
+        assert!(parse_domain_patterns("*.").is_err());
+    }
+}
@@ -8,3 +8,41 @@ static EMAIL_RE: Lazy<Regex> = Lazy::new(|| {
 pub fn is_valid_email(email: &str) -> bool {
     EMAIL_RE.is_match(email)
 }
+
+/// The form an address is identified by: trimmed, lowercased, and with an
+/// internationalized domain converted to punycode. The local part is
+/// lowercased too, as virtually every mail provider ignores its case. `None`
+/// if the domain is not a valid IDN.
+pub fn canonicalize_email(email: &str) -> Option<String> {
+    let (local_part, domain) = email.trim().rsplit_once('@')?;
+    let domain = idna::domain_to_ascii(domain).ok()?;
+    Some(format!("{}@{}", local_part.to_lowercase(), domain))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_form_ignores_case_and_whitespace() {
+        assert_eq!(
+            canonicalize_email("  Bob.Smith@Example.COM\n").as_deref(),
+            Some("bob.smith@example.com")
+        );
+    }
+
+    #[test]
+    fn test_converts_idn_domains_to_punycode() {
+        let canonical = canonicalize_email("anna@Bücher.example").unwrap();
+        assert_eq!(canonical, "anna@xn--bcher-kva.example");
+        assert!(is_valid_email(&canonical));
+    }
+
+    #[test]
+    fn test_rejects_addresses_without_a_usable_domain() {
+        assert_eq!(canonicalize_email("no-at-sign"), None);
+        assert!(!is_valid_email(
+            &canonicalize_email("a@exa mple.com").unwrap_or_default()
+        ));
+    }
+}
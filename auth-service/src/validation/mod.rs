@@ -1,5 +1,9 @@
+mod domain;
 mod email;
 mod password;
+mod profile;
 
+pub use domain::*;
 pub use email::*;
 pub use password::*;
+pub use profile::*;
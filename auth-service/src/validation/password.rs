@@ -1,28 +1,259 @@
-use once_cell::sync::Lazy;
-use regex::Regex;
+use std::fmt;
 
-static UPPER_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"[A-Z]").unwrap());
-static SPECIAL_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"[^A-Za-z0-9]").unwrap());
+use unicode_normalization::UnicodeNormalization;
 
-/// True if pw is ≥8 chars, has at least one uppercase and one special char
-pub fn is_valid_password(pw: &str) -> bool {
-    pw.len() >= 8 && UPPER_RE.is_match(pw) && SPECIAL_RE.is_match(pw)
+/// Hard cap on any password we are willing to hash, whatever the policy says.
+pub const MAX_PASSWORD_LENGTH: usize = 1024;
+
+/// Rules a newly chosen password must satisfy. Lengths are counted in
+/// characters after NFKC normalization.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub max_length: usize,
+    pub require_lowercase: bool,
+    pub require_uppercase: bool,
+    pub require_digit: bool,
+    pub require_special: bool,
+    /// Minimum `estimate_entropy_bits` score; 0 disables the check.
+    pub min_entropy_bits: u32,
+    /// Refuse passwords containing the local part of the account's email.
+    pub forbid_email_local_part: bool,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            max_length: 128,
+            require_lowercase: false,
+            require_uppercase: true,
+            require_digit: false,
+            require_special: true,
+            min_entropy_bits: 0,
+            forbid_email_local_part: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PasswordRuleViolation {
+    TooShort(usize),
+    TooLong(usize),
+    MissingLowercase,
+    MissingUppercase,
+    MissingDigit,
+    MissingSpecial,
+    TooWeak(u32),
+    ContainsEmail,
+}
+
+impl fmt::Display for PasswordRuleViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooShort(min) => write!(f, "must be at least {} characters long", min),
+            Self::TooLong(max) => write!(f, "must be at most {} characters long", max),
+            Self::MissingLowercase => f.write_str("must contain a lowercase letter"),
+            Self::MissingUppercase => f.write_str("must contain an uppercase letter"),
+            Self::MissingDigit => f.write_str("must contain a digit"),
+            Self::MissingSpecial => f.write_str("must contain a special character"),
+            Self::TooWeak(_) => {
+                f.write_str("is too easy to guess, use a longer or more varied password")
+            }
+            Self::ContainsEmail => f.write_str("must not contain your email address"),
+        }
+    }
+}
+
+/// Every rule a password broke, displayed as one `; `-separated list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasswordPolicyViolations(pub Vec<PasswordRuleViolation>);
+
+impl fmt::Display for PasswordPolicyViolations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, violation) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+impl PasswordPolicy {
+    /// All rules `password` violates, in a stable order. `password` must
+    /// already be normalized with `normalize_password`.
+    pub fn check(&self, password: &str, email: Option<&str>) -> Vec<PasswordRuleViolation> {
+        let mut violations = Vec::new();
+        let length = password.chars().count();
+        if length < self.min_length {
+            violations.push(PasswordRuleViolation::TooShort(self.min_length));
+        }
+        if length > self.max_length {
+            violations.push(PasswordRuleViolation::TooLong(self.max_length));
+        }
+        if self.require_lowercase && !password.chars().any(char::is_lowercase) {
+            violations.push(PasswordRuleViolation::MissingLowercase);
+        }
+        if self.require_uppercase && !password.chars().any(char::is_uppercase) {
+            violations.push(PasswordRuleViolation::MissingUppercase);
+        }
+        if self.require_digit && !password.chars().any(char::is_numeric) {
+            violations.push(PasswordRuleViolation::MissingDigit);
+        }
+        if self.require_special && !password.chars().any(|c| !c.is_alphanumeric()) {
+            violations.push(PasswordRuleViolation::MissingSpecial);
+        }
+        if self.min_entropy_bits > 0
+            && estimate_entropy_bits(password) < self.min_entropy_bits as f64
+        {
+            violations.push(PasswordRuleViolation::TooWeak(self.min_entropy_bits));
+        }
+        if self.forbid_email_local_part {
+            let local_part = email
+                .and_then(|email| email.split('@').next())
+                .map(str::to_lowercase)
+                .unwrap_or_default();
+            // Very short local parts would match far too many passwords
+            if local_part.chars().count() >= 3 && password.to_lowercase().contains(&local_part) {
+                violations.push(PasswordRuleViolation::ContainsEmail);
+            }
+        }
+        violations
+    }
+}
+
+/// NFKC form, so visually identical passwords typed on different keyboards
+/// or input methods hash the same.
+pub fn normalize_password(password: &str) -> String {
+    password.nfkc().collect()
+}
+
+/// Rough strength score in bits: the size of the character pool the password
+/// draws from, applied to its distinct characters at full weight and to
+/// repeated ones at half weight, so "aaaaaaaaaaaa" scores far below a
+/// random string of the same length.
+pub fn estimate_entropy_bits(password: &str) -> f64 {
+    let mut pool = 0u32;
+    if password.chars().any(|c| c.is_ascii_lowercase()) {
+        pool += 26;
+    }
+    if password.chars().any(|c| c.is_ascii_uppercase()) {
+        pool += 26;
+    }
+    if password.chars().any(|c| c.is_ascii_digit()) {
+        pool += 10;
+    }
+    if password
+        .chars()
+        .any(|c| c.is_ascii() && !c.is_ascii_alphanumeric())
+    {
+        pool += 33;
+    }
+    if !password.is_ascii() {
+        pool += 100;
+    }
+    if pool == 0 {
+        return 0.0;
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    let mut effective_length = 0.0;
+    for c in password.chars() {
+        effective_length += if seen.insert(c) { 1.0 } else { 0.5 };
+    }
+    effective_length * (pool as f64).log2()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[tokio::test]
-    async fn rejects_short_or_simple() {
-        assert!(!is_valid_password("Short!")); // too short
-        assert!(!is_valid_password("alllowercase!")); // no uppercase
-        assert!(!is_valid_password("NOUPPERCASE1")); // no special
+    fn violations(policy: &PasswordPolicy, password: &str) -> Vec<PasswordRuleViolation> {
+        policy.check(&normalize_password(password), Some("jane.doe@example.com"))
+    }
+
+    #[test]
+    fn test_default_policy_rejects_short_or_simple() {
+        let policy = PasswordPolicy::default();
+        assert_eq!(
+            violations(&policy, "Short!"),
+            vec![PasswordRuleViolation::TooShort(8)]
+        );
+        assert_eq!(
+            violations(&policy, "alllowercase!"),
+            vec![PasswordRuleViolation::MissingUppercase]
+        );
+        assert_eq!(
+            violations(&policy, "NOUPPERCASE1"),
+            vec![PasswordRuleViolation::MissingSpecial]
+        );
+    }
+
+    #[test]
+    fn test_default_policy_accepts_good_passwords() {
+        let policy = PasswordPolicy::default();
+        assert!(violations(&policy, "Rustacean!").is_empty());
+        assert!(violations(&policy, "P@ssW0rd123").is_empty());
+    }
+
+    #[test]
+    fn test_reports_every_violated_rule() {
+        let policy = PasswordPolicy {
+            min_length: 12,
+            require_lowercase: true,
+            require_digit: true,
+            min_entropy_bits: 60,
+            forbid_email_local_part: true,
+            ..PasswordPolicy::default()
+        };
+        assert_eq!(
+            violations(&policy, "JANE.DOE"),
+            vec![
+                PasswordRuleViolation::TooShort(12),
+                PasswordRuleViolation::MissingLowercase,
+                PasswordRuleViolation::MissingDigit,
+                PasswordRuleViolation::TooWeak(60),
+                PasswordRuleViolation::ContainsEmail,
+            ]
+        );
+        assert_eq!(
+            PasswordPolicyViolations(vec![
+                PasswordRuleViolation::TooShort(12),
+                PasswordRuleViolation::MissingDigit,
+            ])
+            .to_string(),
+            "must be at least 12 characters long; must contain a digit"
+        );
+    }
+
+    #[test]
+    fn test_enforces_max_length_in_characters() {
+        let policy = PasswordPolicy {
+            max_length: 10,
+            ..PasswordPolicy::default()
+        };
+        // 10 characters but 20 bytes
+        assert!(violations(&policy, "Ää!Ää!Ää!Ä").is_empty());
+        assert_eq!(
+            violations(&policy, "Ää!Ää!Ää!Ää"),
+            vec![PasswordRuleViolation::TooLong(10)]
+        );
+    }
+
+    #[test]
+    fn test_normalizes_compatibility_characters() {
+        // U+FB01 LATIN SMALL LIGATURE FI and fullwidth letters
+        assert_eq!(normalize_password("\u{FB01}Ｐａｓｓ!"), "fiPass!");
+        // Precomposed and decomposed forms of the same letter
+        assert_eq!(normalize_password("e\u{301}"), normalize_password("\u{E9}"));
     }
 
-    #[tokio::test]
-    async fn accepts_good_passwords() {
-        assert!(is_valid_password("Rustacean!"));
-        assert!(is_valid_password("P@ssW0rd123"));
+    #[test]
+    fn test_entropy_penalizes_repetition() {
+        assert!(estimate_entropy_bits("aaaaaaaaaaaa") < estimate_entropy_bits("qzmxkwnvrtpl"));
+        assert!(estimate_entropy_bits("Tr0ub4dor&3-horse") > 80.0);
+        assert_eq!(estimate_entropy_bits(""), 0.0);
     }
 }
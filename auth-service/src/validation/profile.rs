@@ -0,0 +1,65 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+// BCP 47 shape: language, then optional script/region/variant subtags
+static LOCALE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[A-Za-z]{2,3}(-[A-Za-z0-9]{2,8})*$").unwrap());
+// IANA zone names such as "UTC", "Europe/Berlin" or "America/Argentina/Buenos_Aires"
+static TIMEZONE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[A-Za-z][A-Za-z0-9_+-]*(/[A-Za-z0-9][A-Za-z0-9_+-]*)*$").unwrap());
+
+/// True if the name is 1-64 characters after trimming and has no control characters
+pub fn is_valid_display_name(name: &str) -> bool {
+    let name = name.trim();
+    let len = name.chars().count();
+    (1..=64).contains(&len) && !name.chars().any(char::is_control)
+}
+
+pub fn is_valid_locale(locale: &str) -> bool {
+    locale.len() <= 35 && LOCALE_RE.is_match(locale)
+}
+
+pub fn is_valid_timezone(timezone: &str) -> bool {
+    timezone.len() <= 64 && TIMEZONE_RE.is_match(timezone)
+}
+
+/// True for an absolute https URL of at most 2048 characters without whitespace
+pub fn is_valid_avatar_url(url: &str) -> bool {
+    url.len() <= 2048
+        && !url.chars().any(|c| c.is_whitespace() || c.is_control())
+        && url
+            .strip_prefix("https://")
+            .is_some_and(|rest| !rest.is_empty() && !rest.starts_with('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accepts_good_profile_values() {
+        assert!(is_valid_display_name("Ada Lovelace"));
+        assert!(is_valid_locale("en"));
+        assert!(is_valid_locale("pt-BR"));
+        assert!(is_valid_locale("zh-Hant-TW"));
+        assert!(is_valid_timezone("UTC"));
+        assert!(is_valid_timezone("America/Argentina/Buenos_Aires"));
+        assert!(is_valid_timezone("Etc/GMT+5"));
+        assert!(is_valid_avatar_url("https://cdn.example.com/a.png"));
+    }
+
+    #[test]
+    fn test_rejects_bad_profile_values() {
+        assert!(!is_valid_display_name("   "));
+        assert!(!is_valid_display_name(&"x".repeat(65)));
+        assert!(!is_valid_display_name("bad\nname"));
+        assert!(!is_valid_locale("english!"));
+        assert!(!is_valid_locale("e"));
+        assert!(!is_valid_timezone("Europe/../etc"));
+        assert!(!is_valid_timezone("/Berlin"));
+        assert!(!is_valid_avatar_url("http://cdn.example.com/a.png"));
+        assert!(!is_valid_avatar_url("javascript:alert(1)"));
+        assert!(!is_valid_avatar_url("https:///a.png"));
+        assert!(!is_valid_avatar_url("https://cdn.example.com/a b.png"));
+    }
+}
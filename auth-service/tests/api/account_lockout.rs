@@ -0,0 +1,117 @@
+use crate::helpers::{get_random_email, TestApp, TestContext, ADMIN_TOKEN};
+use test_context::test_context;
+
+const PASSWORD: &str = "Password123!";
+const WRONG_PASSWORD: &str = "WrongPassword1!";
+// LOGIN_MAX_FAILURES default
+const MAX_FAILURES: usize = 5;
+
+/// Fail the password `MAX_FAILURES` times so the account ends up locked.
+async fn lock_account(app: &TestApp, email: &str) {
+    for _ in 1..MAX_FAILURES {
+        let response = app.login(email.to_owned(), WRONG_PASSWORD.to_owned()).await;
+        assert_eq!(response.status().as_u16(), 401);
+    }
+    let response = app.login(email.to_owned(), WRONG_PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 423);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_lock_account_after_repeated_failures(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let response = app.signup(email.clone(), PASSWORD.to_owned(), false).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    lock_account(app, &email).await;
+
+    // Even the right password is refused while locked
+    let response = app.login(email.clone(), PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 423);
+    let retry_after: i64 = response
+        .headers()
+        .get("retry-after")
+        .expect("No Retry-After header")
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    // The lock ends on a whole second, so it may run up to 1s past 300
+    assert!(retry_after > 0 && retry_after <= 301);
+
+    assert!(app
+        .emails_to(&email)
+        .await
+        .iter()
+        .any(|sent| sent.subject == "your account has been locked"));
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_reset_failures_after_successful_login(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let response = app.signup(email.clone(), PASSWORD.to_owned(), false).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    for _ in 0..2 {
+        for _ in 1..MAX_FAILURES {
+            let response = app.login(email.clone(), WRONG_PASSWORD.to_owned()).await;
+            assert_eq!(response.status().as_u16(), 401);
+        }
+        let response = app.login(email.clone(), PASSWORD.to_owned()).await;
+        assert_eq!(response.status().as_u16(), 200);
+    }
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_unlock_account_as_admin(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let response = app.signup(email.clone(), PASSWORD.to_owned(), false).await;
+    assert_eq!(response.status().as_u16(), 201);
+    lock_account(app, &email).await;
+
+    let response = app.unlock_account("not-the-admin-token", &email).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.unlock_account(ADMIN_TOKEN, &email).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.login(email, PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_404_when_unlocking_unknown_user(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+
+    let response = app.unlock_account(ADMIN_TOKEN, &get_random_email()).await;
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_count_password_rechecks_towards_lockout(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let token = app.signup_and_login(&email, PASSWORD).await;
+
+    for _ in 1..MAX_FAILURES {
+        let response = app
+            .change_password(&token, WRONG_PASSWORD, "NewPassword123!")
+            .await;
+        assert_eq!(response.status().as_u16(), 401);
+    }
+    let response = app.delete_account(&token, WRONG_PASSWORD).await;
+    assert_eq!(response.status().as_u16(), 423);
+
+    // The right password no longer gets through either
+    let response = app.delete_account(&token, PASSWORD).await;
+    assert_eq!(response.status().as_u16(), 423);
+    let response = app.login(email, PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 423);
+}
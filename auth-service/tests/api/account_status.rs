@@ -0,0 +1,144 @@
+use crate::helpers::{get_random_email, TestContext, ADMIN_TOKEN};
+use auth_service::domain::{AccountStatus, AccountStatusResponse, Email, UserStore};
+use auth_service::routes::TwoFactorAuthResponse;
+use auth_service::services::SqlUserStore;
+use chrono::{Duration, Utc};
+use test_context::test_context;
+
+const PASSWORD: &str = "Password123!";
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_disable_account_and_revoke_sessions(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let token = app.signup_and_login(&email, PASSWORD).await;
+
+    let body = serde_json::json!({ "status": "disabled", "reason": "fraud review" });
+    let response = app.set_account_status(ADMIN_TOKEN, &email, &body).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response
+            .json::<AccountStatusResponse>()
+            .await
+            .expect("Could not deserialize response body to AccountStatusResponse"),
+        AccountStatusResponse {
+            email: email.clone(),
+            status: "disabled".to_owned(),
+            suspended_until: None,
+            reason: Some("fraud review".to_owned()),
+        }
+    );
+
+    let response = app.verify_token(token).await;
+    assert_eq!(response.status().as_u16(), 401);
+    let response = app.login(email.clone(), PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    let body = serde_json::json!({ "status": "active" });
+    let response = app.set_account_status(ADMIN_TOKEN, &email, &body).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let response = app.login(email, PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_refuse_login_while_suspended(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let _ = app.signup_and_login(&email, PASSWORD).await;
+
+    let until = Utc::now() + Duration::hours(1);
+    let body = serde_json::json!({ "status": "suspended", "suspendedUntil": until });
+    let response = app.set_account_status(ADMIN_TOKEN, &email, &body).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.login(email, PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert!(response.text().await.unwrap().contains("suspended until"));
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_refuse_magic_link_for_disabled_account(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let _ = app.signup_and_login(&email, PASSWORD).await;
+
+    let body = serde_json::json!({ "status": "disabled" });
+    let response = app.set_account_status(ADMIN_TOKEN, &email, &body).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.request_magic_link(email.clone()).await;
+    assert_eq!(response.status().as_u16(), 202);
+    let sent = app.emails_to(&email).await;
+    let link = &sent.last().expect("No login link was emailed").content;
+    let token = link.split_once("token=").expect("Link has no token").1;
+
+    let response = app.consume_magic_link(token).await;
+    assert_eq!(response.status().as_u16(), 403);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_refuse_pending_2fa_login_once_disabled(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let response = app.signup(email.clone(), PASSWORD.to_owned(), true).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let response = app.login(email.clone(), PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 206);
+    let login_attempt_id = response
+        .json::<TwoFactorAuthResponse>()
+        .await
+        .expect("Could not deserialize response body to TwoFactorAuthResponse")
+        .login_attempt_id;
+    let mfa_code = app.get_2fa_code(&login_attempt_id).await;
+
+    // Disabled straight in the database, as auth-admin does, which leaves
+    // the pending code in place
+    SqlUserStore::new(app.db_client.clone())
+        .set_status(
+            Email::parse(email.clone()).unwrap(),
+            AccountStatus::Disabled,
+            None,
+        )
+        .await
+        .expect("Could not disable the user");
+
+    let response = app.verify_mfa(email, login_attempt_id, mfa_code).await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert!(response.cookies().next().is_none());
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_reject_invalid_status_requests(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let _ = app.signup_and_login(&email, PASSWORD).await;
+
+    let past = Utc::now() - Duration::hours(1);
+    let cases = [
+        serde_json::json!({ "status": "banned" }),
+        serde_json::json!({ "status": "suspended" }),
+        serde_json::json!({ "status": "suspended", "suspendedUntil": past }),
+        serde_json::json!({ "status": "disabled", "suspendedUntil": Utc::now() }),
+    ];
+    for body in cases {
+        let response = app.set_account_status(ADMIN_TOKEN, &email, &body).await;
+        assert_eq!(response.status().as_u16(), 422, "body: {}", body);
+    }
+
+    let body = serde_json::json!({ "status": "disabled" });
+    let response = app
+        .set_account_status("not-the-admin-token", &email, &body)
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+    let response = app
+        .set_account_status(ADMIN_TOKEN, &get_random_email(), &body)
+        .await;
+    assert_eq!(response.status().as_u16(), 404);
+}
@@ -0,0 +1,162 @@
+use crate::helpers::{get_random_email, TestContext, ADMIN_TOKEN, DISPOSABLE_DOMAIN};
+use test_context::test_context;
+use uuid::Uuid;
+
+const PASSWORD: &str = "Password123!";
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_change_email_only_after_confirmation(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let new_email = get_random_email();
+    let token = app.signup_and_login(&email, PASSWORD).await;
+
+    let response = app.request_email_change(&token, &new_email, PASSWORD).await;
+    assert_eq!(response.status().as_u16(), 202);
+
+    // Still the old address until the link is followed
+    let response = app.login(new_email.clone(), PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 401);
+    let response = app.verify_token(token.clone()).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let confirm = app
+        .emailed_token(&new_email, "confirm your new email address")
+        .await;
+    let response = app.confirm_email_change(&confirm).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.verify_token(token).await;
+    assert_eq!(response.status().as_u16(), 401);
+    let response = app.login(email, PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 401);
+    let response = app.login(new_email, PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.confirm_email_change(&confirm).await;
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_cancel_from_old_address(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let new_email = get_random_email();
+    let token = app.signup_and_login(&email, PASSWORD).await;
+
+    let response = app.request_email_change(&token, &new_email, PASSWORD).await;
+    assert_eq!(response.status().as_u16(), 202);
+
+    let cancel = app
+        .emailed_token(&email, "your email address is changing")
+        .await;
+    let response = app.cancel_email_change(&cancel).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let confirm = app
+        .emailed_token(&new_email, "confirm your new email address")
+        .await;
+    let response = app.confirm_email_change(&confirm).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app.login(email, PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_409_if_new_email_was_taken(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let new_email = get_random_email();
+    let token = app.signup_and_login(&email, PASSWORD).await;
+
+    let response = app.request_email_change(&token, &new_email, PASSWORD).await;
+    assert_eq!(response.status().as_u16(), 202);
+    let confirm = app
+        .emailed_token(&new_email, "confirm your new email address")
+        .await;
+
+    let response = app
+        .signup(new_email.clone(), PASSWORD.to_owned(), false)
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let response = app.confirm_email_change(&confirm).await;
+    assert_eq!(response.status().as_u16(), 409);
+    // The refusal leaves the link in place rather than using it up
+    let response = app.confirm_email_change(&confirm).await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    let response = app.login(email, PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_refuse_to_confirm_for_disabled_account(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let new_email = get_random_email();
+    let token = app.signup_and_login(&email, PASSWORD).await;
+
+    let response = app.request_email_change(&token, &new_email, PASSWORD).await;
+    assert_eq!(response.status().as_u16(), 202);
+    let confirm = app
+        .emailed_token(&new_email, "confirm your new email address")
+        .await;
+
+    let body = serde_json::json!({ "status": "disabled" });
+    let response = app.set_account_status(ADMIN_TOKEN, &email, &body).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let response = app.confirm_email_change(&confirm).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    let body = serde_json::json!({ "status": "active" });
+    let response = app.set_account_status(ADMIN_TOKEN, &email, &body).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let response = app.confirm_email_change(&confirm).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_401_for_wrong_password(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let new_email = get_random_email();
+    let token = app.signup_and_login(&email, PASSWORD).await;
+
+    let response = app
+        .request_email_change(&token, &new_email, "WrongPassword1!")
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+    assert!(app.emails_to(&new_email).await.is_empty());
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_403_if_new_email_domain_is_not_allowed(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let token = app.signup_and_login(&email, PASSWORD).await;
+
+    let disposable = format!("{}@{}", Uuid::new_v4(), DISPOSABLE_DOMAIN);
+    let response = app
+        .request_email_change(&token, &disposable, PASSWORD)
+        .await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert!(app.emails_to(&disposable).await.is_empty());
+
+    app.set_config_vars(&[("SIGNUP_DENIED_DOMAINS", "denied.example")])
+        .await;
+    let denied = format!("{}@Denied.Example", Uuid::new_v4());
+    let response = app.request_email_change(&token, &denied, PASSWORD).await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(
+        response.text().await.unwrap(),
+        "addresses in this email domain are not allowed"
+    );
+}
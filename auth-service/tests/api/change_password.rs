@@ -0,0 +1,94 @@
+use crate::helpers::{get_random_email, TestContext, BREACHED_PASSWORD};
+use auth_service::domain::{ChangePasswordResponse, PasswordPolicyResponse};
+use test_context::test_context;
+
+const PASSWORD: &str = "Password123!";
+const NEW_PASSWORD: &str = "Different456!";
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_change_password_and_keep_only_current_session(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let token = app.signup_and_login(&email, PASSWORD).await;
+    let other_session = app
+        .token_service
+        .read()
+        .await
+        .issue_initial_session(&email)
+        .await
+        .expect("Failed to issue session");
+
+    let response = app.change_password(&token, PASSWORD, NEW_PASSWORD).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response
+            .json::<ChangePasswordResponse>()
+            .await
+            .expect("Could not deserialize response body to ChangePasswordResponse"),
+        ChangePasswordResponse {
+            message: "Password changed".to_owned(),
+        }
+    );
+
+    let response = app.verify_token(token).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let response = app.verify_token(other_session.access_token).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.login(email.clone(), PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 401);
+    let response = app.login(email, NEW_PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_401_for_wrong_current_password(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let token = app.signup_and_login(&email, PASSWORD).await;
+
+    let response = app
+        .change_password(&token, "WrongPassword1!", NEW_PASSWORD)
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.login(email, PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_422_for_invalid_new_password(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let token = app.signup_and_login(&email, PASSWORD).await;
+
+    let response = app.change_password(&token, PASSWORD, "weak").await;
+    assert_eq!(response.status().as_u16(), 422);
+    let body = response
+        .json::<PasswordPolicyResponse>()
+        .await
+        .expect("Could not deserialize response body to PasswordPolicyResponse");
+    assert!(body.violations.len() > 1);
+
+    let response = app
+        .change_password(&token, PASSWORD, BREACHED_PASSWORD)
+        .await;
+    assert_eq!(response.status().as_u16(), 422);
+
+    let response = app.login(email, PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_401_without_token(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+
+    let response = app
+        .change_password("not-a-token", PASSWORD, NEW_PASSWORD)
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+}
@@ -0,0 +1,106 @@
+use crate::helpers::{get_random_email, TestContext, ADMIN_TOKEN};
+use auth_service::domain::DataExport;
+use test_context::test_context;
+
+const PASSWORD: &str = "Password123!";
+
+fn access_token(response: &reqwest::Response) -> String {
+    response
+        .cookies()
+        .find(|cookie| cookie.name() == "access_token")
+        .expect("No access token cookie found")
+        .value()
+        .to_owned()
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_export_account_sessions_and_consents(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    app.set_terms_version("2025-01").await;
+    let email = get_random_email();
+    let response = app
+        .signup_accepting_terms(email.clone(), PASSWORD.to_owned(), "2025-01")
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let response = app.login(email.clone(), PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let token = access_token(&response);
+    // A second session, which logging out then revokes
+    let response = app.login(email.clone(), PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let response = app
+        .http_client
+        .post(format!("{}/logout", &app.address))
+        .bearer_auth(access_token(&response))
+        .send()
+        .await
+        .expect("Failed to execute logout request.");
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.export_me(&token).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response.headers()["content-type"].to_str().unwrap(),
+        "application/json"
+    );
+    assert!(response.headers()["content-disposition"]
+        .to_str()
+        .unwrap()
+        .starts_with("attachment; filename=\"account-data-"));
+    let body = response.text().await.unwrap();
+    assert!(!body.contains("argon2"));
+
+    let export: DataExport = serde_json::from_str(&body).unwrap();
+    assert_eq!(export.profile.email, email);
+    assert_eq!(export.profile.status, "active");
+    assert_eq!(export.login_lockout.failed_attempts, 0);
+    assert_eq!(export.sessions.len(), 2);
+    assert!(export.sessions[0].revoked_at.is_none());
+    assert!(export.sessions[1].revoked_at.is_some());
+    assert!(!export.mfa.email_code_enabled);
+    assert!(export.mfa.trusted_devices.is_empty());
+    assert_eq!(export.consents.len(), 1);
+    assert_eq!(export.consents[0].kind, "termsOfService");
+    assert_eq!(export.consents[0].version, "2025-01");
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_reject_export_without_valid_token(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let response = app.export_me("not-a-token").await;
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_let_admins_export_any_account(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let response = app.signup(email.clone(), PASSWORD.to_owned(), true).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let response = app.export_user_data("wrong-token", &email).await;
+    assert_eq!(response.status().as_u16(), 401);
+    let response = app.export_user_data(ADMIN_TOKEN, &get_random_email()).await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    let body = serde_json::json!({ "status": "disabled", "reason": "fraud review" });
+    let response = app.set_account_status(ADMIN_TOKEN, &email, &body).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.export_user_data(ADMIN_TOKEN, &email).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let export: DataExport = response.json().await.unwrap();
+    assert_eq!(export.profile.email, email);
+    assert_eq!(export.profile.status, "disabled");
+    assert_eq!(
+        export.profile.status_reason.as_deref(),
+        Some("fraud review")
+    );
+    assert!(export.mfa.email_code_enabled);
+    assert!(export.sessions.is_empty());
+    assert!(export.consents.is_empty());
+}
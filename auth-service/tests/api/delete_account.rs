@@ -0,0 +1,103 @@
+use crate::helpers::{get_random_email, TestContext};
+use auth_service::domain::DeleteAccountResponse;
+use auth_service::routes::TwoFactorAuthResponse;
+use test_context::test_context;
+
+const PASSWORD: &str = "Password123!";
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_delete_account_and_revoke_sessions(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let token = app.signup_and_login(&email, PASSWORD).await;
+    let other_session = app
+        .token_service
+        .read()
+        .await
+        .issue_initial_session(&email)
+        .await
+        .expect("Failed to issue session");
+
+    let response = app.delete_account(&token, PASSWORD).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response
+        .cookies()
+        .any(|cookie| cookie.name() == "access_token" && cookie.value().is_empty()));
+    assert_eq!(
+        response
+            .json::<DeleteAccountResponse>()
+            .await
+            .expect("Could not deserialize response body to DeleteAccountResponse"),
+        DeleteAccountResponse {
+            message: "Account deleted successfully".to_owned(),
+        }
+    );
+
+    let response = app.login(email, PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    for access_token in [token, other_session.access_token] {
+        let response = app.verify_token(access_token).await;
+        assert_eq!(response.status().as_u16(), 401);
+    }
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_purge_pending_2fa_codes(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let token = app.signup_and_login(&email, PASSWORD).await;
+    let response = app.enable_mfa(&token).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let login_attempt_id = app
+        .login(email.clone(), PASSWORD.to_owned())
+        .await
+        .json::<TwoFactorAuthResponse>()
+        .await
+        .expect("Could not deserialize response body to TwoFactorAuthResponse")
+        .login_attempt_id;
+    let mfa_code = app.get_2fa_code(&login_attempt_id).await;
+
+    let response = app.delete_account(&token, PASSWORD).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.verify_mfa(email, login_attempt_id, mfa_code).await;
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_401_and_keep_account_if_password_incorrect(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let token = app.signup_and_login(&email, PASSWORD).await;
+
+    let response = app.delete_account(&token, "Wrong123!!").await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.login(email, PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_401_if_invalid_token(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+
+    let response = app.delete_account("invalid_token", PASSWORD).await;
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_422_if_malformed_password(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let token = app.signup_and_login(&email, PASSWORD).await;
+
+    let response = app.delete_account(&token, "").await;
+    assert_eq!(response.status().as_u16(), 422);
+}
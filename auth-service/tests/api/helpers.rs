@@ -1,7 +1,10 @@
 use auth_service::{app_router, get_db_pool};
 
-use auth_service::services::{HashmapTwoFACodeStore, HashsetRefreshStore, MockEmailClient};
-use auth_service::services::{SqlUserStore, TokenService};
+use auth_service::services::{
+    BreachedPasswordFilter, DisposableDomainList, HashmapOneTimeTokenStore,
+    HashmapTrustedDeviceStore, HashmapTwoFACodeStore, HashsetRefreshStore,
+};
+use auth_service::services::{SqlInvitationStore, SqlTermsStore, SqlUserStore, TokenService};
 use reqwest::cookie::CookieStore;
 use reqwest::cookie::Jar;
 
@@ -14,11 +17,15 @@ use tokio::net::TcpListener;
 use tokio::spawn;
 use uuid::Uuid;
 
-use auth_service::app_state::{AppState, EmailClientType, TwoFACodeStoreType};
-use auth_service::domain::SignupRequestBody;
+use auth_service::app_state::{
+    AppState, OneTimeTokenStoreType, TrustedDeviceStoreType, TwoFACodeStoreType, UserStoreType,
+};
+use auth_service::domain::{Email, EmailClient, LoginAttemptId, SignupRequestBody, TwoFAPurpose};
 use auth_service::migrations;
 use auth_service::utils::Config;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
 use test_context::AsyncTestContext;
 use tokio::sync::RwLock;
 use welds::connections::any::AnyClient;
@@ -27,6 +34,11 @@ use welds::connections::any::AnyClient;
 pub struct LoginBody {
     pub email: String,
     pub password: String,
+    #[serde(
+        rename = "acceptedTermsVersion",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub accepted_terms_version: Option<String>,
 }
 
 pub struct Verify2FABody {
@@ -49,10 +61,21 @@ impl Serialize for Verify2FABody {
 }
 
 #[derive(Serialize)]
-pub struct VerifyJWTBody {
-    pub token: String,
+pub struct Resend2FABody {
+    pub email: String,
+    #[serde(rename = "loginAttemptId")]
+    pub login_attempt_id: String,
 }
 
+/// Bearer token accepted by the /admin endpoints in tests.
+pub const ADMIN_TOKEN: &str = "test_admin_token";
+
+/// Password in the breached-password filter every test app loads.
+pub const BREACHED_PASSWORD: &str = "Password1!";
+
+/// Domain in the disposable-domain list every test app loads.
+pub const DISPOSABLE_DOMAIN: &str = "mailinator.com";
+
 pub struct TestContext {
     pub test_app: TestApp,
     db_file_path: String,
@@ -100,6 +123,49 @@ impl AsyncTestContext for TestContext {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct SentEmail {
+    pub recipient: String,
+    pub subject: String,
+    pub content: String,
+}
+
+/// Keeps every email so tests can read the links and codes a user would
+/// receive.
+#[derive(Default)]
+pub struct CapturingEmailClient {
+    sent: Mutex<Vec<SentEmail>>,
+}
+
+impl CapturingEmailClient {
+    pub fn sent_to(&self, recipient: &str) -> Vec<SentEmail> {
+        self.sent
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|email| email.recipient == recipient)
+            .cloned()
+            .collect()
+    }
+}
+
+#[async_trait::async_trait]
+impl EmailClient for CapturingEmailClient {
+    async fn send_email(
+        &self,
+        recipient: &Email,
+        subject: &str,
+        content: &str,
+    ) -> Result<(), String> {
+        self.sent.lock().unwrap().push(SentEmail {
+            recipient: recipient.as_ref().to_owned(),
+            subject: subject.to_owned(),
+            content: content.to_owned(),
+        });
+        Ok(())
+    }
+}
+
 #[allow(dead_code)]
 pub struct TestApp {
     pub address: String,
@@ -108,8 +174,11 @@ pub struct TestApp {
     pub token_service: Arc<RwLock<TokenService>>,
     pub config: Arc<RwLock<Config>>,
     pub twofa_code_store: TwoFACodeStoreType,
-    pub email_client: EmailClientType,
+    pub email_client: Arc<RwLock<CapturingEmailClient>>,
     pub db_client: AnyClient,
+    pub trusted_device_store: TrustedDeviceStoreType,
+    pub one_time_token_store: OneTimeTokenStoreType,
+    config_vars: Mutex<HashMap<String, String>>,
 }
 
 #[allow(dead_code)]
@@ -140,6 +209,9 @@ impl TestApp {
         // Required because Config::default() mandates REDIS_HOST even though
         // these API tests use the in-memory refresh store implementation.
         std::env::set_var("REDIS_HOST", "127.0.0.1:6379");
+        // Let tests resend 2FA codes back to back; the resend cap still applies.
+        std::env::set_var("MFA_RESEND_COOLDOWN_SECONDS", "0");
+        std::env::set_var("ADMIN_API_TOKEN", ADMIN_TOKEN);
 
         // Create the database file if it doesn't exist
         if let Some(parent) = std::path::Path::new(db_file_path).parent() {
@@ -153,22 +225,42 @@ impl TestApp {
         let config = Arc::new(RwLock::new(
             Config::default().expect("could not start config for tests"),
         ));
+        let db_client = get_db_pool(&db_url).await.unwrap();
+        let user_store: UserStoreType = Arc::new(RwLock::new(
+            SqlUserStore::new(db_client.clone())
+                .with_argon2_params(config.read().await.argon2_params().clone()),
+        ));
         let token_service = Arc::new(RwLock::new(
-            TokenService::new(config.clone(), Box::new(HashsetRefreshStore::default())).await,
+            TokenService::new(config.clone(), Box::new(HashsetRefreshStore::default()))
+                .await
+                .with_user_store(user_store.clone()),
         ));
         let twofa_code_store = Arc::new(RwLock::new(HashmapTwoFACodeStore::default()));
-        let email_client = Arc::new(RwLock::new(MockEmailClient::default()));
-        let db_client = get_db_pool(&db_url).await.unwrap();
-        let user_store = SqlUserStore::new(db_client.clone());
+        let email_client = Arc::new(RwLock::new(CapturingEmailClient::default()));
+        let trusted_device_store = Arc::new(RwLock::new(HashmapTrustedDeviceStore::default()));
+        let one_time_token_store = Arc::new(RwLock::new(HashmapOneTimeTokenStore::default()));
+        let invitation_store = Arc::new(RwLock::new(SqlInvitationStore::new(db_client.clone())));
+        let terms_store = Arc::new(RwLock::new(SqlTermsStore::new(db_client.clone())));
+
+        let mut breached_passwords = BreachedPasswordFilter::new(16, 0.0001);
+        breached_passwords.insert_password(BREACHED_PASSWORD);
+        let mut disposable_domains = DisposableDomainList::default();
+        disposable_domains.insert_domain(DISPOSABLE_DOMAIN);
 
         let app_state = AppState::new(
-            Arc::new(RwLock::new(user_store)),
+            user_store.clone(),
             token_service.clone(),
             Arc::clone(&config),
             twofa_code_store.clone(),
             email_client.clone(),
             db_client.clone(),
-        );
+        )
+        .with_trusted_device_store(trusted_device_store.clone())
+        .with_one_time_token_store(one_time_token_store.clone())
+        .with_invitation_store(invitation_store)
+        .with_terms_store(terms_store)
+        .with_breached_passwords(Arc::new(breached_passwords))
+        .with_disposable_domains(Arc::new(disposable_domains));
         let listener = TcpListener::bind("127.0.0.1:0")
             .await
             .expect("failed binding to an ephemeral port");
@@ -176,7 +268,10 @@ impl TestApp {
         let port = listener.local_addr().unwrap().port();
         let address = format!("http://127.0.0.1:{}", port);
 
-        let server = axum::serve(listener, app_router(app_state));
+        let server = axum::serve(
+            listener,
+            app_router(app_state).into_make_service_with_connect_info::<SocketAddr>(),
+        );
 
         spawn(async move {
             if let Err(e) = server.await {
@@ -199,6 +294,9 @@ impl TestApp {
             twofa_code_store,
             email_client,
             db_client,
+            trusted_device_store,
+            one_time_token_store,
+            config_vars: Mutex::default(),
         };
 
         test_app
@@ -217,6 +315,8 @@ impl TestApp {
             email,
             password,
             requires_mfa,
+            invitation_code: None,
+            accepted_terms_version: None,
         };
 
         self.http_client
@@ -228,8 +328,56 @@ impl TestApp {
             .expect("Failed to execute signup request.")
     }
 
+    pub async fn signup_with_invitation(
+        &self,
+        email: String,
+        password: String,
+        invitation_code: &str,
+    ) -> Response {
+        let body = SignupRequestBody {
+            email,
+            password,
+            requires_mfa: false,
+            invitation_code: Some(invitation_code.to_owned()),
+            accepted_terms_version: None,
+        };
+
+        self.http_client
+            .post(format!("{}/signup", &self.address))
+            .json(&body)
+            .send()
+            .await
+            .expect("Failed to execute signup request.")
+    }
+
+    pub async fn signup_accepting_terms(
+        &self,
+        email: String,
+        password: String,
+        terms_version: &str,
+    ) -> Response {
+        let body = SignupRequestBody {
+            email,
+            password,
+            requires_mfa: false,
+            invitation_code: None,
+            accepted_terms_version: Some(terms_version.to_owned()),
+        };
+
+        self.http_client
+            .post(format!("{}/signup", &self.address))
+            .json(&body)
+            .send()
+            .await
+            .expect("Failed to execute signup request.")
+    }
+
     pub async fn login(&self, email: String, password: String) -> Response {
-        let body = LoginBody { email, password };
+        let body = LoginBody {
+            email,
+            password,
+            accepted_terms_version: None,
+        };
 
         self.http_client
             .post(&format!("{}/login", &self.address))
@@ -240,6 +388,34 @@ impl TestApp {
             .expect("Failed to execute login request.")
     }
 
+    pub async fn login_accepting_terms(
+        &self,
+        email: String,
+        password: String,
+        terms_version: &str,
+    ) -> Response {
+        let body = LoginBody {
+            email,
+            password,
+            accepted_terms_version: Some(terms_version.to_owned()),
+        };
+
+        self.http_client
+            .post(format!("{}/login", &self.address))
+            .json(&body)
+            .send()
+            .await
+            .expect("Failed to execute login request.")
+    }
+
+    pub async fn get_terms(&self) -> Response {
+        self.http_client
+            .get(format!("{}/terms", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute terms request.")
+    }
+
     pub async fn verify_mfa(
         &self,
         email: String,
@@ -261,6 +437,311 @@ impl TestApp {
             .expect("Failed to execute verify 2fa request.")
     }
 
+    pub async fn verify_mfa_trusting_device(
+        &self,
+        email: String,
+        login_attempt_id: String,
+        mfa_code: String,
+    ) -> Response {
+        self.http_client
+            .post(format!("{}/verify-2fa", &self.address))
+            .json(&serde_json::json!({
+                "email": email,
+                "loginAttemptId": login_attempt_id,
+                "2FACode": mfa_code,
+                "trustDevice": true,
+            }))
+            .send()
+            .await
+            .expect("Failed to execute verify 2fa request.")
+    }
+
+    /// Log in presenting a trusted-device cookie. The cookie is `Secure`, so the
+    /// jar would not send it to the plain-http test server on its own.
+    pub async fn login_with_device_cookie(
+        &self,
+        email: String,
+        password: String,
+        device_cookie: &str,
+    ) -> Response {
+        let body = LoginBody {
+            email,
+            password,
+            accepted_terms_version: None,
+        };
+        let cookie_name = self
+            .config
+            .read()
+            .await
+            .trusted_device_cookie_name()
+            .to_owned();
+
+        self.http_client
+            .post(format!("{}/login", &self.address))
+            .header("Cookie", format!("{}={}", cookie_name, device_cookie))
+            .json(&body)
+            .send()
+            .await
+            .expect("Failed to execute login request.")
+    }
+
+    pub async fn resend_mfa(&self, email: String, login_attempt_id: String) -> Response {
+        let body = Resend2FABody {
+            email,
+            login_attempt_id,
+        };
+
+        self.http_client
+            .post(format!("{}/resend-2fa", &self.address))
+            .json(&body)
+            .send()
+            .await
+            .expect("Failed to execute resend 2fa request.")
+    }
+
+    pub async fn enable_mfa(&self, access_token: &str) -> Response {
+        self.http_client
+            .post(format!("{}/enable-2fa", &self.address))
+            .bearer_auth(access_token)
+            .send()
+            .await
+            .expect("Failed to execute enable 2fa request.")
+    }
+
+    pub async fn disable_mfa<Body>(&self, access_token: &str, body: &Body) -> Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/disable-2fa", &self.address))
+            .bearer_auth(access_token)
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute disable 2fa request.")
+    }
+
+    pub async fn request_magic_link(&self, email: String) -> Response {
+        self.http_client
+            .post(format!("{}/magic-link", &self.address))
+            .json(&serde_json::json!({ "email": email }))
+            .send()
+            .await
+            .expect("Failed to execute magic link request.")
+    }
+
+    pub async fn consume_magic_link(&self, token: &str) -> Response {
+        self.http_client
+            .get(format!("{}/magic-link/consume", &self.address))
+            .query(&[("token", token)])
+            .send()
+            .await
+            .expect("Failed to execute consume magic link request.")
+    }
+
+    pub async fn consume_magic_link_accepting_terms(
+        &self,
+        token: &str,
+        terms_version: &str,
+    ) -> Response {
+        self.http_client
+            .get(format!("{}/magic-link/consume", &self.address))
+            .query(&[("token", token), ("acceptedTermsVersion", terms_version)])
+            .send()
+            .await
+            .expect("Failed to execute consume magic link request.")
+    }
+
+    pub async fn request_password_reset(&self, email: &str) -> Response {
+        self.http_client
+            .post(format!("{}/password-reset/request", &self.address))
+            .json(&serde_json::json!({ "email": email }))
+            .send()
+            .await
+            .expect("Failed to execute password reset request.")
+    }
+
+    pub async fn confirm_password_reset(&self, token: &str, new_password: &str) -> Response {
+        self.http_client
+            .post(format!("{}/password-reset/confirm", &self.address))
+            .json(&serde_json::json!({ "token": token, "newPassword": new_password }))
+            .send()
+            .await
+            .expect("Failed to execute password reset confirm request.")
+    }
+
+    pub async fn request_email_change(
+        &self,
+        access_token: &str,
+        new_email: &str,
+        password: &str,
+    ) -> Response {
+        self.http_client
+            .post(format!("{}/change-email", &self.address))
+            .bearer_auth(access_token)
+            .json(&serde_json::json!({ "newEmail": new_email, "password": password }))
+            .send()
+            .await
+            .expect("Failed to execute change email request.")
+    }
+
+    pub async fn confirm_email_change(&self, token: &str) -> Response {
+        self.http_client
+            .get(format!("{}/change-email/confirm", &self.address))
+            .query(&[("token", token)])
+            .send()
+            .await
+            .expect("Failed to execute confirm email change request.")
+    }
+
+    pub async fn cancel_email_change(&self, token: &str) -> Response {
+        self.http_client
+            .get(format!("{}/change-email/cancel", &self.address))
+            .query(&[("token", token)])
+            .send()
+            .await
+            .expect("Failed to execute cancel email change request.")
+    }
+
+    pub async fn get_me(&self, access_token: &str) -> Response {
+        self.http_client
+            .get(format!("{}/me", &self.address))
+            .bearer_auth(access_token)
+            .send()
+            .await
+            .expect("Failed to execute get me request.")
+    }
+
+    pub async fn export_me(&self, access_token: &str) -> Response {
+        self.http_client
+            .get(format!("{}/me/export", &self.address))
+            .bearer_auth(access_token)
+            .send()
+            .await
+            .expect("Failed to execute export me request.")
+    }
+
+    pub async fn update_me<Body>(&self, access_token: &str, body: &Body) -> Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .patch(format!("{}/me", &self.address))
+            .bearer_auth(access_token)
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute update me request.")
+    }
+
+    pub async fn change_password(
+        &self,
+        access_token: &str,
+        current_password: &str,
+        new_password: &str,
+    ) -> Response {
+        self.http_client
+            .post(format!("{}/change-password", &self.address))
+            .bearer_auth(access_token)
+            .json(&serde_json::json!({
+                "currentPassword": current_password,
+                "newPassword": new_password,
+            }))
+            .send()
+            .await
+            .expect("Failed to execute change password request.")
+    }
+
+    pub async fn unlock_account(&self, admin_token: &str, email: &str) -> Response {
+        self.http_client
+            .post(format!("{}/admin/users/{}/unlock", &self.address, email))
+            .bearer_auth(admin_token)
+            .send()
+            .await
+            .expect("Failed to execute unlock account request.")
+    }
+
+    pub async fn export_user_data(&self, admin_token: &str, email: &str) -> Response {
+        self.http_client
+            .get(format!("{}/admin/users/{}/export", &self.address, email))
+            .bearer_auth(admin_token)
+            .send()
+            .await
+            .expect("Failed to execute export user data request.")
+    }
+
+    pub async fn set_account_status<Body>(
+        &self,
+        admin_token: &str,
+        email: &str,
+        body: &Body,
+    ) -> Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .put(format!("{}/admin/users/{}/status", &self.address, email))
+            .bearer_auth(admin_token)
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute set account status request.")
+    }
+
+    pub async fn import_users<Body>(&self, admin_token: &str, body: &Body) -> Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/admin/users/import", &self.address))
+            .bearer_auth(admin_token)
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute import users request.")
+    }
+
+    pub async fn create_invitation<Body>(&self, admin_token: &str, body: &Body) -> Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/admin/invitations", &self.address))
+            .bearer_auth(admin_token)
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute create invitation request.")
+    }
+
+    pub async fn delete_account(&self, access_token: &str, password: &str) -> Response {
+        self.http_client
+            .delete(format!("{}/delete-account", &self.address))
+            .bearer_auth(access_token)
+            .json(&serde_json::json!({ "password": password }))
+            .send()
+            .await
+            .expect("Failed to execute delete account request.")
+    }
+
+    pub async fn verify_email(&self, token: &str) -> Response {
+        self.http_client
+            .get(format!("{}/verify-email", &self.address))
+            .query(&[("token", token)])
+            .send()
+            .await
+            .expect("Failed to execute verify email request.")
+    }
+
+    pub async fn resend_verification_email(&self, email: String) -> Response {
+        self.http_client
+            .post(format!("{}/verify-email/resend", &self.address))
+            .json(&serde_json::json!({ "email": email }))
+            .send()
+            .await
+            .expect("Failed to execute resend verification email request.")
+    }
+
     pub async fn logout(&self) -> Response {
         let url = Url::parse(&self.address).unwrap();
         let response = self
@@ -276,17 +757,94 @@ impl TestApp {
         response
     }
 
-    #[allow(dead_code)]
     pub async fn verify_token(&self, jwt_token: String) -> Response {
-        let body = VerifyJWTBody { token: jwt_token };
         self.http_client
             .post(&format!("{}/verify-token", &self.address))
-            .json(&body)
+            .bearer_auth(jwt_token)
             .send()
             .await
             .expect("Failed to execute verify token request.")
     }
 
+    /// Reads the pending 2FA code for a login or step-up attempt straight from
+    /// the store, standing in for the email the user would receive.
+    pub async fn get_2fa_code(&self, login_attempt_id: &str) -> String {
+        let login_attempt_id = LoginAttemptId::parse(login_attempt_id.to_string()).unwrap();
+        let store = self.twofa_code_store.read().await;
+        let (_, code) = match store.get_code(&login_attempt_id, TwoFAPurpose::Login).await {
+            Ok(pending) => pending,
+            Err(_) => store
+                .get_code(&login_attempt_id, TwoFAPurpose::DisableMfa)
+                .await
+                .unwrap(),
+        };
+        code.as_ref().to_string()
+    }
+
+    /// Sign up without 2FA and log in, returning the access token.
+    pub async fn signup_and_login(&self, email: &str, password: &str) -> String {
+        let response = self
+            .signup(email.to_owned(), password.to_owned(), false)
+            .await;
+        assert_eq!(response.status().as_u16(), 201);
+
+        let response = self.login(email.to_owned(), password.to_owned()).await;
+        assert_eq!(response.status().as_u16(), 200);
+        let token = response
+            .cookies()
+            .find(|cookie| cookie.name() == "access_token")
+            .expect("No access token cookie found")
+            .value()
+            .to_owned();
+        token
+    }
+
+    /// Token from the most recent email to `recipient` with `subject`.
+    pub async fn emailed_token(&self, recipient: &str, subject: &str) -> String {
+        let sent = self.emails_to(recipient).await;
+        let content = &sent
+            .iter()
+            .rev()
+            .find(|email| email.subject == subject)
+            .expect("Expected email was not sent")
+            .content;
+        // Links carry the token as their last query value (`?token=`, `?reset=`)
+        content
+            .rsplit_once('=')
+            .expect("Email has no token")
+            .1
+            .to_owned()
+    }
+
+    /// Rebuild the app's config from the test environment with `vars` on
+    /// top, keeping the overrides of earlier calls.
+    pub async fn set_config_vars(&self, vars: &[(&str, &str)]) {
+        let config = {
+            let mut overrides = self.config_vars.lock().unwrap();
+            overrides.extend(
+                vars.iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string())),
+            );
+            Config::from_vars(|key| {
+                overrides
+                    .get(key)
+                    .cloned()
+                    .or_else(|| std::env::var(key).ok())
+            })
+            .expect("invalid test config")
+        };
+        *self.config.write().await = config;
+    }
+
+    pub async fn set_terms_version(&self, version: &str) {
+        self.set_config_vars(&[("TERMS_VERSION", version)]).await;
+    }
+
+    /// Emails the test client has sent to `recipient`, oldest first.
+    pub async fn emails_to(&self, recipient: &str) -> Vec<SentEmail> {
+        self.email_client.read().await.sent_to(recipient)
+    }
+
     #[allow(dead_code)]
     pub async fn post_verify_2fa(
         &self,
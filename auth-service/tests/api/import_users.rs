@@ -0,0 +1,187 @@
+use crate::helpers::{get_random_email, TestContext, ADMIN_TOKEN, DISPOSABLE_DOMAIN};
+use auth_service::domain::{
+    Email, ImportRejection, ImportUsersResponse, Password, User, UserStore, UserStoreError,
+};
+use auth_service::services::SqlUserStore;
+use test_context::test_context;
+
+const PASSWORD: &str = "Password123!";
+/// "password" hashed by a passlib-style PBKDF2-SHA256 setup
+const PASSLIB_PBKDF2: &str =
+    "$pbkdf2-sha256$1000$c2FsdHNhbHQ$E196ZhRPzw.wA84EjzHwJO1cv/MFJdO6C/sxmUeTYqY";
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_import_legacy_hashes_and_upgrade_them_on_login(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let bcrypt_email = get_random_email();
+    let pbkdf2_email = get_random_email();
+    let bcrypt_hash = bcrypt::hash(PASSWORD, 4).unwrap();
+
+    let body = serde_json::json!({
+        "users": [
+            { "email": bcrypt_email, "passwordHash": bcrypt_hash, "emailVerified": true },
+            { "email": pbkdf2_email, "passwordHash": PASSLIB_PBKDF2 },
+        ]
+    });
+    let response = app.import_users(ADMIN_TOKEN, &body).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response
+            .json::<ImportUsersResponse>()
+            .await
+            .expect("Could not deserialize response body to ImportUsersResponse"),
+        ImportUsersResponse {
+            imported: 2,
+            rejected: vec![],
+        }
+    );
+
+    let store = SqlUserStore::new(app.db_client.clone());
+    let stored_hash = |user: User| user.password.as_ref().to_owned();
+    let bcrypt_user = Email::parse(bcrypt_email.clone()).unwrap();
+    let user = store.get_user(bcrypt_user.clone()).await.unwrap();
+    assert!(user.email_verified);
+    assert_eq!(stored_hash(user), bcrypt_hash);
+
+    let response = app
+        .login(bcrypt_email.clone(), "Wrong123!".to_owned())
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+    let response = app.login(bcrypt_email.clone(), PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let response = app.login(pbkdf2_email.clone(), "password".to_owned()).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let user = store.get_user(bcrypt_user).await.unwrap();
+    assert!(stored_hash(user).starts_with("$argon2id$"));
+    let user = store
+        .get_user(Email::parse(pbkdf2_email.clone()).unwrap())
+        .await
+        .unwrap();
+    assert!(stored_hash(user).starts_with("$argon2id$"));
+
+    let response = app.login(pbkdf2_email, "password".to_owned()).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_report_rejected_records_and_import_the_rest(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let existing = get_random_email();
+    let fresh = get_random_email();
+    let disposable = format!("someone@{}", DISPOSABLE_DOMAIN);
+    let response = app
+        .signup(existing.clone(), PASSWORD.to_owned(), false)
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let body = serde_json::json!({
+        "users": [
+            { "email": "not-an-email", "passwordHash": PASSLIB_PBKDF2 },
+            { "email": existing, "passwordHash": PASSLIB_PBKDF2 },
+            { "email": fresh, "passwordHash": "md5:5f4dcc3b5aa765d61d8327deb882cf99" },
+            { "email": fresh, "passwordHash": PASSLIB_PBKDF2, "requires2FA": true },
+            { "email": disposable, "passwordHash": PASSLIB_PBKDF2 },
+        ]
+    });
+    let response = app.import_users(ADMIN_TOKEN, &body).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response
+            .json::<ImportUsersResponse>()
+            .await
+            .expect("Could not deserialize response body to ImportUsersResponse"),
+        ImportUsersResponse {
+            imported: 1,
+            rejected: vec![
+                ImportRejection {
+                    index: 0,
+                    email: "not-an-email".to_owned(),
+                    error: "invalid email address".to_owned(),
+                },
+                ImportRejection {
+                    index: 1,
+                    email: existing,
+                    error: "user already exists".to_owned(),
+                },
+                ImportRejection {
+                    index: 2,
+                    email: fresh.clone(),
+                    error: "unsupported password hash format or cost".to_owned(),
+                },
+                ImportRejection {
+                    index: 4,
+                    email: disposable,
+                    error: "email domain is not allowed".to_owned(),
+                },
+            ],
+        }
+    );
+
+    let response = app.login(fresh, "password".to_owned()).await;
+    assert_eq!(response.status().as_u16(), 206);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_reject_imports_without_admin_token_or_over_the_batch_limit(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let body = serde_json::json!({
+        "users": [{ "email": email, "passwordHash": PASSLIB_PBKDF2 }]
+    });
+
+    let response = app.import_users("wrong-token", &body).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let record = serde_json::json!({ "email": email, "passwordHash": PASSLIB_PBKDF2 });
+    let body = serde_json::json!({ "users": vec![record; 1001] });
+    let response = app.import_users(ADMIN_TOKEN, &body).await;
+    assert_eq!(response.status().as_u16(), 413);
+
+    let response = app.login(email, "password".to_owned()).await;
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn sql_store_should_check_batches_against_existing_and_earlier_users(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let existing = get_random_email();
+    let fresh = Email::parse(get_random_email()).unwrap();
+    let response = app
+        .signup(existing.clone(), PASSWORD.to_owned(), false)
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let mut store = SqlUserStore::new(app.db_client.clone());
+    let imported = |email: &Email, hash: &str| {
+        User::new(email.clone(), Password::from_hash(hash.to_owned()), false)
+    };
+    let batch = vec![
+        imported(&Email::parse(existing).unwrap(), PASSLIB_PBKDF2),
+        imported(&fresh, "md5:5f4dcc3b5aa765d61d8327deb882cf99"),
+        imported(&fresh, PASSLIB_PBKDF2),
+        imported(&fresh, PASSLIB_PBKDF2),
+    ];
+    let expected = vec![
+        Err(UserStoreError::UserAlreadyExists),
+        Err(UserStoreError::UnsupportedPasswordHash),
+        Ok(()),
+        Err(UserStoreError::UserAlreadyExists),
+    ];
+
+    let results = store.import_users(batch.clone(), true).await.unwrap();
+    assert_eq!(results, expected);
+    assert_eq!(
+        store.get_user(fresh.clone()).await,
+        Err(UserStoreError::UserNotFound)
+    );
+
+    let results = store.import_users(batch, false).await.unwrap();
+    assert_eq!(results, expected);
+    let user = store.get_user(fresh).await.unwrap();
+    assert_eq!(user.password.as_ref(), PASSLIB_PBKDF2);
+}
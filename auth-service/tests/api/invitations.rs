@@ -0,0 +1,168 @@
+use crate::helpers::{get_random_email, TestApp, TestContext, ADMIN_TOKEN};
+use auth_service::domain::InvitationResponse;
+use test_context::test_context;
+use welds::Client;
+
+const PASSWORD: &str = "Password123!";
+
+async fn invite_only(app: &TestApp) {
+    app.set_config_vars(&[("INVITE_ONLY_SIGNUP", "true")]).await;
+}
+
+async fn create_invitation(app: &TestApp, body: serde_json::Value) -> InvitationResponse {
+    let response = app.create_invitation(ADMIN_TOKEN, &body).await;
+    assert_eq!(response.status().as_u16(), 201);
+    response
+        .json::<InvitationResponse>()
+        .await
+        .expect("Could not deserialize response body to InvitationResponse")
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_require_invitation_code_when_invite_only(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    invite_only(app).await;
+
+    let response = app
+        .signup(get_random_email(), PASSWORD.to_owned(), false)
+        .await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(
+        response.text().await.unwrap(),
+        "an invitation code is required to sign up"
+    );
+
+    let response = app
+        .signup_with_invitation(get_random_email(), PASSWORD.to_owned(), "not-a-code")
+        .await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(response.text().await.unwrap(), "invalid invitation code");
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_sign_up_with_invitation_until_used_up(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    invite_only(app).await;
+    let invitation = create_invitation(app, serde_json::json!({ "maxUses": 2 })).await;
+    assert_eq!(invitation.max_uses, 2);
+    assert_eq!(invitation.email, None);
+
+    for _ in 0..2 {
+        let response = app
+            .signup_with_invitation(get_random_email(), PASSWORD.to_owned(), &invitation.code)
+            .await;
+        assert_eq!(response.status().as_u16(), 201);
+    }
+
+    let response = app
+        .signup_with_invitation(get_random_email(), PASSWORD.to_owned(), &invitation.code)
+        .await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(
+        response.text().await.unwrap(),
+        "invitation code has already been used"
+    );
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_give_back_invitation_use_when_signup_fails(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let existing = get_random_email();
+    let response = app
+        .signup(existing.clone(), PASSWORD.to_owned(), false)
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    invite_only(app).await;
+    let invitation = create_invitation(app, serde_json::json!({})).await;
+
+    let response = app
+        .signup_with_invitation(existing, PASSWORD.to_owned(), &invitation.code)
+        .await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    let response = app
+        .signup_with_invitation(get_random_email(), PASSWORD.to_owned(), &invitation.code)
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_reject_expired_invitation(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    invite_only(app).await;
+    let invitation = create_invitation(app, serde_json::json!({})).await;
+
+    app.db_client
+        .execute("UPDATE invitations SET expires_at = 0", &[])
+        .await
+        .unwrap();
+
+    let response = app
+        .signup_with_invitation(get_random_email(), PASSWORD.to_owned(), &invitation.code)
+        .await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(
+        response.text().await.unwrap(),
+        "invitation code has expired"
+    );
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_email_invitation_bound_to_address(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    invite_only(app).await;
+    let email = get_random_email();
+    let invitation = create_invitation(
+        app,
+        serde_json::json!({ "email": email, "sendEmail": true }),
+    )
+    .await;
+    assert_eq!(invitation.email.as_deref(), Some(email.as_str()));
+
+    let sent = app.emails_to(&email).await;
+    let link = &sent.last().expect("No invitation was emailed").content;
+    assert_eq!(link, &invitation.signup_url);
+    let code = link.split_once("invite=").expect("Link has no code").1;
+
+    let response = app
+        .signup_with_invitation(get_random_email(), PASSWORD.to_owned(), code)
+        .await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(
+        response.text().await.unwrap(),
+        "invitation code was issued for a different email address"
+    );
+
+    let response = app
+        .signup_with_invitation(email.to_uppercase(), PASSWORD.to_owned(), code)
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_validate_invitation_requests(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+
+    let response = app
+        .create_invitation("wrong_token", &serde_json::json!({}))
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    for body in [
+        serde_json::json!({ "sendEmail": true }),
+        serde_json::json!({ "maxUses": 0 }),
+        serde_json::json!({ "expiresInSeconds": -1 }),
+        serde_json::json!({ "expiresInSeconds": i64::MAX }),
+        serde_json::json!({ "email": "not-an-email" }),
+    ] {
+        let response = app.create_invitation(ADMIN_TOKEN, &body).await;
+        assert_eq!(response.status().as_u16(), 422, "{body}");
+    }
+}
@@ -1,5 +1,8 @@
 use crate::helpers::{get_random_email, TestContext};
+use argon2::{Params, PasswordHash};
+use auth_service::domain::{Email, Password, User, UserStore};
 use auth_service::routes::TwoFactorAuthResponse;
+use auth_service::services::SqlUserStore;
 use test_context::test_context;
 
 #[test_context(TestContext)]
@@ -90,3 +93,100 @@ async fn should_return_206_if_valid_credentials_and_2fa_enabled(ctx: &mut TestCo
         "2FA required".to_owned()
     );
 }
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_upgrade_weaker_password_hash_on_login(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let password = "Password123!".to_string();
+
+    let response = app.signup(email.clone(), password.clone(), false).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    // Store the password the way an older, cheaper configuration would have
+    let parsed_email = Email::parse(email.clone()).unwrap();
+    let mut legacy_store = SqlUserStore::new(app.db_client.clone())
+        .with_argon2_params(Params::new(4096, 1, 1, None).unwrap());
+    legacy_store
+        .update_password(
+            parsed_email.clone(),
+            Password::parse(password.clone()).unwrap(),
+        )
+        .await
+        .unwrap();
+    let stored_cost = |user: User| {
+        let hash = user.password.as_ref().to_owned();
+        let params = Params::try_from(&PasswordHash::new(&hash).unwrap()).unwrap();
+        (params.m_cost(), params.t_cost(), params.p_cost())
+    };
+    let user = legacy_store.get_user(parsed_email.clone()).await.unwrap();
+    assert_eq!(stored_cost(user), (4096, 1, 1));
+
+    let response = app.login(email.clone(), password.clone()).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let configured = app.config.read().await.argon2_params().clone();
+    let user = legacy_store.get_user(parsed_email).await.unwrap();
+    assert_eq!(
+        stored_cost(user),
+        (
+            configured.m_cost(),
+            configured.t_cost(),
+            configured.p_cost()
+        )
+    );
+
+    let response = app.login(email, password).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_accept_and_normalize_passwords_hashed_before_normalization(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    // "é" as "e" plus a combining acute accent, which NFKC composes
+    let decomposed = "Cafe\u{301}Password1!".to_string();
+    let composed = "Caf\u{e9}Password1!".to_string();
+
+    let response = app
+        .signup(email.clone(), "Password123!".to_owned(), false)
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    // Older releases hashed the password exactly as typed
+    let parsed_email = Email::parse(email.clone()).unwrap();
+    let mut store = SqlUserStore::new(app.db_client.clone());
+    store
+        .update_password(
+            parsed_email.clone(),
+            Password::from_hash(decomposed.clone()),
+        )
+        .await
+        .unwrap();
+    let legacy_hash = store.get_user(parsed_email.clone()).await.unwrap().password;
+
+    let response = app.login(email.clone(), decomposed).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let user = store.get_user(parsed_email).await.unwrap();
+    assert_ne!(user.password, legacy_hash);
+
+    // The hash now covers the normalized form, which either spelling yields
+    let response = app.login(email, composed).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_login_with_email_in_any_case(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email().replace("@example.com", "@Example.com");
+    let password = "Password123!".to_string();
+
+    let response = app.signup(email.clone(), password.clone(), false).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let response = app.login(email.to_uppercase(), password).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
@@ -0,0 +1,104 @@
+use crate::helpers::{get_random_email, TestContext};
+use auth_service::routes::TwoFactorAuthResponse;
+use test_context::test_context;
+
+const PASSWORD: &str = "Password123!";
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_log_in_once_with_emailed_link(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let response = app.signup(email.clone(), PASSWORD.to_owned(), false).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let response = app.request_magic_link(email.clone()).await;
+    assert_eq!(response.status().as_u16(), 202);
+    let token = app.emailed_token(&email, "your login link").await;
+
+    let response = app.consume_magic_link(&token).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response
+        .cookies()
+        .any(|cookie| cookie.name() == "access_token" && !cookie.value().is_empty()));
+
+    let response = app.consume_magic_link(&token).await;
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_mark_email_verified(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    app.set_config_vars(&[("REQUIRE_VERIFIED_EMAIL", "true")])
+        .await;
+    let email = get_random_email();
+    let response = app.signup(email.clone(), PASSWORD.to_owned(), false).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let response = app.request_magic_link(email.clone()).await;
+    assert_eq!(response.status().as_u16(), 202);
+    let token = app.emailed_token(&email, "your login link").await;
+    let response = app.consume_magic_link(&token).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.login(email, PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_route_through_2fa_when_required(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let response = app.signup(email.clone(), PASSWORD.to_owned(), true).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let response = app.request_magic_link(email.clone()).await;
+    assert_eq!(response.status().as_u16(), 202);
+    let token = app.emailed_token(&email, "your login link").await;
+
+    let response = app.consume_magic_link(&token).await;
+    assert_eq!(response.status().as_u16(), 206);
+    assert!(response
+        .cookies()
+        .all(|cookie| cookie.name() != "access_token"));
+
+    let login_attempt_id = response
+        .json::<TwoFactorAuthResponse>()
+        .await
+        .expect("Could not deserialize response body to TwoFactorAuthResponse")
+        .login_attempt_id;
+    let mfa_code = app.get_2fa_code(&login_attempt_id).await;
+    let response = app.verify_mfa(email, login_attempt_id, mfa_code).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_202_without_email_for_unknown_account(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+
+    let response = app.request_magic_link(email.clone()).await;
+    assert_eq!(response.status().as_u16(), 202);
+    assert!(app.emails_to(&email).await.is_empty());
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_401_for_unknown_token(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+
+    let response = app.consume_magic_link("not-a-real-token").await;
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_422_for_malformed_email(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+
+    let response = app.request_magic_link("not-an-email".to_owned()).await;
+    assert_eq!(response.status().as_u16(), 422);
+}
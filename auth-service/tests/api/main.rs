@@ -1,7 +1,24 @@
+mod account_lockout;
+mod account_status;
+mod change_email;
+mod change_password;
+mod data_export;
+mod delete_account;
 mod helpers;
+mod import_users;
+mod invitations;
 mod login;
 mod logout;
+mod magic_link;
+mod me;
+mod mfa_settings;
+mod migrations;
+mod password_reset;
+mod resend_2fa;
 mod root;
 mod signup;
+mod terms;
+mod trusted_devices;
 mod verify_2fa;
+mod verify_email;
 mod verify_token;
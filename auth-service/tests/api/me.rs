@@ -0,0 +1,115 @@
+use crate::helpers::{get_random_email, TestContext};
+use auth_service::domain::MeResponse;
+use test_context::test_context;
+
+const PASSWORD: &str = "Password123!";
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_account_without_password(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let token = app.signup_and_login(&email, PASSWORD).await;
+
+    let response = app.get_me(&token).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert!(body.get("password").is_none());
+    assert!(!body.to_string().contains("argon2"));
+
+    let me: MeResponse = serde_json::from_value(body).unwrap();
+    assert_eq!(
+        me,
+        MeResponse {
+            email,
+            requires_mfa: false,
+            email_verified: false,
+            display_name: None,
+            locale: None,
+            timezone: None,
+            avatar_url: None,
+        }
+    );
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_update_and_clear_profile_fields(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let token = app.signup_and_login(&email, PASSWORD).await;
+
+    let body = serde_json::json!({
+        "displayName": "  Ada Lovelace ",
+        "locale": "en-GB",
+        "timezone": "Europe/London",
+        "avatarUrl": "https://cdn.example.com/ada.png",
+    });
+    let response = app.update_me(&token, &body).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body = serde_json::json!({ "avatarUrl": null, "locale": "fr" });
+    let response = app.update_me(&token, &body).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let me = app
+        .get_me(&token)
+        .await
+        .json::<MeResponse>()
+        .await
+        .expect("Could not deserialize response body to MeResponse");
+    assert_eq!(me.display_name.as_deref(), Some("Ada Lovelace"));
+    assert_eq!(me.locale.as_deref(), Some("fr"));
+    assert_eq!(me.timezone.as_deref(), Some("Europe/London"));
+    assert_eq!(me.avatar_url, None);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_422_listing_every_invalid_field(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let token = app.signup_and_login(&email, PASSWORD).await;
+
+    let body = serde_json::json!({
+        "displayName": "Valid Name",
+        "timezone": "../etc/passwd",
+        "avatarUrl": "javascript:alert(1)",
+    });
+    let response = app.update_me(&token, &body).await;
+    assert_eq!(response.status().as_u16(), 422);
+    let message = response.text().await.unwrap();
+    assert!(message.contains("timezone"));
+    assert!(message.contains("avatarUrl"));
+
+    // Nothing was saved
+    let me = app
+        .get_me(&token)
+        .await
+        .json::<MeResponse>()
+        .await
+        .expect("Could not deserialize response body to MeResponse");
+    assert_eq!(me.display_name, None);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_401_without_token(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+
+    let response = app.get_me("not-a-token").await;
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_email_as_typed_at_signup(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email().replace("@example.com", "@Example.com");
+    let token = app.signup_and_login(&email, PASSWORD).await;
+
+    let response = app.get_me(&token).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let me: MeResponse = response.json().await.unwrap();
+    assert_eq!(me.email, email);
+}
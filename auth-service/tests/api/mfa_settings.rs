@@ -0,0 +1,235 @@
+use crate::helpers::{get_random_email, TestApp, TestContext};
+use auth_service::domain::MfaSettingsResponse;
+use auth_service::routes::TwoFactorAuthResponse;
+use test_context::test_context;
+
+const PASSWORD: &str = "Password123!";
+
+fn access_token(response: &reqwest::Response) -> String {
+    response
+        .cookies()
+        .find(|cookie| cookie.name() == "access_token")
+        .expect("No access token cookie found")
+        .value()
+        .to_owned()
+}
+
+async fn login_attempt_id(response: reqwest::Response) -> String {
+    response
+        .json::<TwoFactorAuthResponse>()
+        .await
+        .expect("Could not deserialize response body to TwoFactorAuthResponse")
+        .login_attempt_id
+}
+
+/// Sign up a 2FA user and complete login, returning the access token.
+async fn logged_in_mfa_user(app: &TestApp, email: &str) -> String {
+    let response = app
+        .signup(email.to_owned(), PASSWORD.to_owned(), true)
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let response = app.login(email.to_owned(), PASSWORD.to_owned()).await;
+    let login_attempt_id = login_attempt_id(response).await;
+    let mfa_code = app.get_2fa_code(&login_attempt_id).await;
+
+    let response = app
+        .verify_mfa(email.to_owned(), login_attempt_id, mfa_code)
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    access_token(&response)
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_require_2fa_on_login_after_enabling(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let response = app.signup(email.clone(), PASSWORD.to_owned(), false).await;
+    assert_eq!(response.status().as_u16(), 201);
+    let response = app.login(email.clone(), PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.enable_mfa(&access_token(&response)).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response
+            .json::<MfaSettingsResponse>()
+            .await
+            .expect("Could not deserialize response body to MfaSettingsResponse"),
+        MfaSettingsResponse {
+            message: "2FA enabled".to_owned(),
+            requires_mfa: true,
+        }
+    );
+
+    let response = app.login(email, PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 206);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_disable_2fa_after_password_and_code_step_up(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let token = logged_in_mfa_user(app, &email).await;
+
+    let response = app
+        .disable_mfa(&token, &serde_json::json!({ "password": PASSWORD }))
+        .await;
+    assert_eq!(response.status().as_u16(), 206);
+    let login_attempt_id = login_attempt_id(response).await;
+    let mfa_code = app.get_2fa_code(&login_attempt_id).await;
+
+    let response = app
+        .disable_mfa(
+            &token,
+            &serde_json::json!({
+                "password": PASSWORD,
+                "loginAttemptId": login_attempt_id,
+                "2FACode": mfa_code,
+            }),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.login(email, PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_not_accept_login_and_step_up_codes_for_each_other(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let token = logged_in_mfa_user(app, &email).await;
+
+    let response = app.login(email.clone(), PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 206);
+    let login_attempt = login_attempt_id(response).await;
+    let login_code = app.get_2fa_code(&login_attempt).await;
+
+    let response = app
+        .disable_mfa(
+            &token,
+            &serde_json::json!({
+                "password": PASSWORD,
+                "loginAttemptId": login_attempt,
+                "2FACode": login_code,
+            }),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app
+        .disable_mfa(&token, &serde_json::json!({ "password": PASSWORD }))
+        .await;
+    assert_eq!(response.status().as_u16(), 206);
+    let step_up_attempt = login_attempt_id(response).await;
+    let step_up_code = app.get_2fa_code(&step_up_attempt).await;
+
+    let response = app
+        .verify_mfa(email.clone(), step_up_attempt.clone(), step_up_code.clone())
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    // Neither code was used up by the refused attempt
+    let response = app
+        .verify_mfa(email.clone(), login_attempt, login_code)
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let response = app
+        .disable_mfa(
+            &token,
+            &serde_json::json!({
+                "password": PASSWORD,
+                "loginAttemptId": step_up_attempt,
+                "2FACode": step_up_code,
+            }),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_count_wrong_step_up_passwords_towards_lockout(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let token = logged_in_mfa_user(app, &email).await;
+
+    // LOGIN_MAX_FAILURES default
+    for _ in 1..5 {
+        let response = app
+            .disable_mfa(&token, &serde_json::json!({ "password": "Wrong123!!" }))
+            .await;
+        assert_eq!(response.status().as_u16(), 401);
+    }
+    let response = app
+        .disable_mfa(&token, &serde_json::json!({ "password": "Wrong123!!" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 423);
+
+    let response = app
+        .disable_mfa(&token, &serde_json::json!({ "password": PASSWORD }))
+        .await;
+    assert_eq!(response.status().as_u16(), 423);
+    let response = app.login(email, PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 423);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_keep_2fa_if_step_up_fails(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let token = logged_in_mfa_user(app, &email).await;
+
+    let response = app
+        .disable_mfa(&token, &serde_json::json!({ "password": "Wrong123!!" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app
+        .disable_mfa(&token, &serde_json::json!({ "password": PASSWORD }))
+        .await;
+    let login_attempt_id = login_attempt_id(response).await;
+    let mfa_code = app.get_2fa_code(&login_attempt_id).await;
+    let wrong_code = if mfa_code == "000000" {
+        "111111"
+    } else {
+        "000000"
+    };
+
+    let response = app
+        .disable_mfa(
+            &token,
+            &serde_json::json!({
+                "password": PASSWORD,
+                "loginAttemptId": login_attempt_id,
+                "2FACode": wrong_code,
+            }),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.login(email, PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 206);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_401_without_valid_token(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+
+    let response = app.enable_mfa("invalid_token").await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app
+        .disable_mfa(
+            "invalid_token",
+            &serde_json::json!({ "password": PASSWORD }),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+}
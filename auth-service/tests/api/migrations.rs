@@ -0,0 +1,68 @@
+use crate::helpers::TestContext;
+use auth_service::domain::{Email, UserStore};
+use auth_service::migrations;
+use auth_service::services::SqlUserStore;
+use test_context::test_context;
+use welds::Client;
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_refuse_to_canonicalize_emails_until_collisions_are_resolved(ctx: &mut TestContext) {
+    let db = &ctx.test_app.db_client;
+    // Back to the schema from before canonicalization, with legacy rows
+    welds::migrations::down(db, "canonicalize_user_emails")
+        .await
+        .unwrap();
+    for email in [
+        "Alice@Example.com",
+        "Bob@Example.com",
+        "bob@example.com",
+        "BOB@example.com",
+    ] {
+        db.execute(
+            &format!(
+                "INSERT INTO users (email, password_hash, requires_2fa, created_at, updated_at) \
+                 VALUES ('{}', 'hash', false, 0, 0)",
+                email
+            ),
+            &[],
+        )
+        .await
+        .unwrap();
+    }
+
+    let err = migrations::up(db).await.unwrap_err();
+    assert!(matches!(
+        &err,
+        migrations::MigrationError::EmailCollisions(collisions) if collisions == &vec![vec![
+            "BOB@example.com".to_owned(),
+            "Bob@Example.com".to_owned(),
+            "bob@example.com".to_owned(),
+        ]]
+    ));
+    assert!(err
+        .to_string()
+        .starts_with("accounts BOB@example.com, Bob@Example.com, bob@example.com differ"));
+
+    db.execute(
+        "DELETE FROM users WHERE email IN ('BOB@example.com', 'bob@example.com')",
+        &[],
+    )
+    .await
+    .unwrap();
+    migrations::up(db).await.unwrap();
+    assert!(migrations::email_collisions(db).await.unwrap().is_empty());
+
+    let user_store = SqlUserStore::new(db.clone());
+    let alice = user_store
+        .get_user(Email::parse("ALICE@example.com".to_owned()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(alice.email.as_ref(), "alice@example.com");
+    assert_eq!(alice.email.display(), "Alice@Example.com");
+    let bob = user_store
+        .get_user(Email::parse("bob@example.com".to_owned()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(bob.email.display(), "Bob@Example.com");
+}
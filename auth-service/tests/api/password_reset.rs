@@ -0,0 +1,124 @@
+use crate::helpers::{get_random_email, TestContext, BREACHED_PASSWORD};
+use auth_service::domain::PasswordPolicyResponse;
+use test_context::test_context;
+
+const PASSWORD: &str = "Password123!";
+const NEW_PASSWORD: &str = "Different456!";
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_reset_password_once_and_revoke_sessions(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let response = app.signup(email.clone(), PASSWORD.to_owned(), false).await;
+    assert_eq!(response.status().as_u16(), 201);
+    let session = app
+        .token_service
+        .read()
+        .await
+        .issue_initial_session(&email)
+        .await
+        .expect("Failed to issue session");
+
+    let response = app.request_password_reset(&email).await;
+    assert_eq!(response.status().as_u16(), 202);
+    let token = app.emailed_token(&email, "reset your password").await;
+
+    let response = app.confirm_password_reset(&token, NEW_PASSWORD).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.verify_token(session.access_token).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.login(email.clone(), PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 401);
+    let response = app.login(email, NEW_PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.confirm_password_reset(&token, "Another789!").await;
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_accept_unknown_and_malformed_emails_without_sending(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+
+    for email in [get_random_email(), "not-an-email".to_owned()] {
+        let response = app.request_password_reset(&email).await;
+        assert_eq!(response.status().as_u16(), 202);
+        assert!(app.emails_to(&email).await.is_empty());
+    }
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_keep_token_when_new_password_is_invalid(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    app.set_config_vars(&[("PASSWORD_FORBID_EMAIL_LOCAL_PART", "true")])
+        .await;
+    let email = get_random_email();
+    let response = app.signup(email.clone(), PASSWORD.to_owned(), false).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let response = app.request_password_reset(&email).await;
+    assert_eq!(response.status().as_u16(), 202);
+    let token = app.emailed_token(&email, "reset your password").await;
+
+    let response = app.confirm_password_reset(&token, "weak").await;
+    assert_eq!(response.status().as_u16(), 422);
+    let body = response
+        .json::<PasswordPolicyResponse>()
+        .await
+        .expect("Could not deserialize response body to PasswordPolicyResponse");
+    assert!(body.violations.len() > 1);
+    let response = app.confirm_password_reset(&token, BREACHED_PASSWORD).await;
+    assert_eq!(response.status().as_u16(), 422);
+
+    // The email rule needs the account the token names, yet still runs first
+    let local_part = email.split('@').next().unwrap();
+    let response = app
+        .confirm_password_reset(&token, &format!("{}Aa1!", local_part))
+        .await;
+    assert_eq!(response.status().as_u16(), 422);
+    let body = response
+        .json::<PasswordPolicyResponse>()
+        .await
+        .expect("Could not deserialize response body to PasswordPolicyResponse");
+    assert_eq!(body.violations, vec!["must not contain your email address"]);
+
+    let response = app.confirm_password_reset(&token, NEW_PASSWORD).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_reject_unknown_token(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+
+    let response = app
+        .confirm_password_reset("not-a-real-token", NEW_PASSWORD)
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_link_to_the_reset_form_on_the_auth_ui(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    app.signup(email.clone(), PASSWORD.to_owned(), false).await;
+
+    app.request_password_reset(&email).await;
+    let token = app.emailed_token(&email, "reset your password").await;
+    let base_url = app.config.read().await.public_base_url().to_owned();
+    let link = format!("{}/?reset={}", base_url.trim_end_matches('/'), token);
+    assert!(app
+        .emails_to(&email)
+        .await
+        .iter()
+        .any(|sent| sent.content == link));
+
+    let page = app.get_root().await.text().await.unwrap();
+    assert!(page.contains("id=\"reset-form\""));
+}
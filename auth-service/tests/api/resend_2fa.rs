@@ -0,0 +1,84 @@
+use crate::helpers::{get_random_email, TestContext};
+use auth_service::routes::TwoFactorAuthResponse;
+use test_context::test_context;
+
+async fn login_with_2fa(app: &crate::helpers::TestApp, email: &str) -> String {
+    let password = "Password123!".to_string();
+    let response = app.signup(email.to_owned(), password.clone(), true).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let login_response = app.login(email.to_owned(), password).await;
+    assert_eq!(login_response.status().as_u16(), 206);
+    login_response
+        .json::<TwoFactorAuthResponse>()
+        .await
+        .expect("Could not deserialize response body to TwoFactorAuthResponse")
+        .login_attempt_id
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_200_and_replace_code(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let login_attempt_id = login_with_2fa(app, &email).await;
+    let first_code = app.get_2fa_code(&login_attempt_id).await;
+
+    let response = app
+        .resend_mfa(email.clone(), login_attempt_id.clone())
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let body = response
+        .json::<TwoFactorAuthResponse>()
+        .await
+        .expect("Could not deserialize response body to TwoFactorAuthResponse");
+    assert_eq!(body.login_attempt_id, login_attempt_id);
+
+    let second_code = app.get_2fa_code(&login_attempt_id).await;
+    if first_code != second_code {
+        let response = app
+            .verify_mfa(email.clone(), login_attempt_id.clone(), first_code)
+            .await;
+        assert_eq!(response.status().as_u16(), 401);
+    }
+
+    let response = app
+        .verify_mfa(email.clone(), login_attempt_id, second_code)
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_429_after_max_resends(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let login_attempt_id = login_with_2fa(app, &email).await;
+
+    let max_resends = app.config.read().await.mfa_max_resends();
+    for _ in 0..max_resends {
+        let response = app
+            .resend_mfa(email.clone(), login_attempt_id.clone())
+            .await;
+        assert_eq!(response.status().as_u16(), 200);
+    }
+
+    let response = app.resend_mfa(email, login_attempt_id).await;
+    assert_eq!(response.status().as_u16(), 429);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_401_if_unknown_attempt_or_wrong_email(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let login_attempt_id = login_with_2fa(app, &email).await;
+
+    let response = app
+        .resend_mfa(email.clone(), uuid::Uuid::new_v4().to_string())
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.resend_mfa(get_random_email(), login_attempt_id).await;
+    assert_eq!(response.status().as_u16(), 401);
+}
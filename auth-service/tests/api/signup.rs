@@ -1,6 +1,8 @@
-use crate::helpers::{get_random_email, TestContext};
+use crate::helpers::{get_random_email, TestContext, BREACHED_PASSWORD, DISPOSABLE_DOMAIN};
 use auth_service::domain::signup_response::SignupResponse;
+use auth_service::domain::PasswordPolicyResponse;
 use test_context::test_context;
+use uuid::Uuid;
 
 #[test_context(TestContext)]
 #[tokio::test]
@@ -27,6 +29,45 @@ async fn should_return_422_if_malformed_password(ctx: &mut TestContext) {
     let response = app.signup(empty_email, password, requires_mfa).await;
     assert_eq!(response.status().as_u16(), 422, "Password is too short");
 }
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_list_every_violated_password_rule(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    app.set_config_vars(&[("PASSWORD_FORBID_EMAIL_LOCAL_PART", "true")])
+        .await;
+    let email = get_random_email();
+    let local_part = email.split('@').next().unwrap().to_uppercase();
+
+    let response = app.signup(email.clone(), local_part, false).await;
+    assert_eq!(response.status().as_u16(), 422);
+    assert_eq!(
+        response
+            .json::<PasswordPolicyResponse>()
+            .await
+            .expect("Could not deserialize response body to PasswordPolicyResponse"),
+        PasswordPolicyResponse {
+            message: "password does not meet the requirements".to_owned(),
+            violations: vec!["must not contain your email address".to_owned()],
+        }
+    );
+
+    let response = app.signup(email, "short".to_owned(), false).await;
+    assert_eq!(response.status().as_u16(), 422);
+    let body = response
+        .json::<PasswordPolicyResponse>()
+        .await
+        .expect("Could not deserialize response body to PasswordPolicyResponse");
+    assert_eq!(
+        body.violations,
+        vec![
+            "must be at least 8 characters long",
+            "must contain an uppercase letter",
+            "must contain a special character",
+        ]
+    );
+}
+
 #[test_context(TestContext)]
 #[tokio::test]
 async fn should_return_201_if_fields_are_sent(ctx: &mut TestContext) {
@@ -74,3 +115,94 @@ async fn should_return_409_if_email_already_exists(ctx: &mut TestContext) {
     assert_eq!(response.status().as_u16(), 409);
     assert_eq!(response.text().await.unwrap(), expected_response);
 }
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_422_if_password_is_breached(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let random_email = get_random_email();
+
+    let response = app
+        .signup(random_email.clone(), BREACHED_PASSWORD.to_owned(), false)
+        .await;
+    assert_eq!(response.status().as_u16(), 422);
+    assert_eq!(
+        response.text().await.unwrap(),
+        "this password has appeared in a data breach, please choose a different one"
+    );
+
+    let response = app.login(random_email, BREACHED_PASSWORD.to_owned()).await;
+    assert_ne!(response.status().as_u16(), 200);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_409_if_email_differs_only_in_case(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = format!("Case.{}@Example.COM", Uuid::new_v4());
+    let password = String::from("Ilads123!");
+
+    let response = app.signup(email.clone(), password.clone(), false).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let lowercase = email.to_lowercase();
+    let response = app
+        .signup(format!("  {}", lowercase), password, false)
+        .await;
+    assert_eq!(response.status().as_u16(), 409);
+    assert_eq!(
+        response.text().await.unwrap(),
+        format!("User with email {} already exists.", lowercase)
+    );
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_403_if_email_domain_is_not_allowed(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    app.set_config_vars(&[
+        ("SIGNUP_ALLOWED_DOMAINS", "corp.example,*.corp.example"),
+        ("SIGNUP_DENIED_DOMAINS", "contractors.corp.example"),
+    ])
+    .await;
+    let password = String::from("Ilads123!");
+
+    for domain in ["corp.example", "EU.Corp.Example"] {
+        let email = format!("{}@{}", Uuid::new_v4(), domain);
+        let response = app.signup(email, password.clone(), false).await;
+        assert_eq!(response.status().as_u16(), 201, "{domain}");
+    }
+
+    for domain in [
+        "contractors.corp.example",
+        "example.com",
+        "corp.example.com",
+    ] {
+        let email = format!("{}@{}", Uuid::new_v4(), domain);
+        let response = app.signup(email, password.clone(), false).await;
+        assert_eq!(response.status().as_u16(), 403, "{domain}");
+        assert_eq!(
+            response.text().await.unwrap(),
+            "signups from this email domain are not allowed"
+        );
+    }
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_403_if_email_domain_is_disposable(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let password = String::from("Ilads123!");
+
+    for domain in [
+        DISPOSABLE_DOMAIN.to_owned(),
+        format!("eu.{}", DISPOSABLE_DOMAIN),
+    ] {
+        let email = format!("{}@{}", Uuid::new_v4(), domain.to_uppercase());
+        let response = app.signup(email.clone(), password.clone(), false).await;
+        assert_eq!(response.status().as_u16(), 403, "{domain}");
+
+        let response = app.login(email, password.clone()).await;
+        assert_ne!(response.status().as_u16(), 200);
+    }
+}
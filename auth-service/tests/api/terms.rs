@@ -0,0 +1,210 @@
+use crate::helpers::{get_random_email, TestApp, TestContext};
+use auth_service::domain::TermsResponse;
+use auth_service::routes::TermsRequiredResponse;
+use test_context::test_context;
+use welds::Client;
+
+const PASSWORD: &str = "Password123!";
+
+/// Stored `(version, ip address)` pairs, oldest first.
+async fn acceptances(app: &TestApp) -> Vec<(String, Option<String>)> {
+    app.db_client
+        .fetch_rows(
+            "SELECT terms_version, ip_address FROM terms_acceptances ORDER BY id",
+            &[],
+        )
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|row| {
+            (
+                row.get("terms_version").unwrap(),
+                row.get("ip_address").unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_require_current_terms_at_signup(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let response = app.get_terms().await;
+    assert_eq!(
+        response.json::<TermsResponse>().await.unwrap(),
+        TermsResponse {
+            version: None,
+            url: None
+        }
+    );
+
+    app.set_terms_version("2025-01").await;
+    let response = app.get_terms().await;
+    assert_eq!(
+        response.json::<TermsResponse>().await.unwrap().version,
+        Some("2025-01".to_owned())
+    );
+
+    let response = app
+        .signup(get_random_email(), PASSWORD.to_owned(), false)
+        .await;
+    assert_eq!(response.status().as_u16(), 422);
+    assert_eq!(
+        response.text().await.unwrap(),
+        "terms of service version 2025-01 must be accepted to sign up"
+    );
+    let response = app
+        .signup_accepting_terms(get_random_email(), PASSWORD.to_owned(), "2024-06")
+        .await;
+    assert_eq!(response.status().as_u16(), 422);
+
+    let email = get_random_email();
+    let response = app
+        .signup_accepting_terms(email.clone(), PASSWORD.to_owned(), "2025-01")
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+    assert_eq!(
+        acceptances(app).await,
+        vec![("2025-01".to_owned(), Some("127.0.0.1".to_owned()))]
+    );
+
+    let response = app.login(email, PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_ask_for_new_terms_at_login_until_accepted(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    app.set_terms_version("2025-01").await;
+    let response = app
+        .signup_accepting_terms(email.clone(), PASSWORD.to_owned(), "2025-01")
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    app.set_terms_version("2025-06").await;
+    let response = app.login(email.clone(), PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert!(!response
+        .cookies()
+        .any(|cookie| cookie.name() == "access_token"));
+    let body = response
+        .json::<TermsRequiredResponse>()
+        .await
+        .expect("Could not deserialize response body to TermsRequiredResponse");
+    assert_eq!(body.terms_version, "2025-06");
+
+    let response = app
+        .login_accepting_terms(email.clone(), "Wrong123!".to_owned(), "2025-06")
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+    let response = app
+        .login_accepting_terms(email.clone(), PASSWORD.to_owned(), "2025-01")
+        .await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(acceptances(app).await.len(), 1);
+
+    let response = app
+        .login_accepting_terms(email.clone(), PASSWORD.to_owned(), "2025-06")
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let response = app.login(email, PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let versions: Vec<String> = acceptances(app)
+        .await
+        .into_iter()
+        .map(|(version, _)| version)
+        .collect();
+    assert_eq!(versions, ["2025-01", "2025-06"]);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_ask_for_terms_before_2fa(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let response = app.signup(email.clone(), PASSWORD.to_owned(), true).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    app.set_terms_version("2025-06").await;
+    let response = app.login(email.clone(), PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert!(app
+        .emails_to(&email)
+        .await
+        .iter()
+        .all(|sent| sent.subject != "your 2fa code"));
+
+    let response = app
+        .login_accepting_terms(email, PASSWORD.to_owned(), "2025-06")
+        .await;
+    assert_eq!(response.status().as_u16(), 206);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_ask_for_new_terms_before_redeeming_magic_links(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let response = app.signup(email.clone(), PASSWORD.to_owned(), false).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    app.set_terms_version("2025-06").await;
+    let response = app.request_magic_link(email.clone()).await;
+    assert_eq!(response.status().as_u16(), 202);
+    let token = app.emailed_token(&email, "your login link").await;
+
+    let response = app.consume_magic_link(&token).await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert!(!response
+        .cookies()
+        .any(|cookie| cookie.name() == "access_token"));
+    let body = response
+        .json::<TermsRequiredResponse>()
+        .await
+        .expect("Could not deserialize response body to TermsRequiredResponse");
+    assert_eq!(body.terms_version, "2025-06");
+    assert!(acceptances(app).await.is_empty());
+
+    // The prompt leaves the link usable for the accepting request
+    let response = app
+        .consume_magic_link_accepting_terms(&token, "2025-06")
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        acceptances(app).await,
+        vec![("2025-06".to_owned(), Some("127.0.0.1".to_owned()))]
+    );
+
+    let response = app
+        .consume_magic_link_accepting_terms(&token, "2025-06")
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_delete_acceptances_with_the_account(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    app.set_terms_version("2025-06").await;
+    let response = app
+        .signup_accepting_terms(email.clone(), PASSWORD.to_owned(), "2025-06")
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+    assert_eq!(acceptances(app).await.len(), 1);
+
+    let response = app.login(email, PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let token = response
+        .cookies()
+        .find(|cookie| cookie.name() == "access_token")
+        .expect("No access token cookie found")
+        .value()
+        .to_owned();
+    let response = app.delete_account(&token, PASSWORD).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(acceptances(app).await.is_empty());
+}
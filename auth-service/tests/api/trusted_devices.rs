@@ -0,0 +1,133 @@
+use crate::helpers::{get_random_email, TestApp, TestContext};
+use auth_service::domain::TrustedDeviceResponse;
+use auth_service::routes::TwoFactorAuthResponse;
+use test_context::test_context;
+
+const PASSWORD: &str = "Password123!";
+
+/// Sign up a 2FA user, complete 2FA with "trust this device" and return the
+/// device cookie value together with the issued access token.
+async fn trust_new_device(app: &TestApp, email: &str) -> (String, String) {
+    let response = app
+        .signup(email.to_owned(), PASSWORD.to_owned(), true)
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let login_attempt_id = app
+        .login(email.to_owned(), PASSWORD.to_owned())
+        .await
+        .json::<TwoFactorAuthResponse>()
+        .await
+        .expect("Could not deserialize response body to TwoFactorAuthResponse")
+        .login_attempt_id;
+    let mfa_code = app.get_2fa_code(&login_attempt_id).await;
+
+    let response = app
+        .verify_mfa_trusting_device(email.to_owned(), login_attempt_id, mfa_code)
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let device_cookie = response
+        .cookies()
+        .find(|cookie| cookie.name() == "trusted_device")
+        .expect("No trusted device cookie found");
+    assert!(device_cookie.http_only());
+    let access_token = response
+        .cookies()
+        .find(|cookie| cookie.name() == "access_token")
+        .expect("No access token cookie found");
+
+    (
+        device_cookie.value().to_owned(),
+        access_token.value().to_owned(),
+    )
+}
+
+async fn list_devices(app: &TestApp, access_token: &str) -> reqwest::Response {
+    app.http_client
+        .get(format!("{}/trusted-devices", &app.address))
+        .header("Authorization", format!("Bearer {}", access_token))
+        .send()
+        .await
+        .expect("Failed to execute list trusted devices request.")
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_skip_2fa_on_trusted_device_until_revoked(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let (device_cookie, access_token) = trust_new_device(app, &email).await;
+
+    let response = app
+        .login_with_device_cookie(email.clone(), PASSWORD.to_owned(), &device_cookie)
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let devices = list_devices(app, &access_token)
+        .await
+        .json::<Vec<TrustedDeviceResponse>>()
+        .await
+        .expect("Could not deserialize response body to trusted devices");
+    assert_eq!(devices.len(), 1);
+
+    let response = app
+        .http_client
+        .delete(format!(
+            "{}/trusted-devices/{}",
+            &app.address, devices[0].device_id
+        ))
+        .header("Authorization", format!("Bearer {}", access_token))
+        .send()
+        .await
+        .expect("Failed to execute revoke trusted device request.");
+    assert_eq!(response.status().as_u16(), 204);
+
+    let response = app
+        .login_with_device_cookie(email, PASSWORD.to_owned(), &device_cookie)
+        .await;
+    assert_eq!(response.status().as_u16(), 206);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_require_2fa_if_device_trusted_by_another_user(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let (device_cookie, _) = trust_new_device(app, &get_random_email()).await;
+
+    let other_email = get_random_email();
+    let response = app
+        .signup(other_email.clone(), PASSWORD.to_owned(), true)
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let response = app
+        .login_with_device_cookie(other_email, PASSWORD.to_owned(), &device_cookie)
+        .await;
+    assert_eq!(response.status().as_u16(), 206);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_require_2fa_if_device_cookie_tampered(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let (device_cookie, _) = trust_new_device(app, &email).await;
+
+    let (_, mac) = device_cookie.split_once('.').unwrap();
+    let forged = format!("{}.{}", uuid::Uuid::new_v4(), mac);
+
+    let response = app
+        .login_with_device_cookie(email, PASSWORD.to_owned(), &forged)
+        .await;
+    assert_eq!(response.status().as_u16(), 206);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_401_listing_devices_without_token(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+
+    let response = list_devices(app, "invalid_token").await;
+    assert_eq!(response.status().as_u16(), 401);
+}
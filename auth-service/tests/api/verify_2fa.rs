@@ -6,7 +6,7 @@ use test_context::test_context;
 #[test_context(TestContext)]
 #[tokio::test]
 async fn should_return_401_if_old_code(ctx: &mut TestContext) {
-    // Call login twice. Then, attempt to call verify-fa with the 2FA code from the first login requet. This should fail.
+    // Verify a login once, then try to reuse the same code. The second attempt should fail.
     let app = &ctx.test_app;
     let email = get_random_email();
     let password = "Password123!".to_string();
@@ -18,34 +18,82 @@ async fn should_return_401_if_old_code(ctx: &mut TestContext) {
         .await;
     assert_eq!(response.status().as_u16(), 201);
 
-    // First login - this will generate a 2FA code
-    let login_response_1 = app.login(email.clone(), password.clone()).await;
-    assert_eq!(login_response_1.status().as_u16(), 206, "first assert");
+    let login_response = app.login(email.clone(), password.clone()).await;
+    assert_eq!(login_response.status().as_u16(), 206);
 
-    let first_login_response = login_response_1
+    let login_attempt_id = login_response
         .json::<TwoFactorAuthResponse>()
         .await
-        .expect("Could not deserialize response body to TwoFactorAuthResponse");
+        .expect("Could not deserialize response body to TwoFactorAuthResponse")
+        .login_attempt_id;
+    let mfa_code = app.get_2fa_code(&login_attempt_id).await;
 
-    let first_login_attempt_id = first_login_response.login_attempt_id;
+    let verify_response = app
+        .verify_mfa(email.clone(), login_attempt_id.clone(), mfa_code.clone())
+        .await;
+    assert_eq!(verify_response.status().as_u16(), 200);
 
-    // Get the first 2FA code from the store
-    let first_2fa_code = {
-        let email_parsed = auth_service::domain::Email::parse(email.clone()).unwrap();
-        let store = app.twofa_code_store.read().await;
-        let (_, code) = store.get_code(&email_parsed).await.unwrap();
-        code.as_ref().to_string()
-    };
+    // The challenge is consumed, so the same code cannot be used again
+    let verify_response = app
+        .verify_mfa(email.clone(), login_attempt_id, mfa_code)
+        .await;
+
+    assert_eq!(verify_response.status().as_u16(), 401);
+}
 
-    // Second login - this will generate a new 2FA code and overwrite the first one
-    let login_response_2 = app.login(email.clone(), password.clone()).await;
-    let login_response_2_status = login_response_2.status().as_u16();
-    let _login_response_2_body = login_response_2.text().await.unwrap();
-    assert_eq!(login_response_2_status, 206, "second assert");
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_200_for_each_concurrent_login(ctx: &mut TestContext) {
+    // Log in twice (e.g. laptop and phone) before verifying either. Both should succeed.
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let password = "Password123!".to_string();
+
+    let response = app.signup(email.clone(), password.clone(), true).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let mut attempts = Vec::new();
+    for _ in 0..2 {
+        let login_response = app.login(email.clone(), password.clone()).await;
+        assert_eq!(login_response.status().as_u16(), 206);
+        let login_attempt_id = login_response
+            .json::<TwoFactorAuthResponse>()
+            .await
+            .expect("Could not deserialize response body to TwoFactorAuthResponse")
+            .login_attempt_id;
+        let mfa_code = app.get_2fa_code(&login_attempt_id).await;
+        attempts.push((login_attempt_id, mfa_code));
+    }
+
+    for (login_attempt_id, mfa_code) in attempts {
+        let verify_response = app
+            .verify_mfa(email.clone(), login_attempt_id, mfa_code)
+            .await;
+        assert_eq!(verify_response.status().as_u16(), 200);
+    }
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_401_if_attempt_belongs_to_another_email(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let password = "Password123!".to_string();
+
+    let response = app.signup(email.clone(), password.clone(), true).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let login_attempt_id = app
+        .login(email.clone(), password.clone())
+        .await
+        .json::<TwoFactorAuthResponse>()
+        .await
+        .expect("Could not deserialize response body to TwoFactorAuthResponse")
+        .login_attempt_id;
+    let mfa_code = app.get_2fa_code(&login_attempt_id).await;
 
-    // Now try to verify 2FA with the old code from the first login
     let verify_response = app
-        .verify_mfa(email.clone(), first_login_attempt_id, first_2fa_code)
+        .verify_mfa(get_random_email(), login_attempt_id, mfa_code)
         .await;
 
     assert_eq!(verify_response.status().as_u16(), 401);
@@ -90,12 +138,7 @@ async fn should_return_200_if_correct_code(ctx: &mut TestContext) {
     let login_attempt_id = two_factor_response.login_attempt_id;
 
     // Get the 2FA code from the store
-    let mfa_code = {
-        let email_parsed = auth_service::domain::Email::parse(email.clone()).unwrap();
-        let store = app.twofa_code_store.read().await;
-        let (_, code) = store.get_code(&email_parsed).await.unwrap();
-        code.as_ref().to_string()
-    };
+    let mfa_code = app.get_2fa_code(&login_attempt_id).await;
 
     // Verify 2FA with the correct code
     let verify_response = app
@@ -125,3 +168,47 @@ async fn should_return_200_if_correct_code(ctx: &mut TestContext) {
 
     assert_eq!(login_response_body.message, "MFA verification successful");
 }
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_429_and_invalidate_attempt_after_too_many_incorrect_codes(
+    ctx: &mut TestContext,
+) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let password = "Password123!".to_string();
+
+    let response = app.signup(email.clone(), password.clone(), true).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let login_response = app.login(email.clone(), password.clone()).await;
+    assert_eq!(login_response.status().as_u16(), 206);
+    let login_attempt_id = login_response
+        .json::<TwoFactorAuthResponse>()
+        .await
+        .expect("Could not deserialize response body to TwoFactorAuthResponse")
+        .login_attempt_id;
+
+    let mfa_code = app.get_2fa_code(&login_attempt_id).await;
+
+    // Generated codes are always in 100000..=999999, so this one never matches
+    let wrong_code = "000000".to_string();
+    let max_attempts = app.config.read().await.mfa_max_attempts();
+    for _ in 1..max_attempts {
+        let response = app
+            .verify_mfa(email.clone(), login_attempt_id.clone(), wrong_code.clone())
+            .await;
+        assert_eq!(response.status().as_u16(), 401);
+    }
+
+    let response = app
+        .verify_mfa(email.clone(), login_attempt_id.clone(), wrong_code)
+        .await;
+    assert_eq!(response.status().as_u16(), 429);
+
+    // The attempt is gone, so even the right code no longer works
+    let response = app
+        .verify_mfa(email.clone(), login_attempt_id, mfa_code)
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+}
@@ -0,0 +1,90 @@
+use crate::helpers::{get_random_email, TestApp, TestContext};
+use test_context::test_context;
+
+const PASSWORD: &str = "Password123!";
+
+async fn require_verified_email(app: &TestApp) {
+    app.set_config_vars(&[("REQUIRE_VERIFIED_EMAIL", "true")])
+        .await;
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_refuse_login_until_email_verified(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    require_verified_email(app).await;
+    let email = get_random_email();
+    let response = app.signup(email.clone(), PASSWORD.to_owned(), false).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let response = app.login(email.clone(), PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    let token = app
+        .emailed_token(&email, "confirm your email address")
+        .await;
+    let response = app.verify_email(&token).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.login(email, PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_allow_unverified_login_when_not_required(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let response = app.signup(email.clone(), PASSWORD.to_owned(), false).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let response = app.login(email, PASSWORD.to_owned()).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_return_400_for_tampered_token(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let response = app.signup(email.clone(), PASSWORD.to_owned(), false).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let token = app
+        .emailed_token(&email, "confirm your email address")
+        .await;
+    let (_, rest) = token.split_once('.').unwrap();
+    let forged = format!("{}.{}", "b3RoZXJAZXhhbXBsZS5jb20", rest);
+
+    let response = app.verify_email(&forged).await;
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[test_context(TestContext)]
+#[tokio::test]
+async fn should_resend_link_only_to_unverified_accounts(ctx: &mut TestContext) {
+    let app = &ctx.test_app;
+    let email = get_random_email();
+    let response = app.signup(email.clone(), PASSWORD.to_owned(), false).await;
+    assert_eq!(response.status().as_u16(), 201);
+    assert_eq!(app.emails_to(&email).await.len(), 1);
+
+    let response = app.resend_verification_email(email.clone()).await;
+    assert_eq!(response.status().as_u16(), 202);
+    assert_eq!(app.emails_to(&email).await.len(), 2);
+
+    let token = app
+        .emailed_token(&email, "confirm your email address")
+        .await;
+    let response = app.verify_email(&token).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.resend_verification_email(email.clone()).await;
+    assert_eq!(response.status().as_u16(), 202);
+    assert_eq!(app.emails_to(&email).await.len(), 2);
+
+    let unknown = get_random_email();
+    let response = app.resend_verification_email(unknown.clone()).await;
+    assert_eq!(response.status().as_u16(), 202);
+    assert!(app.emails_to(&unknown).await.is_empty());
+}
@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+use auth_service::services::BreachedPasswordFilter;
+use uuid::Uuid;
+
+fn auth_admin(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_auth-admin"))
+        .args(args)
+        .output()
+        .expect("failed to run auth-admin")
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("auth-admin-{}-{}", Uuid::new_v4(), name))
+}
+
+#[test]
+fn breached_passwords_build_writes_a_loadable_filter() {
+    let hibp = temp_path("hibp.txt");
+    let out = temp_path("breached.bin");
+    // SHA-1 of "password" and "123456"
+    std::fs::write(
+        &hibp,
+        "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:9545824\n\
+         7C4A8D09CA3762AF61E59520943DC26494F8941B:2254650\n",
+    )
+    .unwrap();
+
+    let output = auth_admin(&[
+        "breached-passwords",
+        "build",
+        hibp.to_str().unwrap(),
+        out.to_str().unwrap(),
+    ]);
+    assert!(
+        output.status.success(),
+        "auth-admin failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("wrote 2 hash(es)"));
+
+    let filter = BreachedPasswordFilter::load(&out).expect("filter should load");
+    assert!(filter.contains_password("password"));
+    assert!(filter.contains_password("123456"));
+    assert!(!filter.contains_password("Tr0ub4dor&3-horse-staple"));
+
+    let _ = std::fs::remove_file(hibp);
+    let _ = std::fs::remove_file(out);
+}
+
+#[test]
+fn breached_passwords_build_rejects_malformed_lists() {
+    let hibp = temp_path("hibp.txt");
+    let out = temp_path("breached.bin");
+    std::fs::write(&hibp, "not-a-digest:12\n").unwrap();
+
+    let output = auth_admin(&[
+        "breached-passwords",
+        "build",
+        hibp.to_str().unwrap(),
+        out.to_str().unwrap(),
+    ]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("expected a SHA-1 hex digest"));
+    assert!(!out.exists());
+
+    let _ = std::fs::remove_file(hibp);
+}
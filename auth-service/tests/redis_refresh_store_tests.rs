@@ -227,3 +227,30 @@ async fn double_revoke_is_idempotent() {
         "session should remain revoked"
     );
 }
+
+#[test]
+async fn legacy_sessions_are_revoked_and_their_history_kept() {
+    let mut store = new_store();
+    let local = Uuid::new_v4().simple().to_string();
+    let mut record = make_record(&random_plain(), 300).await;
+    record.user_id = format!("{}@Example.COM", local);
+    let session_id = record.session_id;
+    store.insert_initial(record).await.expect("insert");
+
+    let revoked = store
+        .revoke_legacy_sessions(Utc::now())
+        .await
+        .expect("revoke legacy sessions");
+    assert!(revoked >= 1);
+    assert!(store.is_session_revoked(session_id).await);
+
+    let canonical = format!("{}@example.com", local);
+    let history = store.session_history(&canonical).await.unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].session_id, session_id);
+    assert!(store
+        .user_sessions(&canonical, Utc::now())
+        .await
+        .unwrap()
+        .is_empty());
+}
@@ -1,10 +1,17 @@
 use std::sync::Arc;
 
 use base64::{engine::general_purpose::STANDARD as B64, Engine};
+use chrono::{DateTime, Duration, Utc};
 use rand::RngCore;
 use tokio::sync::RwLock;
+use uuid::Uuid;
 
-use auth_service::domain::RefreshError;
+use auth_service::app_state::UserStoreType;
+use auth_service::domain::{
+    AccountStatus, Email, Password, RefreshError, RefreshRecord, RefreshStore, SessionSummary,
+    User, UserStore,
+};
+use auth_service::services::data_stores::hashmap_user_store::HashmapUserStore;
 use auth_service::services::data_stores::hashset_refresh_store::HashsetRefreshStore;
 use auth_service::services::token_service::AccessError;
 use auth_service::services::TokenService;
@@ -176,6 +183,100 @@ async fn logout_session_revokes_access_and_future_refresh() {
     );
 }
 
+#[tokio::test]
+async fn revoke_user_sessions_keeps_only_the_given_session() {
+    let svc = build_token_service().await;
+    let kept = svc
+        .issue_initial_session("multi")
+        .await
+        .expect("issue kept");
+    let other = svc
+        .issue_initial_session("multi")
+        .await
+        .expect("issue other");
+    let stranger = svc
+        .issue_initial_session("stranger")
+        .await
+        .expect("issue stranger");
+
+    let revoked = svc
+        .revoke_user_sessions("multi", Some(kept.session_id))
+        .await
+        .expect("revoke sessions");
+    assert_eq!(revoked, 1);
+
+    assert!(svc.validate_access(&kept.access_token).await.is_ok());
+    assert!(matches!(
+        svc.validate_access(&other.access_token).await,
+        Err(AccessError::RevokedSession)
+    ));
+    assert!(svc.validate_access(&stranger.access_token).await.is_ok());
+    assert_eq!(
+        svc.user_sessions("multi").await.expect("list sessions"),
+        vec![kept.session_id]
+    );
+}
+
+/// A refresh store whose backend is down.
+struct UnavailableRefreshStore;
+
+#[async_trait::async_trait]
+impl RefreshStore for UnavailableRefreshStore {
+    async fn insert_initial(&mut self, _record: RefreshRecord) -> Result<(), RefreshError> {
+        Err(RefreshError::Internal)
+    }
+    async fn find_record(
+        &self,
+        _presented_plain: &str,
+        _hash_key: &[u8; 32],
+    ) -> Result<RefreshRecord, RefreshError> {
+        Err(RefreshError::Internal)
+    }
+    async fn rotate(
+        &mut self,
+        _presented_plain: &str,
+        _new_plain: &str,
+        _now: DateTime<Utc>,
+        _ttl: Duration,
+        _hash_key: &[u8; 32],
+    ) -> Result<(RefreshRecord, RefreshRecord), RefreshError> {
+        Err(RefreshError::Internal)
+    }
+    async fn revoke_session(&mut self, _session_id: Uuid, _now: DateTime<Utc>) {}
+    async fn revoke_session_internal(&mut self, _session_id: Uuid, _now: DateTime<Utc>) {}
+    async fn is_session_revoked(&self, _session_id: Uuid) -> bool {
+        false
+    }
+    async fn user_sessions(
+        &self,
+        _user_id: &str,
+        _now: DateTime<Utc>,
+    ) -> Result<Vec<Uuid>, RefreshError> {
+        Err(RefreshError::Internal)
+    }
+    async fn session_history(&self, _user_id: &str) -> Result<Vec<SessionSummary>, RefreshError> {
+        Err(RefreshError::Internal)
+    }
+}
+
+#[tokio::test]
+async fn session_lookups_report_store_failures() {
+    set_env_config();
+    let cfg = Arc::new(RwLock::new(
+        Config::default().expect("failed to build test config"),
+    ));
+    let svc = TokenService::new(cfg, Box::new(UnavailableRefreshStore)).await;
+
+    assert!(matches!(
+        svc.revoke_user_sessions("anyone", None).await,
+        Err(RefreshError::Internal)
+    ));
+    assert!(matches!(
+        svc.session_history("anyone").await,
+        Err(RefreshError::Internal)
+    ));
+}
+
 #[tokio::test]
 async fn multiple_sequential_refreshes_work() {
     let svc = build_token_service().await;
@@ -247,3 +348,37 @@ async fn refresh_with_unknown_token_fails() {
         res
     );
 }
+
+#[tokio::test]
+async fn refresh_is_refused_and_session_revoked_for_inactive_account() {
+    let email = Email::parse("blocked@example.com".to_owned()).unwrap();
+    let password = Password::parse("Password123!".to_owned()).unwrap();
+    let mut users = HashmapUserStore::new();
+    users
+        .add_user(User::new(email.clone(), password, false))
+        .await
+        .unwrap();
+    let users: UserStoreType = Arc::new(RwLock::new(users));
+    let svc = build_token_service().await.with_user_store(users.clone());
+
+    let issued = svc.issue_initial_session(email.as_ref()).await.unwrap();
+    let active = svc.refresh(&issued.refresh_token).await.unwrap();
+
+    users
+        .write()
+        .await
+        .set_status(email, AccountStatus::Disabled, None)
+        .await
+        .unwrap();
+
+    let res = svc.refresh(&active.refresh_token).await;
+    assert!(
+        matches!(res, Err(RefreshError::AccountInactive)),
+        "expected AccountInactive, got {:?}",
+        res
+    );
+    assert!(matches!(
+        svc.validate_access(&active.access_token).await,
+        Err(AccessError::RevokedSession)
+    ));
+}